@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::solver::assemble_initial_condition::{assemble_initial_condition, assemble_random_initial_condition, assemble_rectangular_seed, assemble_ring_initial_condition, assemble_weighted_initial_condition};
+use crate::solver::{HaltCondition, particle_system_solver, RecordCondition, SolverOptions};
+use crate::solver::graph::{Graph, bipartite::CompleteBipartite, cayley_tree::CayleyTree, configuration_model::ConfigurationModel, diluted_lattice::DilutedLattice, erdos_renyi::ErdosRenyi, grid_n_d::GridND, hypercube::Hypercube, kagome::KagomeLattice, random_geometric::RandomGeometric, ring_lattice::RingLattice, sbm::StochasticBlockModel, star::StarGraph};
+use crate::solver::ips_rules::{IPSRules, anti_voter::AntiVoter, asymmetric_two_si::AsymmetricTwoSI, deffuant::Deffuant, forest_fire::ForestFire, linear_voter::LinearVoter, moran_process::MoranProcess, multistrain::MultiStrainContact, noisy_voter::NoisyVoter, potts::PottsModel, schelling::Schelling, seir_partial::SEIRPartial, si_process::SIProcess, sir_process::SIRProcess, sirs_process::SIRSProcess, two_si_process::TwoSIProcess, voter_process::VoterProcess};
+use crate::visualization::{Coloration, save_as_gif, save_as_growth_img, save_frames_as_pngs};
+
+/// A whole simulation, as defined in a config file, as an alternative to the command line
+/// arguments. Run it with `run_from_spec`. The parameters are the same as those of the command
+/// line arguments, see the help function of the CLI.
+///
+/// # Example
+/// A pretty SIR process development, in TOML
+/// ```toml
+/// halt = { TimePassed = 200.0 }
+/// record = { ConstantTime = 0.1 }
+///
+/// [graph]
+/// kind = "grid_nd"
+/// dimensions = [200, 200]
+///
+/// [ips]
+/// kind = "sir"
+/// birth_rate = 1.0
+/// death_rate = 0.8
+///
+/// [initial]
+/// kind = "different_particles"
+/// state = 1
+/// particles = [20100]
+///
+/// [output]
+/// kind = "gif"
+/// path = "sir 200x200.gif"
+/// height = 200
+/// ms_per_frame = 20
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct SimulationSpec {
+    pub graph: GraphSpec,
+    pub ips: IPSSpec,
+    pub initial: InitialSpec,
+    pub halt: HaltCondition,
+    pub record: RecordCondition,
+    pub output: OutputSpec,
+}
+
+/// The graph of a `SimulationSpec`, with the parameters of the corresponding command line
+/// arguments.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GraphSpec {
+    GridNd { dimensions: Vec<usize>, #[serde(default)] moore: bool },
+    ErdosRenyi { nr_points: usize, avg_neighbors: f64 },
+    DilutedLattice { dim_x: usize, dim_y: usize, probability: f64, probability_vertical: Option<f64> },
+    CayleyTree { coordination: usize, depth: usize },
+    RingLattice { nr_points: usize, k: usize },
+    StochasticBlock { p_in: f64, p_out: f64, block_sizes: Vec<usize> },
+    RandomGeometric { nr_points: usize, radius: f64 },
+    Hypercube { dimension: usize },
+    CompleteBipartite { m: usize, n: usize },
+    Star { nr_leaves: usize },
+    Kagome { dim_x: usize, dim_y: usize },
+    ConfigurationModel { degrees: Vec<usize> },
+}
+
+impl GraphSpec {
+    /// Construct the graph. Random graphs are sampled anew on every call.
+    pub fn build(&self) -> Box<dyn Graph> {
+        match self {
+            GraphSpec::GridNd { dimensions, moore } => {
+                if *moore {
+                    Box::new(GridND::new_moore(dimensions.clone(), vec![true; dimensions.len()]))
+                } else {
+                    Box::new(GridND::from(dimensions.clone()))
+                }
+            }
+            GraphSpec::ErdosRenyi { nr_points, avg_neighbors } => {
+                Box::new(ErdosRenyi::new(*nr_points, avg_neighbors / *nr_points as f64, rand::thread_rng()))
+            }
+            GraphSpec::DilutedLattice { dim_x, dim_y, probability, probability_vertical } => {
+                Box::new(DilutedLattice::new_anisotropic(*dim_x, *dim_y, *probability,
+                                                         probability_vertical.unwrap_or(*probability),
+                                                         rand::thread_rng()))
+            }
+            GraphSpec::CayleyTree { coordination, depth } => {
+                Box::new(CayleyTree::new(*coordination, *depth))
+            }
+            GraphSpec::RingLattice { nr_points, k } => {
+                Box::new(RingLattice::new(*nr_points, *k))
+            }
+            GraphSpec::StochasticBlock { p_in, p_out, block_sizes } => {
+                Box::new(StochasticBlockModel::new(block_sizes.clone(), *p_in, *p_out, rand::thread_rng()))
+            }
+            GraphSpec::RandomGeometric { nr_points, radius } => {
+                Box::new(RandomGeometric::new(*nr_points, *radius, rand::thread_rng()))
+            }
+            GraphSpec::Hypercube { dimension } => {
+                Box::new(Hypercube::new(*dimension))
+            }
+            GraphSpec::CompleteBipartite { m, n } => {
+                Box::new(CompleteBipartite::new(*m, *n))
+            }
+            GraphSpec::Star { nr_leaves } => {
+                Box::new(StarGraph::new(*nr_leaves))
+            }
+            GraphSpec::Kagome { dim_x, dim_y } => {
+                Box::new(KagomeLattice::new(*dim_x, *dim_y, true))
+            }
+            GraphSpec::ConfigurationModel { degrees } => {
+                Box::new(ConfigurationModel::new(degrees.clone(), rand::thread_rng()))
+            }
+        }
+    }
+
+    /// Width of the graph when drawn as an image, which is only possible for 1D and 2D grids.
+    fn image_width(&self) -> Option<u32> {
+        match self {
+            GraphSpec::GridNd { dimensions, .. } if dimensions.len() <= 2 => Some(dimensions[0] as u32),
+            GraphSpec::DilutedLattice { dim_x, .. } => Some(*dim_x as u32),
+            _ => None,
+        }
+    }
+}
+
+/// The interacting particle system of a `SimulationSpec`, with the parameters of the
+/// corresponding command line arguments.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IPSSpec {
+    Si { birth_rate: f64, death_rate: f64, #[serde(default)] spontaneous_rate: f64 },
+    Sir { birth_rate: f64, death_rate: f64 },
+    Sirs { birth_rate: f64, death_rate: f64, waning_rate: f64 },
+    Voter { nr_parties: usize, change_rate: f64 },
+    LinearVoter { nr_parties: usize },
+    TwoSi { birth_rate: f64, death_rate: f64, compete_rate: f64 },
+    TwoSiAsymmetric { birth1: f64, death1: f64, birth2: f64, death2: f64, compete12: f64, compete21: f64 },
+    SeirPartial { birth_i: f64, birth_e_fraction: f64, incubation_rate: f64, recovery_rate: f64 },
+    Antivoter { nr_parties: usize, rate: f64 },
+    Deffuant { nr_bins: usize, confidence: usize, mu: f64 },
+    Moran { fitness: Vec<f64> },
+    NoisyVoter { nr_parties: usize, copy_rate: f64, noise_rate: f64 },
+    Potts { q: usize, beta: f64 },
+    Schelling { tolerance: f64 },
+    ForestFire { growth_rate: f64, lightning_rate: f64, burn_rate: f64 },
+    Multistrain { birth_rates: Vec<f64>, death_rates: Vec<f64> },
+}
+
+impl IPSSpec {
+    /// Construct the rules of the particle system, and their coloration.
+    pub fn build(&self) -> (Box<dyn IPSRules>, Box<dyn Coloration>) {
+        match self {
+            IPSSpec::Si { birth_rate, death_rate, spontaneous_rate } => {
+                rules_and_coloration(|| SIProcess { birth_rate: *birth_rate, death_rate: *death_rate, spontaneous_rate: *spontaneous_rate })
+            }
+            IPSSpec::Sir { birth_rate, death_rate } => {
+                rules_and_coloration(|| SIRProcess { birth_rate: *birth_rate, death_rate: *death_rate })
+            }
+            IPSSpec::Sirs { birth_rate, death_rate, waning_rate } => {
+                rules_and_coloration(|| SIRSProcess { birth_rate: *birth_rate, death_rate: *death_rate, waning_rate: *waning_rate })
+            }
+            IPSSpec::Voter { nr_parties, change_rate } => {
+                rules_and_coloration(|| VoterProcess { nr_parties: *nr_parties, change_rate: *change_rate })
+            }
+            IPSSpec::LinearVoter { nr_parties } => {
+                rules_and_coloration(|| LinearVoter { nr_parties: *nr_parties })
+            }
+            IPSSpec::TwoSi { birth_rate, death_rate, compete_rate } => {
+                rules_and_coloration(|| TwoSIProcess { birth_rate: *birth_rate, death_rate: *death_rate, compete_rate: *compete_rate })
+            }
+            IPSSpec::TwoSiAsymmetric { birth1, death1, birth2, death2, compete12, compete21 } => {
+                rules_and_coloration(|| AsymmetricTwoSI {
+                    birth1: *birth1,
+                    death1: *death1,
+                    birth2: *birth2,
+                    death2: *death2,
+                    compete12: *compete12,
+                    compete21: *compete21,
+                })
+            }
+            IPSSpec::SeirPartial { birth_i, birth_e_fraction, incubation_rate, recovery_rate } => {
+                rules_and_coloration(|| SEIRPartial {
+                    birth_i: *birth_i,
+                    birth_e_fraction: *birth_e_fraction,
+                    incubation_rate: *incubation_rate,
+                    recovery_rate: *recovery_rate,
+                })
+            }
+            IPSSpec::Antivoter { nr_parties, rate } => {
+                rules_and_coloration(|| AntiVoter { nr_parties: *nr_parties, rate: *rate })
+            }
+            IPSSpec::Deffuant { nr_bins, confidence, mu } => {
+                rules_and_coloration(|| Deffuant { nr_bins: *nr_bins, confidence: *confidence, mu: *mu })
+            }
+            IPSSpec::Moran { fitness } => {
+                rules_and_coloration(|| MoranProcess { fitness: fitness.clone() })
+            }
+            IPSSpec::NoisyVoter { nr_parties, copy_rate, noise_rate } => {
+                rules_and_coloration(|| NoisyVoter { nr_parties: *nr_parties, copy_rate: *copy_rate, noise_rate: *noise_rate })
+            }
+            IPSSpec::Potts { q, beta } => {
+                rules_and_coloration(|| PottsModel { q: *q, beta: *beta })
+            }
+            IPSSpec::Schelling { tolerance } => {
+                rules_and_coloration(|| Schelling { tolerance: *tolerance })
+            }
+            IPSSpec::ForestFire { growth_rate, lightning_rate, burn_rate } => {
+                rules_and_coloration(|| ForestFire { growth_rate: *growth_rate, lightning_rate: *lightning_rate, burn_rate: *burn_rate })
+            }
+            IPSSpec::Multistrain { birth_rates, death_rates } => {
+                assert_eq!(birth_rates.len(), death_rates.len()); // every strain needs both rates
+                rules_and_coloration(|| MultiStrainContact { birth_rates: birth_rates.clone(), death_rates: death_rates.clone() })
+            }
+        }
+    }
+}
+
+impl IPSSpec {
+    /// The same particle system, with the rate parameter named `param` (e.g., "birth_rate" for the
+    /// SI process) set to `value`.
+    ///
+    /// # Errors
+    /// Returns an error if the particle system has no rate parameter of that name.
+    pub fn with_parameter(&self, param: &str, value: f64) -> io::Result<IPSSpec> {
+        let mut fields = serde_json::to_value(self)?;
+
+        match fields.get_mut(param) {
+            Some(field) if field.is_f64() => { *field = value.into(); }
+            _ => {
+                return Err(io::Error::new(ErrorKind::InvalidInput,
+                                          format!("The particle system has no rate parameter {}!", param)));
+            }
+        }
+
+        Ok(serde_json::from_value(fields)?)
+    }
+}
+
+/// The particle systems serve as their own coloration, so make two of them.
+fn rules_and_coloration<T: IPSRules + Coloration + 'static>(make: impl Fn() -> T) -> (Box<dyn IPSRules>, Box<dyn Coloration>) {
+    (Box::new(make()), Box::new(make()))
+}
+
+/// The initial condition of a `SimulationSpec`, with the parameters of the corresponding command
+/// line arguments. The particles which are not specified are in the state 0.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InitialSpec {
+    Random,
+    DifferentParticles { state: usize, particles: Vec<usize> },
+    Ring { state: usize, center: usize, radius: usize },
+    /// Only for 2D grids.
+    Rectangle { state: usize, x: usize, y: usize, width: usize, height: usize },
+    Weighted { weights: Vec<(usize, f64)> },
+}
+
+impl InitialSpec {
+    /// Construct the initial condition on `graph`, which is built from `graph_spec`, for a particle
+    /// system with the states `all_states`.
+    ///
+    /// # Errors
+    /// Returns an error if the initial condition doesn't fit the graph (a rectangle on a graph which
+    /// is not a 2D grid), or if the weights are invalid.
+    pub fn build(&self, graph_spec: &GraphSpec, graph: &dyn Graph, all_states: Vec<usize>) -> io::Result<Vec<usize>> {
+        let invalid_input = |message: &str| io::Error::new(ErrorKind::InvalidInput, message);
+
+        match self {
+            InitialSpec::Random => {
+                Ok(assemble_random_initial_condition(all_states, graph.nr_points()))
+            }
+            InitialSpec::DifferentParticles { state, particles } => {
+                let different: HashMap<usize, usize> = particles.iter().map(|&i| (i, *state)).collect();
+                Ok(assemble_initial_condition(0, different, graph.nr_points()))
+            }
+            InitialSpec::Ring { state, center, radius } => {
+                Ok(assemble_ring_initial_condition(graph, *center, *radius, *state, 0))
+            }
+            InitialSpec::Rectangle { state, x, y, width, height } => {
+                match graph_spec {
+                    GraphSpec::GridNd { dimensions, .. } if dimensions.len() == 2 => {
+                        Ok(assemble_rectangular_seed(dimensions[0], dimensions[1], (*x, *y, *width, *height), 0, *state))
+                    }
+                    _ => Err(invalid_input("A rectangular initial condition needs a 2D grid!")),
+                }
+            }
+            InitialSpec::Weighted { weights } => {
+                assemble_weighted_initial_condition(weights, graph.nr_points(), &mut rand::thread_rng())
+                    .map_err(|_| invalid_input("Weights should be nonnegative and not all zero!"))
+            }
+        }
+    }
+}
+
+/// The output of a `SimulationSpec`. Next to the image outputs of the command line, the solution
+/// can be saved as JSON (see `Solution::save_json`) to visualize it later.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OutputSpec {
+    Gif { path: String, height: u32, ms_per_frame: u32, #[serde(default)] loop_forever: bool },
+    Growth { path: String },
+    PngFrames { dir: String, height: u32, scale: u32 },
+    Json { path: String },
+}
+
+impl SimulationSpec {
+    /// Load a spec from the file `path`, which is read as TOML if it ends in ".toml", and as JSON
+    /// otherwise.
+    pub fn load(path: &str) -> io::Result<SimulationSpec> {
+        let contents = fs::read_to_string(path)?;
+
+        if path.ends_with(".toml") {
+            toml::from_str(&contents).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+        } else {
+            Ok(serde_json::from_str(&contents)?)
+        }
+    }
+}
+
+/// Run the simulation defined by `spec`, and save its output.
+///
+/// # Errors
+/// Returns an error if the initial condition doesn't fit the graph (a rectangle on a graph which
+/// is not a 2D grid, or invalid weights), if an image output is asked of a graph which is not a 1D
+/// or 2D grid or of the wrong height, or if the output could not be saved.
+pub fn run_from_spec(spec: SimulationSpec) -> io::Result<()> {
+    let graph = spec.graph.build();
+    println!("Graph:");
+    graph.describe();
+    println!();
+
+    let (ips_rules, coloration) = spec.ips.build();
+    println!("Interacting particle system:");
+    ips_rules.describe();
+    println!();
+
+    let initial_condition = spec.initial.build(&spec.graph, graph.as_ref(), ips_rules.all_states())?;
+    let nr_points = graph.nr_points();
+
+    let solution = particle_system_solver(
+        ips_rules,
+        graph,
+        initial_condition,
+        spec.halt,
+        spec.record,
+        SolverOptions::default(),
+        rand::thread_rng(),
+    );
+
+    println!("Simulated {:.2?} time units, in which {} steps were taken, and {} were recorded.",
+             solution.time_simulated, solution.steps_taken, solution.steps_recorded);
+
+    let image_error = io::Error::other;
+    let image_width = || spec.graph.image_width()
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "Only 1D and 2D grids can be drawn as images!"));
+
+    match spec.output {
+        OutputSpec::Gif { path, height, ms_per_frame, loop_forever } => {
+            save_as_gif(coloration, solution.snapshots, &path, [image_width()?, height],
+                        nr_points, ms_per_frame, loop_forever, None).map_err(image_error)
+        }
+        OutputSpec::Growth { path } => {
+            save_as_growth_img(coloration, solution.snapshots, &path, nr_points as u32).map_err(image_error)
+        }
+        OutputSpec::PngFrames { dir, height, scale } => {
+            save_frames_as_pngs(coloration, solution.snapshots, &dir, image_width()?, height, scale)
+                .map_err(image_error)
+        }
+        OutputSpec::Json { path } => {
+            solution.save_json(&path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIR_SPEC: &str = r#"
+        halt = { TimePassed = 200.0 }
+        record = { ConstantTime = 0.1 }
+
+        [graph]
+        kind = "grid_nd"
+        dimensions = [200, 200]
+
+        [ips]
+        kind = "sir"
+        birth_rate = 1.0
+        death_rate = 0.8
+
+        [initial]
+        kind = "different_particles"
+        state = 1
+        particles = [20100]
+
+        [output]
+        kind = "gif"
+        path = "sir 200x200.gif"
+        height = 200
+        ms_per_frame = 20
+    "#;
+
+    #[test]
+    fn deserialize_toml_spec() {
+        let spec: SimulationSpec = toml::from_str(SIR_SPEC).unwrap();
+
+        assert_eq!(spec.graph, GraphSpec::GridNd { dimensions: vec![200, 200], moore: false });
+        assert_eq!(spec.ips, IPSSpec::Sir { birth_rate: 1.0, death_rate: 0.8 });
+        assert_eq!(spec.initial, InitialSpec::DifferentParticles { state: 1, particles: vec![20100] });
+        assert!(matches!(spec.halt, HaltCondition::TimePassed(time) if time == 200.0));
+        assert!(matches!(spec.record, RecordCondition::ConstantTime(interval) if interval == 0.1));
+        assert_eq!(spec.output, OutputSpec::Gif {
+            path: "sir 200x200.gif".to_string(),
+            height: 200,
+            ms_per_frame: 20,
+            loop_forever: false,
+        });
+
+        let graph = spec.graph.build();
+        assert_eq!(graph.nr_points(), 40000);
+        let initial_condition = spec.initial.build(&spec.graph, graph.as_ref(), vec![0, 1, 2]).unwrap();
+        assert_eq!(initial_condition.iter().filter(|&&state| state == 1).count(), 1);
+        assert_eq!(initial_condition[20100], 1);
+    }
+
+    #[test]
+    fn rectangle_needs_2d_grid() {
+        let graph_spec = GraphSpec::Star { nr_leaves: 4 };
+        let initial = InitialSpec::Rectangle { state: 1, x: 0, y: 0, width: 1, height: 1 };
+
+        assert!(initial.build(&graph_spec, graph_spec.build().as_ref(), vec![0, 1]).is_err());
+    }
+}
@@ -1,19 +1,28 @@
 use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
 use std::time::Instant;
-use clap::{arg, ArgGroup, command, value_parser};
-use crate::solver::assemble_initial_condition::{assemble_initial_condition, assemble_random_initial_condition};
-use crate::solver::{HaltCondition, particle_system_solver, RecordCondition};
-use crate::solver::graph::{Graph, diluted_lattice::DilutedLattice, erdos_renyi::ErdosRenyi, grid_n_d::GridND};
-use crate::solver::ips_rules::{IPSRules, si_process::SIProcess, sir_process::SIRProcess, two_si_process::TwoSIProcess, voter_process::VoterProcess};
-use crate::visualization::{Coloration, save_as_gif, save_as_growth_img};
+use clap::{arg, ArgGroup, ArgMatches, command, Command, value_parser, ErrorKind};
+use crate::solver::assemble_initial_condition::{assemble_initial_condition, assemble_initial_condition_from_image, assemble_random_initial_condition, assemble_weighted_initial_condition, assemble_rectangular_seed, assemble_ring_initial_condition};
+use crate::solver::{HaltCondition, particle_system_solver_checked, RecordCondition, SolverOptions};
+use crate::solver::graph::{Graph, write_dot, bipartite::CompleteBipartite, cayley_tree::CayleyTree, configuration_model::ConfigurationModel, diluted_lattice::DilutedLattice, erdos_renyi::ErdosRenyi, grid_n_d::GridND, hypercube::Hypercube, kagome::KagomeLattice, random_geometric::RandomGeometric, ring_lattice::RingLattice, sbm::StochasticBlockModel, star::StarGraph};
+use crate::solver::ips_rules::{IPSRules, anti_voter::AntiVoter, asymmetric_two_si::AsymmetricTwoSI, deffuant::Deffuant, forest_fire::ForestFire, linear_voter::LinearVoter, moran_process::MoranProcess, multistrain::MultiStrainContact, noisy_voter::NoisyVoter, potts::PottsModel, schelling::Schelling, seir_partial::SEIRPartial, si_process::SIProcess, sir_process::SIRProcess, sirs_process::SIRSProcess, two_si_process::TwoSIProcess, voter_process::VoterProcess};
+use crate::visualization::{Coloration, RecoloredBackground, save_activity_heatmap, save_as_gif, save_counts_plot, save_as_growth_img, save_final_as_image, save_as_slice_montage, save_frames_as_pngs};
 
 pub mod visualization;
 pub mod solver;
+#[cfg(feature = "serde")]
+pub mod config;
 
-fn main() {
-
-    // Get the arguments
-    let matches = command!("cmd")
+/// The command line arguments.
+fn cli() -> Command<'static> {
+    command!("cmd")
+        // Alternatively, define the whole simulation in a config file
+        .arg(arg!(--"config" <FILE_NAME>).required(false)
+            .exclusive(true)
+            .help("Run the simulation defined in the specified config file (TOML if the name ends \
+            in \".toml\", JSON otherwise), instead of by the other arguments. Needs the serde \
+            feature."))
         // Select graph
         .arg(arg!(--"graph-grid-nd" <DIMENSIONS>).required(false)
             .help("Run particle system on an n-dimensional grid. Specify dimensions.")
@@ -21,6 +30,10 @@ fn main() {
             .multiple_values(true)
             .value_parser(value_parser!(usize))
             .validator(|s| s.parse::<usize>()))
+        .arg(arg!(--"moore").required(false)
+            .requires("graph-grid-nd")
+            .help("Use the Moore neighborhood on the grid, where diagonal points are neighbors \
+            too."))
         .arg(arg!(--"graph-erdos-renyi" <NR_NODES_AND_AVG_NEIGHS_PER_NODE>).required(false)
             .help("Run particle system on an Erdos-Renyi graph. Specify dimensions and average \
             number of neighbours per particle.")
@@ -32,48 +45,223 @@ fn main() {
         .arg(arg!(--"graph-diluted-lattice" <X_AND_Y_DIMENSIONS_AND_PERCENTILE>).required(false)
             .help("Run particle system on a 2d diluted lattice graph. Specify dimensions and \
             percentile of the edges being present in the diluted lattice. (100% corresponds with \
-            the ordinary lattice.)")
+            the ordinary lattice.) Optionally specify a second percentile, then the first applies \
+            to the horizontal and the second to the vertical edges.")
             .min_values(3)
-            .max_values(3)
+            .max_values(4)
+            .value_parser(value_parser!(usize))
+            .validator(|s| s.parse::<usize>())
+            .multiple_values(true))
+        .arg(arg!(--"graph-cayley-tree" <COORDINATION_AND_DEPTH>).required(false)
+            .help("Run particle system on a Cayley tree. Specify the coordination number (degree \
+            of the internal nodes) and the depth (distance from the root to the leaves).")
+            .min_values(2)
+            .max_values(2)
+            .value_parser(value_parser!(usize))
+            .validator(|s| s.parse::<usize>())
+            .multiple_values(true))
+        .arg(arg!(--"graph-ring-lattice" <NR_NODES_AND_NEIGHS_PER_SIDE>).required(false)
+            .help("Run particle system on a ring lattice. Specify the number of points and the \
+            number of nearest neighbors on each side every point is connected to.")
+            .min_values(2)
+            .max_values(2)
+            .value_parser(value_parser!(usize))
+            .validator(|s| s.parse::<usize>())
+            .multiple_values(true))
+        .arg(arg!(--"graph-stochastic-block" <P_IN_AND_P_OUT_AND_BLOCK_SIZES>).required(false)
+            .help("Run particle system on a stochastic block model graph. Specify the probability \
+            of an edge within a block, the probability of an edge between blocks, and the sizes \
+            of the blocks.")
+            .min_values(3)
+            .multiple_values(true)
+            // The values are of different types, so they are converted when reading them out
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"graph-random-geometric" <NR_NODES_AND_RADIUS>).required(false)
+            .help("Run particle system on a random geometric graph. Specify the number of points, \
+            placed uniformly in the unit square, and the radius within which they are connected.")
+            .min_values(2)
+            .max_values(2)
+            // The values are of different types, so they are converted when reading them out
+            .validator(|s| s.parse::<f64>())
+            .multiple_values(true))
+        .arg(arg!(--"graph-hypercube" <DIMENSION>).required(false)
+            .help("Run particle system on a hypercube. Specify the dimension, the graph has 2 to \
+            this power points.")
+            .value_parser(value_parser!(usize))
+            .validator(|s| s.parse::<usize>()))
+        .arg(arg!(--"graph-complete-bipartite" <M_AND_N>).required(false)
+            .help("Run particle system on a complete bipartite graph. Specify the sizes of the two \
+            parts; every point is connected to all points of the other part.")
+            .min_values(2)
+            .max_values(2)
+            .value_parser(value_parser!(usize))
+            .validator(|s| s.parse::<usize>())
+            .multiple_values(true))
+        .arg(arg!(--"graph-star" <NR_LEAVES>).required(false)
+            .help("Run particle system on a star graph. Specify the number of leaves, which are \
+            all connected to the hub (point 0) only.")
+            .value_parser(value_parser!(usize))
+            .validator(|s| s.parse::<usize>()))
+        .arg(arg!(--"graph-kagome" <X_AND_Y_DIMENSIONS>).required(false)
+            .help("Run particle system on a toroidal kagome lattice (corner-sharing triangles). \
+            Specify the number of unit cells in each direction; every unit cell has 3 points.")
+            .min_values(2)
+            .max_values(2)
+            .value_parser(value_parser!(usize))
+            .validator(|s| s.parse::<usize>())
+            .multiple_values(true))
+        .arg(arg!(--"graph-configuration-model" <DEGREES>).required(false)
+            .help("Run particle system on a random graph with the specified degree of each point \
+            (the configuration model). Self-loops and multi-edges are removed, lowering the degrees \
+            of the points involved.")
+            .min_values(1)
             .value_parser(value_parser!(usize))
             .validator(|s| s.parse::<usize>())
             .multiple_values(true))
         .group(ArgGroup::new("graph-kind")
-            .args(&["graph-grid-nd", "graph-erdos-renyi", "graph-diluted-lattice"])
-            .required(true)
+            .args(&["graph-grid-nd", "graph-erdos-renyi", "graph-diluted-lattice", "graph-cayley-tree",
+                "graph-ring-lattice", "graph-stochastic-block", "graph-random-geometric", "graph-hypercube",
+                "graph-complete-bipartite", "graph-star", "graph-kagome",
+                "graph-configuration-model"])
         )
         // Select IPS
         .arg(arg!(--"ips-si" <BIRTH_AND_DEATH_RATE>).required(false)
-            .help("Susceptible-Infected (aka contact) process, specify birth and death rates.")
+            .help("Susceptible-Infected (aka contact) process, specify birth and death rates. \
+            Optionally specify a third rate, at which susceptible particles get infected \
+            spontaneously.")
             .min_values(2)
-            .max_values(2)
+            .max_values(3)
             .value_parser(value_parser!(f64))
             .validator(|s| s.parse::<f64>()))
-        .arg(arg!(--"ips-voter" <NR_PARTIES>)
-            .help("Voter process (competitive) on the specified number of parties (i.e., states).")
-            .value_parser(value_parser!(usize)))
-        .arg(arg!(--"ips-two-si" <BIRTH_AND_DEATH_AND_COMPETE_RATE>)
+        .arg(arg!(--"ips-voter" <NR_PARTIES_AND_CHANGE_RATE>).required(false)
+            .help("Voter process (competitive) on the specified number of parties (i.e., states), \
+            with the specified change rate.")
+            .min_values(2)
+            .max_values(2)
+            // The values are of different types, so they are converted when reading them out
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-linear-voter" <NR_PARTIES>).required(false)
+            .help("Linear voter process on the specified number of parties, where particles copy \
+            the party of a random neighbor at rate 1, i.e., join a party at rate equal to the \
+            fraction of their neighbors in it.")
+            .value_parser(value_parser!(usize))
+            .validator(|s| s.parse::<usize>()))
+        .arg(arg!(--"ips-two-si" <BIRTH_AND_DEATH_AND_COMPETE_RATE>).required(false)
             .help("Susceptible-infected process with two identical invasive species (states 1 \
             and 2), competing indirectly via the available space, and directly via conversion (i.e., combat).")
             .min_values(3)
             .max_values(3)
             .value_parser(value_parser!(f64))
             .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-two-si-asymmetric" <RATES>).required(false)
+            .help("Susceptible-infected process with two invasive species (states 1 and 2) with \
+            different rates. Specify the birth and death rate of species 1, the birth and death \
+            rate of species 2, the rate at which species 1 converts species 2, and the rate at \
+            which species 2 converts species 1.")
+            .min_values(6)
+            .max_values(6)
+            .value_parser(value_parser!(f64))
+            .validator(|s| s.parse::<f64>()))
         .arg(arg!(--"ips-sir" <BIRTH_AND_DEATH_RATE>).required(false)
             .help("Susceptible-infected-removed process, specify birth and death rates.")
             .min_values(2)
             .max_values(2)
             .value_parser(value_parser!(f64))
             .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-sirs" <BIRTH_AND_DEATH_AND_WANING_RATE>).required(false)
+            .help("Susceptible-infected-removed-susceptible process, where removed particles become \
+            susceptible again. Specify birth, death and waning rates.")
+            .min_values(3)
+            .max_values(3)
+            .value_parser(value_parser!(f64))
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-seir-partial" <BIRTH_AND_FRACTION_AND_INCUBATION_AND_RECOVERY_RATE>).required(false)
+            .help("Susceptible-exposed-infected-removed process where exposed particles are \
+            partially infectious. Specify the birth rate due to infected neighbors, the fraction of \
+            it due to exposed neighbors, the incubation rate and the recovery rate.")
+            .min_values(4)
+            .max_values(4)
+            .value_parser(value_parser!(f64))
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-antivoter" <NR_PARTIES_AND_RATE>).required(false)
+            .help("Antivoter (anticonformist) process on the specified number of parties (i.e., \
+            states), where particles switch to parties that are a minority among their neighbors \
+            at the specified rate per neighbor not in that party.")
+            .min_values(2)
+            .max_values(2)
+            // The values are of different types, so they are converted when reading them out
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-deffuant" <NR_BINS_AND_CONFIDENCE_AND_MU>).required(false)
+            .help("Deffuant bounded-confidence opinion model on the specified number of opinion \
+            bins (i.e., states). Neighbors at most the specified confidence apart move one bin \
+            towards each other at rate mu.")
+            .min_values(3)
+            .max_values(3)
+            // The values are of different types, so they are converted when reading them out
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-moran" <FITNESSES>).required(false)
+            .help("Moran process, where particles reproduce at a rate given by their fitness and \
+            the offspring replaces a neighbor. Specify the fitness of each type (i.e., state).")
+            .min_values(2)
+            .multiple_values(true)
+            .value_parser(value_parser!(f64))
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-noisy-voter" <NR_PARTIES_AND_COPY_RATE_AND_NOISE_RATE>).required(false)
+            .help("Voter process on the specified number of parties (i.e., states), where particles \
+            copy the party of a neighbor at the copy rate (per neighbor), and switch to a uniformly \
+            random other party at the noise rate.")
+            .min_values(3)
+            .max_values(3)
+            // The values are of different types, so they are converted when reading them out
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-potts" <Q_AND_BETA>).required(false)
+            .help("Potts model with Glauber dynamics on the specified number of spin values (i.e., \
+            states), at the specified inverse temperature beta.")
+            .min_values(2)
+            .max_values(2)
+            // The values are of different types, so they are converted when reading them out
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-schelling" <TOLERANCE>).required(false)
+            .help("Schelling segregation model, where agents of two types move out if the fraction \
+            of their occupied neighbors of their own type is below the tolerance, and newcomers \
+            move into empty sites.")
+            .value_parser(value_parser!(f64))
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-forest-fire" <GROWTH_AND_LIGHTNING_AND_BURN_RATE>).required(false)
+            .help("Forest-fire model, where trees grow on empty sites at the growth rate, are \
+            struck by lightning at the lightning rate, catch fire from burning neighbors (at rate 1 \
+            per neighbor), and burn down at the burn rate.")
+            .min_values(3)
+            .max_values(3)
+            .value_parser(value_parser!(f64))
+            .validator(|s| s.parse::<f64>()))
+        .arg(arg!(--"ips-multistrain" <BIRTH_AND_DEATH_RATES>).required(false)
+            .help("Multitype contact process with competing strains. Specify the birth and death \
+            rate of each strain, in pairs. The strains only compete for the susceptible particles.")
+            .min_values(2)
+            .multiple_values(true)
+            .value_parser(value_parser!(f64))
+            .validator(|s| s.parse::<f64>()))
         .group(ArgGroup::new("ips-kind")
             .args(&[
                 "ips-si",
                 "ips-sir",
                 "ips-voter",
+                "ips-linear-voter",
                 "ips-two-si",
-                "ips-sir"
-            ])
-            .required(true))
+                "ips-two-si-asymmetric",
+                "ips-sir",
+                "ips-sirs",
+                "ips-seir-partial",
+                "ips-antivoter",
+                "ips-deffuant",
+                "ips-moran",
+                "ips-noisy-voter",
+                "ips-potts",
+                "ips-schelling",
+                "ips-forest-fire",
+                "ips-multistrain"
+            ]))
         // Select initial condition
         .arg(arg!(--"initial-random").required(false)
             .help("Start with random initial condition, where each state has equal probability."))
@@ -82,9 +270,34 @@ fn main() {
             will be in the state 0.")
             .min_values(2)
             .value_parser(value_parser!(usize)))
+        .arg(arg!(--"initial-ring" <STATE_AND_CENTER_AND_RADIUS>).required(false)
+            .help("Start with a ring of particles in the specified state, at the specified graph \
+            distance from the specified center particle. The other particles will be in the state 0.")
+            .min_values(3)
+            .max_values(3)
+            .value_parser(value_parser!(usize)))
+        .arg(arg!(--"initial-image" <FILE_NAME>).required(false)
+            .help("Start with the initial condition drawn in the specified image, which should \
+            have one pixel per particle. Pixels are matched to states by the colors in which the \
+            process is drawn; other colors give the state 0."))
+        .arg(arg!(--"initial-rectangle" <STATE_AND_X_AND_Y_AND_WIDTH_AND_HEIGHT>).required(false)
+            .requires("graph-grid-nd")
+            .help("Start with a rectangle of particles in the specified state on a 2d grid, \
+            specified by the coordinates of its corner and its width and height. The other \
+            particles will be in the state 0.")
+            .min_values(5)
+            .max_values(5)
+            .value_parser(value_parser!(usize)))
+        .arg(arg!(--"initial-weighted" <STATES_AND_WEIGHTS>).required(false)
+            .help("Start with random initial condition, where each particle is in a state with \
+            probability proportional to its weight. Specify pairs of a state and its weight.")
+            .min_values(2)
+            .multiple_values(true)
+            // The values are of different types, so they are converted when reading them out
+            .validator(|s| s.parse::<f64>()))
         .group(ArgGroup::new("initial-kind")
-            .args(&["initial-random", "initial-different-particles"])
-            .required(true))
+            .args(&["initial-random", "initial-different-particles", "initial-ring", "initial-image",
+                "initial-rectangle", "initial-weighted"]))
         // Select halting condition
         .arg(arg!(--"halt-time-passed" <TIME_PASSED>).required(false)
             .help("Stop simulation after a certain specified amount of time as passed.")
@@ -98,9 +311,21 @@ fn main() {
             .help("Stop simulation after a certain specified number of steps have been taken.")
             .value_parser(value_parser!(u64))
             .validator(|s| s.parse::<u64>()))
+        .arg(arg!(--"halt-quiet-period" <TIME>).required(false)
+            .help("Stop simulation when no particle has left the state 0 (i.e., no new infection \
+            has occurred) for the specified amount of time.")
+            .value_parser(value_parser!(f64))
+            .validator(|s| s.parse::<f64>()))
         .group(ArgGroup::new("halt-kind")
-            .args(&["halt-time-passed", "halt-steps-recorded", "halt-steps-taken"])
-            .required(true))
+            .args(&["halt-time-passed", "halt-steps-recorded", "halt-steps-taken", "halt-quiet-period"]))
+        .arg(arg!(--"halt-min-steps" <STEPS>).required(false)
+            .help("Don't stop simulation before a certain specified number of steps have been \
+            taken, even if the halting condition is met.")
+            .value_parser(value_parser!(u64)))
+        .arg(arg!(--"halt-max-steps" <STEPS>).required(false)
+            .help("Also stop simulation after a certain specified number of steps have been taken, \
+            if the halting condition hasn't stopped it before. Prevents runaway runs.")
+            .value_parser(value_parser!(u64)))
         // Select record condition
         .arg(arg!(--"record-final").required(false)
             .help("Only record the final state."))
@@ -110,9 +335,35 @@ fn main() {
         .arg(arg!(--"record-constant-time" <TIME>).required(false)
             .help("Record state at every whole multiple of  the specified time.")
             .value_parser(value_parser!(f64)))
+        .arg(arg!(--"record-constant-time-deduplicated" <TIME>).required(false)
+            .help("Record state at every whole multiple of the specified time, but only once if \
+            the state did not change in between.")
+            .value_parser(value_parser!(f64)))
+        .arg(arg!(--"record-adaptive" <MIN_AND_MAX_INTERVAL>).required(false)
+            .help("Record state at an interval between the specified minimum and maximum, which \
+            is shorter when the system is more active.")
+            .min_values(2)
+            .max_values(2)
+            .value_parser(value_parser!(f64)))
+        .arg(arg!(--"record-reservoir" <NR_SNAPSHOTS>).required(false)
+            .help("Record a uniformly random sample of at most the specified number of states, \
+            including the final state, to bound the memory use of long simulations.")
+            .value_parser(value_parser!(usize)))
+        .arg(arg!(--"burn-in" <TIME>).required(false)
+            .help("Only start recording after the specified time has passed, to discard the \
+            transient from the initial condition. The final state is always recorded.")
+            .value_parser(value_parser!(f64)))
+        .arg(arg!(--"record-window" <X_AND_Y_AND_WIDTH_AND_HEIGHT>).required(false)
+            .requires("graph-grid-nd")
+            .conflicts_with("image-slice-montage")
+            .help("Only record a rectangular window of a 2d grid, specified by the coordinates of \
+            its corner and its width and height. The output images show only this window.")
+            .min_values(4)
+            .max_values(4)
+            .value_parser(value_parser!(usize)))
         .group(ArgGroup::new("record-kind")
-            .args(&["record-final", "record-nth-step", "record-constant-time"])
-            .required(true))
+            .args(&["record-final", "record-nth-step", "record-constant-time",
+                "record-constant-time-deduplicated", "record-adaptive", "record-reservoir"]))
         // Select output kind
         .arg(arg!(--"image-growth").required(false)
             .help("Record output of growth-image type. The output file name must end in .png."))
@@ -121,14 +372,110 @@ fn main() {
             .min_values(2)
             .max_values(2)
             .value_parser(value_parser!(u32)))
+        .arg(arg!(--"gif-loop").required(false)
+            .requires("gif_output_kind")
+            .help("Make the output gif loop forever, instead of replaying once."))
+        .arg(arg!(--"background-color" <R_G_B>).required(false)
+            .help("Draw the state 0 in the specified color (red, green and blue values from 0 to \
+            255) instead of the color of the particle system.")
+            .min_values(3)
+            .max_values(3)
+            .value_parser(value_parser!(u8)))
+        .arg(arg!(--"gif-timestamps").required(false)
+            .requires("image-gif")
+            .help("Write the simulated time of each frame in the top left corner of the gif."))
+        .arg(arg!(--"image-png-frames" <IMG_Y_AND_SCALE>).required(false)
+            .help("Record output as a directory of png images, one per frame, with each particle \
+            drawn as a square of the specified scale. The output name is the directory.")
+            .min_values(2)
+            .max_values(2)
+            .value_parser(value_parser!(u32)))
+        .arg(arg!(--"image-final" <IMG_Y_AND_SCALE>).required(false)
+            .help("Record output as a png image of only the final state, with each particle drawn \
+            as a square of the specified scale. The output file name must end in .png.")
+            .min_values(2)
+            .max_values(2)
+            .value_parser(value_parser!(u32)))
+        .arg(arg!(--"image-slice-montage" <MS_PER_FRAME>).required(false)
+            .requires("graph-grid-nd")
+            .help("Record output of a 3d grid as a gif, with the z-slices side by side. The output \
+            file name must end in .gif.")
+            .value_parser(value_parser!(u32)))
         .group(ArgGroup::new("image_output_kind")
-            .args(&["image-growth", "image-gif"])
-            .required(true))
+            .args(&["image-growth", "image-gif", "image-png-frames", "image-final", "image-slice-montage"]))
+        .group(ArgGroup::new("gif_output_kind")
+            .args(&["image-gif", "image-slice-montage"]))
         // Set output file name
-        .arg(arg!(--"output" <FILE_NAME>).required(true)
+        .arg(arg!(--"output" <FILE_NAME>).required(false)
             .help("File output name."))
+        .arg(arg!(--"reactivity-trace" <FILE_NAME>).required(false)
+            .help("Also write the total reactivity at the time of each recorded snapshot to the \
+            specified file, as comma-separated values."))
+        .arg(arg!(--"event-log" <FILE_NAME>).required(false)
+            .help("Also write every transition (time, particle, old state and new state) to the \
+            specified file, as comma-separated values."))
+        .arg(arg!(--"activity-heatmap" <FILE_NAME>).required(false)
+            .requires("graph-grid-nd")
+            .help("Also save an image to the specified file (should end in \".png\") where every \
+            particle is brighter the more often it changed state. Only for 2D grids."))
+        .arg(arg!(--"counts-plot" <FILE_NAME>).required(false)
+            .help("Also save a plot of the number of particles in each state over time to the \
+            specified file (should end in \".png\")."))
+        .arg(arg!(--"zealots" <PARTICLES>).required(false)
+            .help("Particles which never change their state, but still influence their neighbors \
+            (e.g., zealots in the voter process).")
+            .min_values(1)
+            .multiple_values(true)
+            .value_parser(value_parser!(usize)))
+        .arg(arg!(--"immunized" <PARTICLES>).required(false)
+            .help("Particles which are removed from the particle system: they never change their \
+            state, and don't influence their neighbors (e.g., vaccinated sites).")
+            .min_values(1)
+            .multiple_values(true)
+            .value_parser(value_parser!(usize)))
+        .arg(arg!(--"time-scale" <FACTOR>).required(false)
+            .help("Multiply the simulated time by the specified factor, e.g., to convert it to \
+            physical units. The halting and record times are in the scaled units.")
+            .value_parser(value_parser!(f64)))
+        .arg(arg!(--"sparse-to-state").required(false)
+            .help("Only sample the new state of a particle among the states it can change to. \
+            Faster for particle systems with many states, e.g., the voter process with many \
+            parties."))
+        .arg(arg!(--"max-record-megabytes" <MEGABYTES>).required(false)
+            .help("Stop with an error if the recorded snapshots would take more than the \
+            specified amount of memory, before simulating if possible.")
+            .value_parser(value_parser!(usize)))
+        .arg(arg!(--"graph-dot" <FILE_NAME>).required(false)
+            .help("Also write the graph to the specified file in the DOT format of GraphViz."))
+}
 
-        .get_matches();
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+
+    // Get the arguments
+    let mut command = cli();
+    let matches = command.get_matches_mut();
+
+    // Alternatively, the whole simulation is defined in a config file
+    if let Some(config_file) = matches.get_one::<String>("config") {
+        #[cfg(feature = "serde")]
+        {
+            let spec = config::SimulationSpec::load(config_file)?;
+            config::run_from_spec(spec)?;
+            return Ok(());
+        }
+        #[cfg(not(feature = "serde"))]
+        return Err(format!("Reading {} needs the serde feature!", config_file).into());
+    }
+    // Otherwise, all parts of the simulation have to be given by the arguments. These can't be
+    // required arguments, since the config file replaces them.
+    for required in ["graph-kind", "ips-kind", "initial-kind", "halt-kind", "record-kind",
+        "image_output_kind", "output"] {
+        if !matches.contains_id(required) {
+            command.error(ErrorKind::MissingRequiredArgument,
+                          format!("'{}' is required when no config file is given.", required))
+                .exit();
+        }
+    }
 
     /* Convert the arguments to usable objects */
 
@@ -145,9 +492,16 @@ fn main() {
             grid_dimensions.push(*i);
         }
 
-        graph = Box::new(
-            GridND::from(grid_dimensions)
-        )
+        if matches.is_present("moore") {
+            let glue = vec![true; grid_dimensions.len()];
+            graph = Box::new(
+                GridND::new_moore(grid_dimensions, glue)
+            )
+        } else {
+            graph = Box::new(
+                GridND::from(grid_dimensions)
+            )
+        }
     } else if matches.is_present("graph-erdos-renyi") {
         // Erdos-Renyi graph. arguments are the nr. of points, and avg. nr. of neighbors
         let mut values = matches.get_many::<usize>("graph-erdos-renyi").unwrap();
@@ -159,15 +513,106 @@ fn main() {
             ErdosRenyi::new(*nr_points, *avg_nr_neighs as f64 / *nr_points as f64, rand::thread_rng())
         )
     } else if matches.is_present("graph-diluted-lattice") {
-        // Diluted lattice graph. arguments are x-dimension, y-dimension, and percentage connected.
+        // Diluted lattice graph. arguments are x-dimension, y-dimension, and percentage connected,
+        // optionally followed by the percentage of vertical edges connected.
         let mut values = matches.get_many::<usize>("graph-diluted-lattice").unwrap();
 
         let dim_x = values.next().unwrap();
         let dim_y = values.next().unwrap();
         let percentile = values.next().unwrap();
+        let percentile_vertical = values.next().unwrap_or(percentile);
+
+        graph = Box::new(
+            DilutedLattice::new_anisotropic(*dim_x, *dim_y, *percentile as f64 / 100.0,
+                                            *percentile_vertical as f64 / 100.0, rand::thread_rng())
+        )
+    } else if matches.is_present("graph-cayley-tree") {
+        // Cayley tree. arguments are the coordination number and depth
+        let mut values = matches.get_many::<usize>("graph-cayley-tree").unwrap();
+
+        let coordination = values.next().unwrap();
+        let depth = values.next().unwrap();
+
+        graph = Box::new(
+            CayleyTree::new(*coordination, *depth)
+        )
+    } else if matches.is_present("graph-ring-lattice") {
+        // Ring lattice. arguments are the nr. of points, and nr. of neighbors on each side
+        let mut values = matches.get_many::<usize>("graph-ring-lattice").unwrap();
+
+        let nr_points = values.next().unwrap();
+        let k = values.next().unwrap();
+
+        graph = Box::new(
+            RingLattice::new(*nr_points, *k)
+        )
+    } else if matches.is_present("graph-stochastic-block") {
+        // Stochastic block model. arguments are the probabilities within and between blocks, and
+        // the block sizes
+        let mut values = matches.get_many::<String>("graph-stochastic-block").unwrap();
+
+        let p_in = values.next().unwrap().parse::<f64>().unwrap();
+        let p_out = values.next().unwrap().parse::<f64>().unwrap();
+        let block_sizes: Vec<usize> = values
+            .map(|size| size.parse::<usize>().expect("Block sizes should be whole numbers!"))
+            .collect();
+
+        graph = Box::new(
+            StochasticBlockModel::new(block_sizes, p_in, p_out, rand::thread_rng())
+        )
+    } else if matches.is_present("graph-random-geometric") {
+        // Random geometric graph. arguments are the nr. of points and the connection radius
+        let mut values = matches.get_many::<String>("graph-random-geometric").unwrap();
+
+        let nr_points = values.next().unwrap().parse::<usize>()
+            .expect("The number of points should be a whole number!");
+        let radius = values.next().unwrap().parse::<f64>().unwrap();
+
+        graph = Box::new(
+            RandomGeometric::new(nr_points, radius, rand::thread_rng())
+        )
+    } else if matches.is_present("graph-hypercube") {
+        // Hypercube. argument is the dimension
+        let dimension = matches.get_one::<usize>("graph-hypercube").unwrap();
 
         graph = Box::new(
-            DilutedLattice::new(*dim_x, *dim_y, *percentile as f64 / 100.0, rand::thread_rng())
+            Hypercube::new(*dimension)
+        )
+    } else if matches.is_present("graph-complete-bipartite") {
+        // Complete bipartite graph. arguments are the sizes of the parts
+        let mut values = matches.get_many::<usize>("graph-complete-bipartite").unwrap();
+
+        let m = values.next().unwrap();
+        let n = values.next().unwrap();
+
+        graph = Box::new(
+            CompleteBipartite::new(*m, *n)
+        )
+    } else if matches.is_present("graph-star") {
+        // Star graph. argument is the number of leaves
+        let nr_leaves = matches.get_one::<usize>("graph-star").unwrap();
+
+        graph = Box::new(
+            StarGraph::new(*nr_leaves)
+        )
+    } else if matches.is_present("graph-kagome") {
+        // Toroidal kagome lattice. arguments are the numbers of unit cells in the x and y directions
+        let mut values = matches.get_many::<usize>("graph-kagome").unwrap();
+
+        let dim_x = values.next().unwrap();
+        let dim_y = values.next().unwrap();
+
+        graph = Box::new(
+            KagomeLattice::new(*dim_x, *dim_y, true)
+        )
+    } else if matches.is_present("graph-configuration-model") {
+        // Configuration model. arguments are the degrees of the points
+        let degrees: Vec<usize> = matches.get_many::<usize>("graph-configuration-model").unwrap()
+            .copied()
+            .collect();
+
+        graph = Box::new(
+            ConfigurationModel::new(degrees, rand::thread_rng())
         )
     } else {
         panic!("Graph not recognized!");
@@ -176,43 +621,58 @@ fn main() {
     // Print pretty statistics of the selected graph
     println!("Graph:");
     graph.describe();
+    if let Some(dot_name) = matches.get_one::<String>("graph-dot") {
+        write_dot(graph.as_ref(), dot_name).unwrap();
+    }
     // Precompute nr. of points on the graph
     let graph_nr_points = graph.nr_points();
+    // Width of the graph when drawn as an image, which is only possible for 1d and 2d grids
+    let graph_width: Option<u32> = if let Some(dimensions) = matches.get_many::<usize>("graph-grid-nd") {
+        let dimensions: Vec<usize> = dimensions.copied().collect();
+        if dimensions.len() <= 2 { Some(dimensions[0] as u32) } else { None }
+    } else {
+        matches.get_many::<usize>("graph-diluted-lattice")
+            .map(|mut values| *values.next().unwrap() as u32)
+    };
 
     // Make ips from provided arguments
     let ips_rules: Box<dyn IPSRules>;
     let coloration: Box<dyn Coloration>;
 
     if matches.is_present("ips-si") {
-        // Susceptible-infected process,  parameters are birth and death rate
+        // Susceptible-infected process,  parameters are birth and death rate, and optionally the
+        // spontaneous infection rate
         let mut values = matches.get_many::<f64>("ips-si").unwrap();
-        assert_eq!(values.len(), 2); // raise argument error
         let birth_rate = *values.next().unwrap();
         let death_rate = *values.next().unwrap();
+        let spontaneous_rate = values.next().copied().unwrap_or(0.0);
 
         coloration = Box::new(SIProcess {
             birth_rate,
             death_rate,
+            spontaneous_rate,
         });
 
         ips_rules = Box::new(SIProcess {
             birth_rate,
             death_rate,
+            spontaneous_rate,
         });
     } else if matches.is_present("ips-voter") {
-        // voter model on specified number of parties
-        let nr_parties = *matches.get_one::<usize>("ips-voter").unwrap();
+        // voter model on specified number of parties, with specified change rate
+        coloration = Box::new(voter_from_args(&matches));
+
+        ips_rules = Box::new(voter_from_args(&matches));
+    } else if matches.is_present("ips-linear-voter") {
+        // linear voter model on specified number of parties
+        let nr_parties = *matches.get_one::<usize>("ips-linear-voter").unwrap();
 
-        coloration = Box::new(VoterProcess {
+        coloration = Box::new(LinearVoter {
             nr_parties,
-            change_rate: 1.0, // With this setup, we can't have two parameters of different types
-            // in the same process; nr_parties being a usize excludes the possibility to parameterize
-            // change_rate (a f64)
         });
 
-        ips_rules = Box::new(VoterProcess {
+        ips_rules = Box::new(LinearVoter {
             nr_parties,
-            change_rate: 1.0,
         });
     } else if matches.is_present("ips-two-si") {
         // Two-species SI-model, parameters are birth, death, and compete rates
@@ -233,6 +693,31 @@ fn main() {
             death_rate,
             compete_rate,
         });
+    } else if matches.is_present("ips-two-si-asymmetric") {
+        // Two-species SI-model with different rates, parameters are the birth and death rates of
+        // both species, and the compete rates in both directions
+        let values: Vec<f64> = matches.get_many::<f64>("ips-two-si-asymmetric").unwrap()
+            .copied()
+            .collect();
+        assert_eq!(values.len(), 6); // raise argument error
+
+        coloration = Box::new(AsymmetricTwoSI {
+            birth1: values[0],
+            death1: values[1],
+            birth2: values[2],
+            death2: values[3],
+            compete12: values[4],
+            compete21: values[5],
+        });
+
+        ips_rules = Box::new(AsymmetricTwoSI {
+            birth1: values[0],
+            death1: values[1],
+            birth2: values[2],
+            death2: values[3],
+            compete12: values[4],
+            compete21: values[5],
+        });
     } else if matches.is_present("ips-sir") {
         // Susceptible-infected-removed process, parameters are birth and death rates
         let mut values = matches.get_many::<f64>("ips-sir").unwrap();
@@ -249,15 +734,206 @@ fn main() {
             birth_rate,
             death_rate,
         });
+    } else if matches.is_present("ips-sirs") {
+        // Susceptible-infected-removed-susceptible process, parameters are birth, death and waning
+        // rates
+        let mut values = matches.get_many::<f64>("ips-sirs").unwrap();
+        assert_eq!(values.len(), 3); // raise argument error
+        let birth_rate = *values.next().unwrap();
+        let death_rate = *values.next().unwrap();
+        let waning_rate = *values.next().unwrap();
+
+        coloration = Box::new(SIRSProcess {
+            birth_rate,
+            death_rate,
+            waning_rate,
+        });
+
+        ips_rules = Box::new(SIRSProcess {
+            birth_rate,
+            death_rate,
+            waning_rate,
+        });
+    } else if matches.is_present("ips-seir-partial") {
+        // Susceptible-exposed-infected-removed process with partially infectious exposed,
+        // parameters are birth rate, fraction for exposed, incubation rate, and recovery rate
+        let mut values = matches.get_many::<f64>("ips-seir-partial").unwrap();
+        assert_eq!(values.len(), 4); // raise argument error
+        let birth_i = *values.next().unwrap();
+        let birth_e_fraction = *values.next().unwrap();
+        let incubation_rate = *values.next().unwrap();
+        let recovery_rate = *values.next().unwrap();
+
+        coloration = Box::new(SEIRPartial {
+            birth_i,
+            birth_e_fraction,
+            incubation_rate,
+            recovery_rate,
+        });
+
+        ips_rules = Box::new(SEIRPartial {
+            birth_i,
+            birth_e_fraction,
+            incubation_rate,
+            recovery_rate,
+        });
+    } else if matches.is_present("ips-antivoter") {
+        // antivoter model on specified number of parties, with specified rate
+        let mut values = matches.get_many::<String>("ips-antivoter").unwrap();
+        assert_eq!(values.len(), 2); // raise argument error
+        let nr_parties = values.next().unwrap().parse::<usize>()
+            .expect("The number of parties should be a whole number!");
+        let rate = values.next().unwrap().parse::<f64>().unwrap();
+
+        coloration = Box::new(AntiVoter {
+            nr_parties,
+            rate,
+        });
+
+        ips_rules = Box::new(AntiVoter {
+            nr_parties,
+            rate,
+        });
+    } else if matches.is_present("ips-deffuant") {
+        // Deffuant model, parameters are the nr. of bins, confidence (in bins), and rate mu
+        let mut values = matches.get_many::<String>("ips-deffuant").unwrap();
+        assert_eq!(values.len(), 3); // raise argument error
+        let nr_bins = values.next().unwrap().parse::<usize>()
+            .expect("The number of bins should be a whole number!");
+        let confidence = values.next().unwrap().parse::<usize>()
+            .expect("The confidence should be a whole number!");
+        let mu = values.next().unwrap().parse::<f64>().unwrap();
+
+        coloration = Box::new(Deffuant {
+            nr_bins,
+            confidence,
+            mu,
+        });
+
+        ips_rules = Box::new(Deffuant {
+            nr_bins,
+            confidence,
+            mu,
+        });
+    } else if matches.is_present("ips-moran") {
+        // Moran process, parameters are the fitnesses of the types
+        let fitness: Vec<f64> = matches.get_many::<f64>("ips-moran").unwrap()
+            .copied()
+            .collect();
+
+        coloration = Box::new(MoranProcess {
+            fitness: fitness.clone(),
+        });
+
+        ips_rules = Box::new(MoranProcess {
+            fitness,
+        });
+    } else if matches.is_present("ips-noisy-voter") {
+        // noisy voter model on specified number of parties, with specified copy and noise rate
+        let mut values = matches.get_many::<String>("ips-noisy-voter").unwrap();
+        assert_eq!(values.len(), 3); // raise argument error
+        let nr_parties = values.next().unwrap().parse::<usize>()
+            .expect("The number of parties should be a whole number!");
+        assert!(nr_parties >= 2); // otherwise there is no other party to switch to
+        let copy_rate = values.next().unwrap().parse::<f64>().unwrap();
+        let noise_rate = values.next().unwrap().parse::<f64>().unwrap();
+
+        coloration = Box::new(NoisyVoter {
+            nr_parties,
+            copy_rate,
+            noise_rate,
+        });
+
+        ips_rules = Box::new(NoisyVoter {
+            nr_parties,
+            copy_rate,
+            noise_rate,
+        });
+    } else if matches.is_present("ips-potts") {
+        // Potts model, parameters are the nr. of spin values and the inverse temperature
+        let mut values = matches.get_many::<String>("ips-potts").unwrap();
+        assert_eq!(values.len(), 2); // raise argument error
+        let q = values.next().unwrap().parse::<usize>()
+            .expect("The number of spin values should be a whole number!");
+        let beta = values.next().unwrap().parse::<f64>().unwrap();
+
+        coloration = Box::new(PottsModel {
+            q,
+            beta,
+        });
+
+        ips_rules = Box::new(PottsModel {
+            q,
+            beta,
+        });
+    } else if matches.is_present("ips-schelling") {
+        // Schelling model, parameter is the tolerance
+        let tolerance = *matches.get_one::<f64>("ips-schelling").unwrap();
+
+        coloration = Box::new(Schelling {
+            tolerance,
+        });
+
+        ips_rules = Box::new(Schelling {
+            tolerance,
+        });
+    } else if matches.is_present("ips-forest-fire") {
+        // Forest-fire model, parameters are growth, lightning and burn rate
+        let mut values = matches.get_many::<f64>("ips-forest-fire").unwrap();
+        assert_eq!(values.len(), 3); // raise argument error
+        let growth_rate = *values.next().unwrap();
+        let lightning_rate = *values.next().unwrap();
+        let burn_rate = *values.next().unwrap();
+
+        coloration = Box::new(ForestFire {
+            growth_rate,
+            lightning_rate,
+            burn_rate,
+        });
+
+        ips_rules = Box::new(ForestFire {
+            growth_rate,
+            lightning_rate,
+            burn_rate,
+        });
+    } else if matches.is_present("ips-multistrain") {
+        // Multitype contact process, parameters are the birth and death rates of the strains
+        let values: Vec<f64> = matches.get_many::<f64>("ips-multistrain").unwrap()
+            .copied()
+            .collect();
+        assert_eq!(values.len() % 2, 0); // raise argument error, rates come in pairs
+        let birth_rates: Vec<f64> = values.iter().step_by(2).copied().collect();
+        let death_rates: Vec<f64> = values.iter().skip(1).step_by(2).copied().collect();
+
+        coloration = Box::new(MultiStrainContact {
+            birth_rates: birth_rates.clone(),
+            death_rates: death_rates.clone(),
+        });
+
+        ips_rules = Box::new(MultiStrainContact {
+            birth_rates,
+            death_rates,
+        });
     } else {
         panic!("No other processes implemented")
     }
 
+    // Optionally override the color of the state 0
+    let coloration: Box<dyn Coloration> = if matches.is_present("background-color") {
+        let rgb: Vec<u8> = matches.get_many::<u8>("background-color").unwrap().copied().collect();
+        Box::new(RecoloredBackground { inner: coloration, background: [rgb[0], rgb[1], rgb[2], 255] })
+    } else {
+        coloration
+    };
+
     // Pretty print ips description
     println!("Interacting particle system:");
     ips_rules.describe();
     println!();
 
+    // The rules are moved into the solver, so keep their states for the plots afterwards
+    let ips_states = ips_rules.all_states();
+
     // Make initial condition from provided arguments
     let initial_condition: Vec<usize>;
 
@@ -276,12 +952,66 @@ fn main() {
         }
 
         initial_condition = assemble_initial_condition(0, different_particles_hashmap, graph.nr_points())
+    } else if matches.is_present("initial-ring") {
+        // ring of particles in a certain state at a fixed distance from a center particle
+        let mut values = matches.get_many::<usize>("initial-ring").unwrap();
+        let ring_state = *values.next().unwrap();
+        let center = *values.next().unwrap();
+        let radius_steps = *values.next().unwrap();
+
+        initial_condition = assemble_ring_initial_condition(graph.as_ref(), center, radius_steps, ring_state, 0)
+    } else if matches.is_present("initial-rectangle") {
+        // rectangle of particles in a certain state on a 2d grid
+        let mut dimensions = matches.get_many::<usize>("graph-grid-nd").unwrap();
+        assert_eq!(dimensions.len(), 2); // only makes sense for 2d grids
+        let dim_x = *dimensions.next().unwrap();
+        let dim_y = *dimensions.next().unwrap();
+
+        let mut values = matches.get_many::<usize>("initial-rectangle").unwrap();
+        let seed_state = *values.next().unwrap();
+        let rect = (
+            *values.next().unwrap(),
+            *values.next().unwrap(),
+            *values.next().unwrap(),
+            *values.next().unwrap(),
+        );
+
+        initial_condition = assemble_rectangular_seed(dim_x, dim_y, rect, 0, seed_state)
+    } else if matches.is_present("initial-weighted") {
+        // random initial condition, with specified weights for the states
+        let values: Vec<&String> = matches.get_many::<String>("initial-weighted").unwrap().collect();
+        assert_eq!(values.len() % 2, 0); // raise argument error, need pairs of state and weight
+
+        let mut states_with_weights: Vec<(usize, f64)> = vec![];
+        for pair in values.chunks(2) {
+            states_with_weights.push((
+                pair[0].parse::<usize>().expect("The states should be whole numbers!"),
+                pair[1].parse::<f64>().unwrap(),
+            ));
+        }
+
+        initial_condition = assemble_weighted_initial_condition(&states_with_weights, graph_nr_points, &mut rand::thread_rng())
+            .expect("Weights should be nonnegative and not all zero!")
+    } else if matches.is_present("initial-image") {
+        // read the states from the colors of an image, using the coloration of the process
+        let mut color_to_state: HashMap<[u8; 3], usize> = HashMap::new();
+        for state in ips_rules.all_states() {
+            let [r, g, b, _] = coloration.get_color(state);
+            color_to_state.insert([r, g, b], state);
+        }
+
+        initial_condition = assemble_initial_condition_from_image(
+            matches.get_one::<String>("initial-image").unwrap(),
+            &color_to_state,
+            graph_nr_points,
+            false,
+        ).unwrap()
     } else {
         panic!("Initial condition not recognized!")
     }
 
     // Make halting condition from provided arguments
-    let halting_condition: HaltCondition;
+    let mut halting_condition: HaltCondition;
 
     if matches.is_present("halt-time-passed") {
         halting_condition = HaltCondition::TimePassed(
@@ -295,9 +1025,19 @@ fn main() {
         halting_condition = HaltCondition::StepsTaken(
             *matches.get_one::<u64>("halt-steps-taken").unwrap()
         )
+    } else if matches.is_present("halt-quiet-period") {
+        halting_condition = HaltCondition::QuietPeriod(
+            *matches.get_one::<f64>("halt-quiet-period").unwrap()
+        )
     } else {
         panic!("Halting condition not recognized!")
     }
+    if let Some(min_steps) = matches.get_one::<u64>("halt-min-steps") {
+        halting_condition = HaltCondition::All(vec![halting_condition, HaltCondition::StepsTaken(*min_steps)]);
+    }
+    if let Some(max_steps) = matches.get_one::<u64>("halt-max-steps") {
+        halting_condition = HaltCondition::Any(vec![halting_condition, HaltCondition::StepsTaken(*max_steps)]);
+    }
 
     // Make record condition from provided arguments
     let mut record_condition = RecordCondition::Final();
@@ -312,69 +1052,242 @@ fn main() {
         record_condition = RecordCondition::ConstantTime(
             *matches.get_one::<f64>("record-constant-time").unwrap()
         )
+    } else if matches.is_present("record-constant-time-deduplicated") {
+        record_condition = RecordCondition::ConstantTimeDeduplicated(
+            *matches.get_one::<f64>("record-constant-time-deduplicated").unwrap()
+        )
+    } else if matches.is_present("record-adaptive") {
+        let mut values = matches.get_many::<f64>("record-adaptive").unwrap();
+        record_condition = RecordCondition::AdaptiveRate {
+            min_interval: *values.next().unwrap(),
+            max_interval: *values.next().unwrap(),
+        }
+    } else if matches.is_present("record-reservoir") {
+        record_condition = RecordCondition::ReservoirSample(
+            *matches.get_one::<usize>("record-reservoir").unwrap()
+        )
+    }
+
+    // Make recorded region from provided arguments
+    let mut record_region = None;
+    // Width of the recorded snapshots when drawn as an image
+    let mut snapshot_width = graph_width;
+
+    if matches.is_present("record-window") {
+        // rectangular window on a 2d grid
+        let mut dimensions = matches.get_many::<usize>("graph-grid-nd").unwrap();
+        assert_eq!(dimensions.len(), 2); // only makes sense for 2d grids
+        let dim_x = *dimensions.next().unwrap();
+        let dim_y = *dimensions.next().unwrap();
+
+        let mut values = matches.get_many::<usize>("record-window").unwrap();
+        let x = *values.next().unwrap();
+        let y = *values.next().unwrap();
+        let width = *values.next().unwrap();
+        let height = *values.next().unwrap();
+        assert!(x + width <= dim_x && y + height <= dim_y); // window should fit in the grid
+        snapshot_width = Some(width as u32);
+
+        // row by row, so that the images show the window as it lies in the grid
+        record_region = Some(
+            (y..y + height).flat_map(|j| (x..x + width).map(move |i| i + dim_x * j)).collect()
+        );
     }
 
 
     /* Run simulation */
     let now = Instant::now();
 
-    let (solution, final_state, time_simulated, steps_recorded, steps_taken)
-        = particle_system_solver(
+    let solution = particle_system_solver_checked(
         ips_rules,
         graph,
         initial_condition,
         halting_condition,
         record_condition,
+        SolverOptions {
+            record_reactivity_trace: matches.is_present("reactivity-trace"),
+            record_events: matches.is_present("event-log") || matches.is_present("activity-heatmap"),
+            record_region,
+            rate_multipliers: None,
+            frozen: match matches.get_many::<usize>("zealots") {
+                Some(zealots) => zealots.copied().collect(),
+                None => HashSet::new(),
+            },
+            immunized: match matches.get_many::<usize>("immunized") {
+                Some(immunized) => immunized.copied().collect(),
+                None => HashSet::new(),
+            },
+            time_scale: matches.get_one::<f64>("time-scale").copied(),
+            burn_in_time: matches.get_one::<f64>("burn-in").copied().unwrap_or(0.0),
+            on_record: None,
+            sparse_to_state: matches.is_present("sparse-to-state"),
+            max_record_bytes: matches.get_one::<usize>("max-record-megabytes")
+                .map(|megabytes| megabytes * 1_000_000),
+        },
         rand::thread_rng(),
-    );
+    )?;
 
     let elapsed = now.elapsed();
 
     /* Give some statistics of the simulation */
     println!("Thought for {:.2?}.", elapsed);
     println!("Simulated {:.2?} time units, in which {} steps were taken, and {} were recorded.",
-             time_simulated, steps_taken, steps_recorded);
-    let mut state_counts: HashMap<usize, usize> = HashMap::new();
-    for particle_state in final_state {
-        state_counts.insert(particle_state, state_counts.get(&particle_state).unwrap_or(&0usize) + 1);
-    }
+             solution.time_simulated, solution.steps_taken, solution.steps_recorded);
 
     /* Give some statistics of the final state */
-    println!("The final state has the following counts: {:?}.", state_counts);
+    println!("The final state has the following counts: {:?}.", solution.final_state_counts);
+
+    /* Write the reactivity trace */
+    if let Some(trace_name) = matches.get_one::<String>("reactivity-trace") {
+        let mut trace_file = File::create(trace_name).unwrap();
+        writeln!(trace_file, "time,total_reactivity").unwrap();
+        for (time, total_reactivity) in solution.reactivity_trace.as_ref().unwrap() {
+            writeln!(trace_file, "{},{}", time, total_reactivity).unwrap();
+        }
+    }
+
+    if let Some(log_name) = matches.get_one::<String>("event-log") {
+        let mut log_file = File::create(log_name).unwrap();
+        writeln!(log_file, "time,site,old_state,new_state").unwrap();
+        for event in &solution.events {
+            writeln!(log_file, "{},{},{},{}", event.time, event.site, event.old_state, event.new_state).unwrap();
+        }
+    }
+
+    if let Some(heatmap_name) = matches.get_one::<String>("activity-heatmap") {
+        let dimensions: Vec<u32> = matches.get_many::<usize>("graph-grid-nd").unwrap()
+            .map(|dimension| *dimension as u32)
+            .collect();
+        assert_eq!(dimensions.len(), 2); // only makes sense for 2d grids
+        assert_eq!(heatmap_name[heatmap_name.len() - 4..], *".png");
+
+        save_activity_heatmap([dimensions[0], dimensions[1]], &solution.events, heatmap_name).unwrap();
+    }
+
+    if let Some(plot_name) = matches.get_one::<String>("counts-plot") {
+        save_counts_plot(&solution, &ips_states, coloration.as_ref(), plot_name).unwrap();
+    }
 
     /* Pack simulation into image */
+    // Number of particles in each snapshot
+    let snapshot_len = solution.recorded_indices.as_ref().map_or(graph_nr_points, |indices| indices.len());
+
     if matches.is_present("image-growth") {
         // save as growth image
-        let img_x = graph_nr_points;
+        let img_x = snapshot_len;
         let img_name = matches.get_one::<String>("output").unwrap();
         assert_eq!(img_name[img_name.len() - 4..], *".png");
 
         save_as_growth_img(
             coloration,
-            solution,
+            solution.snapshots,
             img_name,
             img_x as u32,
-        )
+        ).unwrap()
     } else if matches.is_present("image-gif") {
         // save as gif
         let mut values = matches.get_many::<u32>("image-gif").unwrap();
         let img_y = values.next().unwrap();
-        let img_x = graph_nr_points as u32 / img_y;
+        let img_x = snapshot_width.ok_or("A gif can only be made of a 1d or 2d grid!")?;
         let ms_per_frame = values.next().unwrap();
         let img_name = matches.get_one::<String>("output").unwrap();
         assert_eq!(img_name[img_name.len() - 4..], *".gif");
 
         save_as_gif(
             coloration,
-            solution,
+            solution.snapshots,
+            img_name,
+            [img_x, *img_y],
+            snapshot_len,
+            *ms_per_frame,
+            matches.is_present("gif-loop"),
+            if matches.is_present("gif-timestamps") { Some(&solution.times) } else { None },
+        )?
+    } else if matches.is_present("image-slice-montage") {
+        // save as gif with the z-slices of a 3d grid side by side
+        let dimensions: Vec<u32> = matches.get_many::<usize>("graph-grid-nd").unwrap()
+            .map(|dimension| *dimension as u32)
+            .collect();
+        assert_eq!(dimensions.len(), 3); // only makes sense for 3d grids
+        let ms_per_frame = matches.get_one::<u32>("image-slice-montage").unwrap();
+        let img_name = matches.get_one::<String>("output").unwrap();
+        assert_eq!(img_name[img_name.len() - 4..], *".gif");
+
+        save_as_slice_montage(
+            coloration,
+            solution.snapshots,
+            [dimensions[0], dimensions[1], dimensions[2]],
             img_name,
+            *ms_per_frame,
+            matches.is_present("gif-loop"),
+        )?
+    } else if matches.is_present("image-png-frames") {
+        // save as directory of png frames
+        let mut values = matches.get_many::<u32>("image-png-frames").unwrap();
+        let img_y = values.next().unwrap();
+        let img_x = snapshot_width.ok_or("Png frames can only be made of a 1d or 2d grid!")?;
+        let scale = values.next().unwrap();
+        let dir_name = matches.get_one::<String>("output").unwrap();
+
+        save_frames_as_pngs(
+            coloration,
+            solution.snapshots,
+            dir_name,
             img_x,
             *img_y,
-            *ms_per_frame,
-        )
+            *scale,
+        )?
+    } else if matches.is_present("image-final") {
+        // save the final state as png
+        let mut values = matches.get_many::<u32>("image-final").unwrap();
+        let img_y = values.next().unwrap();
+        // The final state is of the whole graph, also if only a region was recorded
+        let img_x = graph_width.ok_or("An image can only be made of a 1d or 2d grid!")?;
+        let scale = values.next().unwrap();
+        let img_name = matches.get_one::<String>("output").unwrap();
+        assert_eq!(img_name[img_name.len() - 4..], *".png");
+
+        save_final_as_image(
+            coloration.as_ref(),
+            &solution,
+            img_name,
+            img_x,
+            *img_y,
+            *scale,
+        )?
     } else {
         panic!("Image output kind not recognized!");
     }
 
     /* Done */
-}
\ No newline at end of file
+    Ok(())
+}
+
+/// The voter process specified by the values of the `--ips-voter` argument. The values are of
+/// different types, so they are only converted here.
+fn voter_from_args(matches: &ArgMatches) -> VoterProcess {
+    let mut values = matches.get_many::<String>("ips-voter").unwrap();
+    assert_eq!(values.len(), 2); // raise argument error
+    let nr_parties = values.next().unwrap().parse::<usize>()
+        .expect("The number of parties should be a whole number!");
+    let change_rate = values.next().unwrap().parse::<f64>().unwrap();
+
+    VoterProcess {
+        nr_parties,
+        change_rate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voter_arguments_round_trip() {
+        let matches = cli().try_get_matches_from(["cmd", "--ips-voter", "3", "0.5"]).unwrap();
+
+        let voter = voter_from_args(&matches);
+
+        assert_eq!((voter.nr_parties, voter.change_rate), (3, 0.5));
+    }
+}
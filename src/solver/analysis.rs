@@ -0,0 +1,483 @@
+use std::collections::VecDeque;
+
+use rand::Rng;
+
+use crate::solver::{HaltCondition, particle_system_solver, RecordCondition, Solution, SolverOptions};
+use crate::solver::ensemble::System;
+use crate::solver::graph::Graph;
+
+/// Sizes of the connected clusters of particles in state `target_state` in the snapshot
+/// `state_slice`, where two particles are connected if they are neighbors in `graph`. The sizes
+/// are sorted in increasing order.
+pub fn cluster_sizes(graph: &dyn Graph, state_slice: &[usize], target_state: usize) -> Vec<usize> {
+    assert_eq!(state_slice.len(), graph.nr_points());
+
+    let mut clusters = UnionFind::new(graph.nr_points());
+    for (i, state) in state_slice.iter().enumerate() {
+        if *state != target_state {
+            continue;
+        }
+        for j in graph.get_neighbors(i) {
+            if state_slice[j] == target_state {
+                clusters.union(i, j);
+            }
+        }
+    }
+
+    // Every cluster is counted at its root
+    let mut sizes: Vec<usize> = vec![];
+    for (i, state) in state_slice.iter().enumerate() {
+        if *state == target_state && clusters.find(i) == i {
+            sizes.push(clusters.size[i]);
+        }
+    }
+    sizes.sort_unstable();
+
+    sizes
+}
+
+/// Sizes of the connected components of `graph` itself, regardless of any states, e.g., to study
+/// the percolation threshold of a `DilutedLattice`. Isolated points are components of size 1. The
+/// sizes are sorted in increasing order, so the largest (giant) component comes last.
+pub fn component_sizes(graph: &dyn Graph) -> Vec<usize> {
+    let mut components = UnionFind::new(graph.nr_points());
+    for i in 0..graph.nr_points() {
+        for j in graph.get_neighbors(i) {
+            components.union(i, j);
+        }
+    }
+
+    // Every component is counted at its root
+    let mut sizes: Vec<usize> = vec![];
+    for i in 0..graph.nr_points() {
+        if components.find(i) == i {
+            sizes.push(components.size[i]);
+        }
+    }
+    sizes.sort_unstable();
+
+    sizes
+}
+
+/// Does the state `target_state` percolate in the snapshot `state_slice` of a 2D grid of size
+/// `dim_x` by `dim_y` (indexed as in `GridND`, `x + dim_x * y`)? Whether a direction is cyclic is
+/// read off from `graph`. In a cyclic direction, the state percolates if some cluster is connected
+/// to itself across the periodic boundary, i.e., it wraps around the torus. In an acyclic
+/// direction, it percolates if some cluster spans from one boundary to the other. Directions of
+/// size 2 are treated as acyclic, since there the periodic boundary can't be told apart.
+pub fn percolates(graph: &dyn Graph, state_slice: &[usize], target_state: usize, dim_x: usize, dim_y: usize) -> bool {
+    assert_eq!(dim_x * dim_y, graph.nr_points());
+    assert_eq!(state_slice.len(), graph.nr_points());
+
+    // A direction is cyclic if the first point is glued to the last point in that direction
+    let cyclic_x = dim_x > 2 && graph.get_neighbors(0).contains(&(dim_x - 1));
+    let cyclic_y = dim_y > 2 && graph.get_neighbors(0).contains(&(dim_x * (dim_y - 1)));
+
+    // Step along an edge in a single direction, unwrapped (so crossing the boundary is a step of 1)
+    let step = |from: usize, to: usize, dimension: usize, cyclic: bool| -> isize {
+        let step = to as isize - from as isize;
+        if cyclic && step.unsigned_abs() == dimension - 1 { -step.signum() } else { step }
+    };
+
+    // Position of the visited points in the unwrapped plane. Visiting a point of the same cluster
+    // again at a different position means that the cluster wraps around.
+    let mut unwrapped: Vec<Option<(isize, isize)>> = vec![None; graph.nr_points()];
+
+    for start in 0..graph.nr_points() {
+        if state_slice[start] != target_state || unwrapped[start].is_some() {
+            continue;
+        }
+
+        // Breadth first search through the cluster of `start`
+        let (mut min_x, mut max_x) = (start % dim_x, start % dim_x);
+        let (mut min_y, mut max_y) = (start / dim_x, start / dim_x);
+        unwrapped[start] = Some(((start % dim_x) as isize, (start / dim_x) as isize));
+        let mut queue: VecDeque<usize> = VecDeque::from([start]);
+
+        while let Some(current) = queue.pop_front() {
+            let (current_x, current_y) = unwrapped[current].unwrap();
+
+            for neighbor in graph.get_neighbors(current) {
+                if state_slice[neighbor] != target_state {
+                    continue;
+                }
+
+                let position = (
+                    current_x + step(current % dim_x, neighbor % dim_x, dim_x, cyclic_x),
+                    current_y + step(current / dim_x, neighbor / dim_x, dim_y, cyclic_y),
+                );
+
+                match unwrapped[neighbor] {
+                    Some(previous) => {
+                        if previous != position { // wraps around
+                            return true;
+                        }
+                    }
+                    None => {
+                        unwrapped[neighbor] = Some(position);
+                        min_x = min_x.min(neighbor % dim_x);
+                        max_x = max_x.max(neighbor % dim_x);
+                        min_y = min_y.min(neighbor / dim_x);
+                        max_y = max_y.max(neighbor / dim_x);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+
+        // Spanning from boundary to boundary in an acyclic direction
+        if (!cyclic_x && min_x == 0 && max_x == dim_x - 1) || (!cyclic_y && min_y == 0 && max_y == dim_y - 1) {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Equal-time two-point correlation of the snapshot `state_slice` of a toroidal grid with the
+/// given dimensions (indexed as in `GridND`), for the distances `0` up to and including
+/// `max_distance`. The indicator is whether two particles are in the same state: the correlation
+/// at distance `r` is the fraction of the pairs of particles `r` steps apart along one of the
+/// axes (with cyclic boundaries) that are in the same state.
+///
+/// So the correlation is 1 at distance 0, and for a uniform snapshot at all distances. This is the
+/// raw correlation, not the connected one: for an uncorrelated snapshot where state `k` has
+/// density `p_k`, it is close to the sum of the `p_k^2` at all positive distances.
+pub fn spatial_correlation(dims: &[usize], state_slice: &[usize], max_distance: usize) -> Vec<f64> {
+    let nr_points: usize = dims.iter().product();
+    assert_eq!(state_slice.len(), nr_points);
+
+    let mut correlation = Vec::with_capacity(max_distance + 1);
+    for distance in 0..=max_distance {
+        let mut nr_pairs = 0;
+        let mut nr_equal = 0;
+
+        // Pairs along each axis in turn
+        let mut step_size = 1;
+        for &dimension in dims {
+            for (i, state) in state_slice.iter().enumerate() {
+                // the coordinate of the point in the current dimension, moved `distance` steps on
+                let coordinate = i / step_size % dimension;
+                let moved_coordinate = (coordinate + distance) % dimension;
+                let j = i + moved_coordinate * step_size - coordinate * step_size;
+
+                nr_pairs += 1;
+                if state_slice[j] == *state {
+                    nr_equal += 1;
+                }
+            }
+            step_size *= dimension;
+        }
+
+        correlation.push(nr_equal as f64 / nr_pairs as f64);
+    }
+
+    correlation
+}
+
+/// Interface density of the snapshot `state_slice`: the fraction of the edges of `graph` whose
+/// endpoints are in different states. Decays as the domains coarsen (e.g., in the voter process),
+/// and is 0 at consensus. Every vertex is visited once, so every (undirected) edge is counted from
+/// both endpoints, which doesn't change the fraction. Returns 0 for a graph without edges.
+pub fn interface_density(graph: &dyn Graph, state_slice: &[usize]) -> f64 {
+    assert_eq!(state_slice.len(), graph.nr_points());
+
+    let mut nr_edges = 0;
+    let mut nr_discordant = 0;
+    for (i, state) in state_slice.iter().enumerate() {
+        for j in graph.get_neighbors(i) {
+            nr_edges += 1;
+            if state_slice[j] != *state {
+                nr_discordant += 1;
+            }
+        }
+    }
+
+    if nr_edges == 0 {
+        0.0
+    } else {
+        nr_discordant as f64 / nr_edges as f64
+    }
+}
+
+/// Degree assortativity coefficient of `graph`: the Pearson correlation between the degrees of
+/// the two endpoints of an edge, over all edges. Positive if points tend to connect to points of
+/// similar degree, negative if high-degree points tend to connect to low-degree points (e.g., in a
+/// star graph). Every edge is counted in both directions, so the coefficient is symmetric. NaN if
+/// the degrees at the endpoints don't vary (e.g., for a regular graph) or there are no edges.
+pub fn assortativity(graph: &dyn Graph) -> f64 {
+    let neighbors: Vec<_> = (0..graph.nr_points()).map(|i| graph.get_neighbors(i)).collect();
+    let degrees: Vec<f64> = neighbors.iter().map(|neighs| neighs.len() as f64).collect();
+
+    let mut nr_edges = 0.0;
+    let mut sum = 0.0; // the sum of the degrees at either end, which are the same
+    let mut sum_squares = 0.0;
+    let mut sum_products = 0.0;
+    for (i, neighs) in neighbors.iter().enumerate() {
+        for &j in neighs {
+            nr_edges += 1.0;
+            sum += degrees[i];
+            sum_squares += degrees[i] * degrees[i];
+            sum_products += degrees[i] * degrees[j];
+        }
+    }
+
+    let mean = sum / nr_edges;
+    (sum_products / nr_edges - mean * mean) / (sum_squares / nr_edges - mean * mean)
+}
+
+/// Eccentricity of every point of `graph`, i.e., the largest graph distance to any point it is
+/// connected to. See `Graph::eccentricities`. Takes a breadth-first search from every point, so
+/// `O(V * E)` time for `V` points and `E` edges.
+pub fn eccentricities(graph: &dyn Graph) -> Vec<usize> {
+    graph.eccentricities()
+}
+
+/// Diameter of `graph`, i.e., the largest graph distance between two connected points, which
+/// bounds how many steps information needs to spread. See `Graph::diameter`. Takes `O(V * E)`
+/// time for `V` points and `E` edges.
+pub fn diameter(graph: &dyn Graph) -> usize {
+    graph.diameter()
+}
+
+/// Radius of `graph`, i.e., the smallest eccentricity of any point. The points with this
+/// eccentricity form the center of the graph. Takes `O(V * E)` time for `V` points and `E` edges.
+pub fn radius(graph: &dyn Graph) -> usize {
+    graph.eccentricities().into_iter().min().unwrap_or(0)
+}
+
+/// Changes between consecutive snapshots of `solution`: the `i`th entry holds the
+/// `(site, old_state, new_state)` of every particle whose state differs between snapshot `i` and
+/// `i + 1`, by increasing position in the snapshot. The site is the index of the particle in the
+/// graph, also if only a region was recorded. Useful for compressing the output when few particles
+/// change between snapshots.
+pub fn snapshot_diffs(solution: &Solution) -> Vec<Vec<(usize, usize, usize)>> {
+    let snapshot_length = solution.recorded_indices.as_ref().map_or(solution.final_state.len(), Vec::len);
+    if snapshot_length == 0 {
+        return vec![];
+    }
+    let site = |position: usize| -> usize {
+        solution.recorded_indices.as_ref().map_or(position, |indices| indices[position])
+    };
+
+    let snapshots: Vec<&[usize]> = solution.snapshots.chunks_exact(snapshot_length).collect();
+    snapshots.windows(2)
+        .map(|pair| pair[0].iter().zip(pair[1].iter()).enumerate()
+            .filter(|(_, (old_state, new_state))| old_state != new_state)
+            .map(|(position, (&old_state, &new_state))| (site(position), old_state, new_state))
+            .collect())
+        .collect()
+}
+
+/// Self-test of the time stepping of `particle_system_solver`. Simulates the first `n_events`
+/// events of `system`, and returns the mean time between events, together with the mean it should
+/// have: the time until the next event is exponentially distributed with the total reactivity as
+/// rate, so the expected mean is the mean of `1 / total_reactivity` over the steps. These should
+/// agree up to a few percent for a thousand events or more. Stops early if the system is absorbed.
+pub fn validate_event_times<R: Rng>(system: System, n_events: usize, rng: R) -> (f64, f64) {
+    assert!(n_events > 0);
+    let (ips_rules, graph, initial_condition) = system;
+
+    // Record the total reactivity at every step, but none of the particles
+    let solution = particle_system_solver(
+        ips_rules,
+        graph,
+        initial_condition,
+        HaltCondition::StepsTaken(n_events as u64 - 1), // the halting condition is checked before each step
+        RecordCondition::EveryNthStep(1),
+        SolverOptions {
+            record_reactivity_trace: true,
+            record_region: Some(vec![]),
+            ..Default::default()
+        },
+        rng,
+    );
+
+    // The last entry of the trace is the final state, after the last event
+    let trace = solution.reactivity_trace.unwrap();
+    let steps = &trace[..trace.len() - 1];
+    assert!(!steps.is_empty(), "No events happened!");
+
+    let mut previous_time = 0.0;
+    let mut total_time_steps = 0.0;
+    let mut total_expected = 0.0;
+    for (time, total_reactivity) in steps {
+        total_time_steps += time - previous_time;
+        total_expected += 1.0 / total_reactivity;
+        previous_time = *time;
+    }
+
+    (total_time_steps / steps.len() as f64, total_expected / steps.len() as f64)
+}
+
+/// Disjoint-set forest with union by size and path compression.
+struct UnionFind {
+    parent: Vec<usize>,
+    /// Size of the set, only correct for roots.
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(nr_elements: usize) -> UnionFind {
+        UnionFind {
+            parent: (0..nr_elements).collect(),
+            size: vec![1; nr_elements],
+        }
+    }
+
+    /// The root of the set containing `element`.
+    fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Compress the path, so later lookups are fast
+        let mut current = element;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+
+        root
+    }
+
+    /// Merge the sets containing `a` and `b`.
+    fn union(&mut self, a: usize, b: usize) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return;
+        }
+
+        // Attach the smaller tree to the larger one
+        let (large, small) = if self.size[root_a] >= self.size[root_b] { (root_a, root_b) } else { (root_b, root_a) };
+        self.parent[small] = large;
+        self.size[large] += self.size[small];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::solver::graph::diluted_lattice::DilutedLattice;
+    use crate::solver::graph::grid_n_d::GridND;
+    use crate::solver::graph::star::StarGraph;
+    use crate::solver::ips_rules::voter_process::VoterProcess;
+    use super::*;
+
+    #[test]
+    fn two_separate_clusters() {
+        let grid = GridND::from((vec![3, 3], vec![false, false]));
+        // The corner (0, 0) and the pair (1, 2), (2, 2)
+        let snapshot = vec![
+            1, 0, 0,
+            0, 0, 0,
+            0, 1, 1,
+        ];
+
+        assert_eq!(cluster_sizes(&grid, &snapshot, 1), vec![1, 2]);
+        assert_eq!(cluster_sizes(&grid, &snapshot, 0), vec![6]);
+    }
+
+    #[test]
+    fn full_row_percolates_horizontally() {
+        let torus = GridND::from(vec![4, 4]);
+        let square = GridND::from((vec![4, 4], vec![false, false]));
+        let row: Vec<usize> = (0..16).map(|i| (i / 4 == 1) as usize).collect();
+        let broken_row: Vec<usize> = (0..16).map(|i| (i / 4 == 1 && i != 6) as usize).collect();
+
+        assert!(percolates(&torus, &row, 1, 4, 4)); // wraps around
+        assert!(percolates(&square, &row, 1, 4, 4)); // spans from side to side
+        assert!(!percolates(&torus, &broken_row, 1, 4, 4));
+        assert!(!percolates(&square, &broken_row, 1, 4, 4));
+    }
+
+    #[test]
+    fn uniform_snapshot_is_fully_correlated() {
+        let correlation = spatial_correlation(&[5, 4], &[2; 20], 3);
+
+        assert_eq!(correlation, vec![1.0; 4]);
+        // A checkerboard on the other hand alternates
+        let checkerboard: Vec<usize> = (0..16).map(|i| (i % 4 + i / 4) % 2).collect();
+        assert_eq!(spatial_correlation(&[4, 4], &checkerboard, 2), vec![1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn alternating_ring_is_all_interface() {
+        let ring = GridND::from(vec![10]);
+        let alternating: Vec<usize> = (0..10).map(|i| i % 2).collect();
+
+        assert_eq!(interface_density(&ring, &alternating), 1.0);
+        assert_eq!(interface_density(&ring, &[3; 10]), 0.0);
+    }
+
+    #[test]
+    fn star_is_disassortative() {
+        // Every edge joins the hub to a leaf
+        let coefficient = assortativity(&StarGraph::new(6));
+
+        assert!(coefficient < 0.0);
+        assert!((coefficient + 1.0).abs() < 1e-12, "{}", coefficient);
+        assert!(assortativity(&GridND::from(vec![4, 4])).is_nan()); // regular
+    }
+
+    #[test]
+    fn components_of_full_and_empty_lattice() {
+        let full = DilutedLattice::new(5, 4, 1.0, StdRng::seed_from_u64(1));
+        let empty = DilutedLattice::new(5, 4, 0.0, StdRng::seed_from_u64(1));
+
+        assert_eq!(component_sizes(&full), vec![20]);
+        assert_eq!(component_sizes(&empty), vec![1; 20]);
+    }
+
+    /// Solution with the given snapshots of `snapshot_length` particles, recorded at times 0, 1, ...
+    fn solution_from_snapshots(snapshots: Vec<usize>, snapshot_length: usize, final_state: Vec<usize>,
+                               recorded_indices: Option<Vec<usize>>) -> Solution {
+        let nr_snapshots = snapshots.len() / snapshot_length;
+        Solution {
+            snapshots,
+            times: (0..nr_snapshots).map(|i| i as f64).collect(),
+            recorded_indices,
+            final_state_counts: HashMap::new(),
+            final_state,
+            time_simulated: (nr_snapshots - 1) as f64,
+            steps_recorded: nr_snapshots as u64,
+            steps_taken: nr_snapshots as u64,
+            reactivity_trace: None,
+            events: vec![],
+        }
+    }
+
+    #[test]
+    fn snapshot_diffs_single_change() {
+        let solution = solution_from_snapshots(vec![0, 1, 2, 0, 2, 2], 3, vec![0, 2, 2], None);
+
+        assert_eq!(snapshot_diffs(&solution), vec![vec![(1, 1, 2)]]);
+    }
+
+    #[test]
+    fn snapshot_diffs_of_region_are_at_graph_indices() {
+        // Region of the particles 4 and 7 out of 9, of which particle 7 changes
+        let solution = solution_from_snapshots(vec![1, 0, 1, 1], 2, vec![0; 9], Some(vec![4, 7]));
+
+        assert_eq!(snapshot_diffs(&solution), vec![vec![(7, 0, 1)]]);
+    }
+
+    #[test]
+    fn mean_event_time_matches_reactivity() {
+        let system: System = (
+            Box::new(VoterProcess { nr_parties: 3, change_rate: 1.0 }),
+            Box::new(GridND::from(vec![10, 10])),
+            (0..100).map(|i| i % 3).collect(),
+        );
+
+        let (empirical_mean, expected_mean) = validate_event_times(system, 5000, StdRng::seed_from_u64(1));
+
+        assert!((empirical_mean / expected_mean - 1.0).abs() < 0.03, "{} versus {}", empirical_mean, expected_mean);
+    }
+}
@@ -1,5 +1,10 @@
 use std::collections::{HashMap};
+use image::ImageError;
+use rand::distributions::{Distribution, WeightedError, WeightedIndex};
+use rand::Rng;
 use rand::seq::SliceRandom;
+use crate::solver::graph::{bfs_distances, Graph};
+use crate::solver::ips_rules::IPSRules;
 
 /// Make an initial condition of the appropriate size `grid_size` from prescribed data.
 /// Fill everything with the state `fill`, except for the indices in the hashmap.
@@ -15,6 +20,51 @@ pub fn assemble_initial_condition(fill: usize, different: HashMap<usize, usize>,
     initial_condition
 }
 
+/// Make an initial condition of the appropriate size `graph_size` where the state of the particle
+/// with index `i` is `f(i)`. For structured patterns that are easier to compute than to list.
+/// # Example
+/// Checkerboard pattern on a 40x40 grid
+/// ```
+/// let initial_condition = assemble_initial_condition_fn(40 * 40, |i| (i % 40 + i / 40) % 2);
+/// ```
+pub fn assemble_initial_condition_fn(graph_size: usize, f: impl Fn(usize) -> usize) -> Vec<usize> {
+    (0..graph_size).map(f).collect()
+}
+
+/// Make an initial condition of the appropriate size `graph_size` where the particles with indices
+/// in `block_indices` are in the state `block_state`, and all other particles in the state `fill`.
+pub fn assemble_block_initial_condition(fill: usize, block_state: usize, block_indices: &[usize], graph_size: usize) -> Vec<usize> {
+    let mut initial_condition: Vec<usize> = vec![fill; graph_size];
+
+    for i in block_indices {
+        initial_condition[*i] = block_state;
+    }
+
+    initial_condition
+}
+
+/// Make an initial condition on a 2D grid of `dim_x` by `dim_y` points where a rectangular region
+/// is in the state `seed_state`, and all other particles in the state `fill`. The rectangle is
+/// given as `rect = (x, y, width, height)`, where `(x, y)` is the corner with the smallest
+/// coordinates. Points are numbered as in `GridND`, i.e., the point `(x, y)` has index
+/// `x + dim_x * y`.
+pub fn assemble_rectangular_seed(dim_x: usize, dim_y: usize, rect: (usize, usize, usize, usize), fill: usize, seed_state: usize) -> Vec<usize> {
+    let (x_start, y_start, width, height) = rect;
+
+    // Make sure that the rectangle fits in the grid
+    assert!(x_start + width <= dim_x);
+    assert!(y_start + height <= dim_y);
+
+    let mut block_indices = vec![];
+    for y in y_start..y_start + height {
+        for x in x_start..x_start + width {
+            block_indices.push(x + dim_x * y);
+        }
+    }
+
+    assemble_block_initial_condition(fill, seed_state, &block_indices, dim_x * dim_y)
+}
+
 /// Make an initial condition of the appropriate size `grid_size` by sampling from a distribution.
 /// A random entry from the hash set `states` will be chosen. Weights can be assigned by repeating a
 /// particular state in the `states` vector.
@@ -30,4 +80,188 @@ pub fn assemble_random_initial_condition(states: Vec<usize>, grid_size: usize) -
     }
 
     initial_condition
-}
\ No newline at end of file
+}
+
+/// Make an initial condition of the appropriate size `graph_size` by sampling every particle
+/// independently from the states in `states_with_weights`, each with probability proportional to
+/// its weight. E.g., `[(0, 0.95), (1, 0.05)]` makes about 5% of the particles infected.
+///
+/// Returns an error if some weight is negative or if all weights are zero.
+pub fn assemble_weighted_initial_condition<R: Rng>(states_with_weights: &[(usize, f64)], graph_size: usize, rng: &mut R) -> Result<Vec<usize>, WeightedError> {
+    let distribution = WeightedIndex::new(states_with_weights.iter().map(|(_, weight)| weight))?;
+
+    let mut initial_condition: Vec<usize> = Vec::new();
+
+    for _ in 0..graph_size {
+        initial_condition.push(
+            states_with_weights[distribution.sample(rng)].0
+        );
+    }
+
+    Ok(initial_condition)
+}
+
+/// Make an initial condition on `graph` consisting of a ring around the point `center`. The points
+/// at graph distance exactly `radius_steps` from `center` are put in the state `ring_state`, all
+/// other points (inside and outside the ring) are put in the state `fill`.
+pub fn assemble_ring_initial_condition(graph: &dyn Graph, center: usize, radius_steps: usize, ring_state: usize, fill: usize) -> Vec<usize> {
+    let distances = bfs_distances(graph, center);
+
+    let mut initial_condition: Vec<usize> = Vec::new();
+
+    for distance in distances {
+        if distance == Some(radius_steps) {
+            initial_condition.push(ring_state)
+        } else {
+            initial_condition.push(fill)
+        }
+    }
+
+    initial_condition
+}
+
+/// Error raised when an initial condition contains a state that is not a state of the particle
+/// system: the particle with index `index` is in the state `state`.
+#[derive(Debug, PartialEq)]
+pub struct InvalidState {
+    pub index: usize,
+    pub state: usize,
+}
+
+/// Check that every particle of `initial_condition` is in one of the states of `rules`. Particles
+/// in other states would have rates of zero and never change, which is easy to miss.
+///
+/// # Errors
+/// Returns the first particle (by index) whose state is not in `rules.all_states()`.
+pub fn validate_initial_condition(rules: &dyn IPSRules, initial_condition: &[usize]) -> Result<(), InvalidState> {
+    let all_states = rules.all_states();
+
+    match initial_condition.iter().position(|state| !all_states.contains(state)) {
+        Some(index) => Err(InvalidState { index, state: initial_condition[index] }),
+        None => Ok(()),
+    }
+}
+
+/// Error raised when making an initial condition from an image.
+#[derive(Debug)]
+pub enum ImageConditionError {
+    /// The image could not be opened or decoded.
+    Image(ImageError),
+    /// The pixel at `(x, y)` has a color which does not correspond to any state.
+    UnknownColor { x: u32, y: u32, color: [u8; 3] },
+}
+
+impl From<ImageError> for ImageConditionError {
+    fn from(error: ImageError) -> Self {
+        ImageConditionError::Image(error)
+    }
+}
+
+/// Make an initial condition of the appropriate size `graph_size` from an image, where the color
+/// of each pixel determines the state of the corresponding particle. Pixels are read row by row,
+/// which matches the output of `save_as_gif` for 2D grids. The alpha channel is ignored.
+///
+/// Colors which are not in `color_to_state` are put in the state 0, or, if `error_on_unknown`,
+/// raise an error.
+pub fn assemble_initial_condition_from_image(path: &str, color_to_state: &HashMap<[u8; 3], usize>, graph_size: usize,
+                                             error_on_unknown: bool) -> Result<Vec<usize>, ImageConditionError> {
+    let img = image::open(path)?.to_rgb8();
+
+    // Make sure that the image has exactly one pixel for every particle
+    assert_eq!((img.width() * img.height()) as usize, graph_size);
+
+    let mut initial_condition: Vec<usize> = Vec::new();
+
+    for (x, y, pixel) in img.enumerate_pixels() {
+        match color_to_state.get(&pixel.0) {
+            Some(state) => { initial_condition.push(*state) }
+            None if error_on_unknown => {
+                return Err(ImageConditionError::UnknownColor { x, y, color: pixel.0 });
+            }
+            None => { initial_condition.push(0) }
+        }
+    }
+
+    Ok(initial_condition)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::solver::graph::grid_n_d::GridND;
+    use crate::solver::ips_rules::sir_process::SIRProcess;
+    use super::*;
+
+    #[test]
+    fn ring_at_exact_distance() {
+        let grid = GridND::from(vec![7, 7]);
+        let center = 3 + 7 * 3;
+
+        let initial_condition = assemble_ring_initial_condition(&grid, center, 2, 1, 0);
+
+        // The points at Manhattan distance 2 from (3, 3), the grid is too large to wrap around
+        for (i, &state) in initial_condition.iter().enumerate() {
+            let distance = (i % 7).abs_diff(3) + (i / 7).abs_diff(3);
+            assert_eq!(state, (distance == 2) as usize, "point {}", i);
+        }
+        assert_eq!(initial_condition.iter().filter(|&&state| state == 1).count(), 8);
+    }
+
+    #[test]
+    fn initial_condition_from_2x2_image() {
+        let path = std::env::temp_dir().join("rust_particle_system_initial.png");
+        let black = [0, 0, 0];
+        let red = [255, 0, 0];
+        let image = image::RgbImage::from_fn(2, 2, |x, y| image::Rgb(if x == y { red } else { black }));
+        image.save(&path).unwrap();
+        let color_to_state = HashMap::from([(black, 0), (red, 1)]);
+
+        let initial_condition = assemble_initial_condition_from_image(path.to_str().unwrap(), &color_to_state, 4, true);
+
+        // Row by row: (0, 0), (1, 0), (0, 1), (1, 1)
+        assert_eq!(initial_condition.unwrap(), vec![1, 0, 0, 1]);
+        let unknown = assemble_initial_condition_from_image(path.to_str().unwrap(), &HashMap::from([(black, 0)]), 4, true);
+        assert!(matches!(unknown, Err(ImageConditionError::UnknownColor { x: 0, y: 0, color: [255, 0, 0] })));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn centered_block_in_4x4_grid() {
+        let initial_condition = assemble_rectangular_seed(4, 4, (1, 1, 2, 2), 0, 1);
+
+        let block: Vec<usize> = (0..16).filter(|&i| initial_condition[i] == 1).collect();
+        assert_eq!(block, vec![5, 6, 9, 10]);
+        assert_eq!(initial_condition, assemble_block_initial_condition(0, 1, &[5, 6, 9, 10], 16));
+    }
+
+    #[test]
+    fn weighted_fractions_match_probabilities() {
+        let mut rng = StdRng::seed_from_u64(4);
+
+        let initial_condition = assemble_weighted_initial_condition(&[(0, 0.7), (1, 0.2), (2, 0.1)], 100 * 100, &mut rng)
+            .unwrap();
+
+        for (state, probability) in [(0, 0.7), (1, 0.2), (2, 0.1)] {
+            let fraction = initial_condition.iter().filter(|&&s| s == state).count() as f64 / 10_000.0;
+            assert!((fraction - probability).abs() < 0.02, "state {}: {}", state, fraction);
+        }
+        assert!(assemble_weighted_initial_condition(&[(0, 0.0)], 4, &mut rng).is_err());
+    }
+
+    #[test]
+    fn checkerboard_from_closure() {
+        // A 4 by 4 checkerboard, numbered as in `GridND`
+        let initial_condition = assemble_initial_condition_fn(16, |i| (i % 4 + i / 4) % 2);
+
+        assert_eq!(initial_condition, vec![0, 1, 0, 1, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0]);
+    }
+
+    #[test]
+    fn out_of_range_state_is_invalid() {
+        let rules = SIRProcess { birth_rate: 1.0, death_rate: 1.0 };
+
+        assert_eq!(validate_initial_condition(&rules, &[0, 2, 3, 1, 4]), Err(InvalidState { index: 2, state: 3 }));
+        assert_eq!(validate_initial_condition(&rules, &[0, 2, 1]), Ok(()));
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+
+use rand::distributions::{Distribution, WeightedError, WeightedIndex};
+use rand::Rng;
+
+use crate::Coloration;
+use crate::solver::{count_states, HaltCondition, RecordCondition, Solution};
+use crate::solver::exponential_distribution::StandardExponential;
+use crate::solver::graph::Graph;
+
+/// The Axelrod model of cultural dissemination. The culture of a site is a vector of
+/// `nr_features` features, each of which takes one of `nr_traits` traits. Every site interacts at
+/// rate 1 with a uniformly random neighbor, with probability the overlap of their cultures (the
+/// fraction of features in which they agree). In an interaction, the site copies one of the
+/// features in which they differ from the neighbor. Sites with identical or completely different
+/// cultures don't interact, so the model can freeze in a state with several cultures.
+///
+/// The culture is encoded as a single state, reading the features as the digits of a number in
+/// base `nr_traits` (the first feature is the least significant digit).
+pub struct Axelrod {
+    pub nr_features: usize,
+    pub nr_traits: usize,
+}
+
+impl Axelrod {
+    /// Number of different cultures, i.e., states.
+    pub fn nr_states(&self) -> usize {
+        self.nr_traits.pow(self.nr_features as u32)
+    }
+
+    /// Decode a state into its features.
+    pub fn features(&self, state: usize) -> Vec<usize> {
+        let mut remainder = state;
+        (0..self.nr_features).map(|_| {
+            let trait_value = remainder % self.nr_traits;
+            remainder /= self.nr_traits;
+            trait_value
+        }).collect()
+    }
+
+    /// Encode features into a state.
+    pub fn encode(&self, features: &[usize]) -> usize {
+        assert_eq!(features.len(), self.nr_features);
+        features.iter().rev().fold(0, |state, trait_value| state * self.nr_traits + trait_value)
+    }
+
+    /// Number of features in which the cultures `a` and `b` agree.
+    pub fn overlap(&self, a: usize, b: usize) -> usize {
+        self.features(a).iter().zip(self.features(b).iter())
+            .filter(|(trait_a, trait_b)| trait_a == trait_b)
+            .count()
+    }
+
+    /// Rate at which a site in culture `current` copies a feature from a neighbor in culture
+    /// `sender`, if it has `nr_neighbors` neighbors.
+    fn interaction_rate(&self, current: usize, sender: usize, nr_neighbors: usize) -> f64 {
+        let overlap = self.overlap(current, sender);
+        if overlap == 0 || overlap == self.nr_features { // no interaction, nothing to copy
+            0.0
+        } else {
+            overlap as f64 / self.nr_features as f64 / nr_neighbors as f64
+        }
+    }
+
+    /// Print a description of the model.
+    pub fn describe(&self) {
+        println!("Axelrod model with {} features of {} traits each.", self.nr_features, self.nr_traits)
+    }
+}
+
+impl Coloration for Axelrod {
+    // The features are distributed over the red, green and blue channels in turn, and every channel
+    // is the average of its features. Cultures that differ in a single trait get similar colors.
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        let mut color = [0u8, 0, 0, 255];
+        let features = self.features(state);
+
+        for (channel, value) in color.iter_mut().take(3).enumerate() {
+            let channel_features: Vec<usize> = features.iter().skip(channel).step_by(3).copied().collect();
+            if channel_features.is_empty() || self.nr_traits < 2 {
+                continue;
+            }
+            let mean = channel_features.iter().sum::<usize>() as f64 / channel_features.len() as f64;
+            *value = (255.0 * mean / (self.nr_traits - 1) as f64).round() as u8;
+        }
+
+        color
+    }
+}
+
+/// Simulator for the Axelrod model. The model doesn't fit the `IPSRules` framework: the number of
+/// states grows exponentially in the number of features, and `particle_system_solver` considers
+/// every state as a possible transition at every step. Here only the feature copies that can
+/// actually happen are considered.
+///
+/// # Parameters
+/// Same as `particle_system_solver`, except:
+/// * `model`: The number of features and traits.
+/// * For the quiet period halting condition, any change of culture counts as an infection.
+///
+/// # Outputs
+/// A `Solution` in the same format as that of `particle_system_solver`, without reactivity trace
+/// and events. The states are the encoded cultures.
+pub fn axelrod_solver<R: Rng>(
+    model: &Axelrod,
+    graph: Box<dyn Graph>,
+    initial_condition: Vec<usize>,
+    halting_condition: HaltCondition,
+    record_condition: RecordCondition,
+    mut rng: R,
+) -> Solution {
+    // * PHASE I: Initialization * //
+
+    let mut states: Vec<usize> = initial_condition;
+    assert_eq!(states.len(), graph.nr_points());
+    assert!(states.iter().all(|&state| state < model.nr_states()));
+
+    // The neighbors don't change, so look them up only once
+    let neighbors: Vec<Vec<usize>> = (0..graph.nr_points())
+        .map(|i| graph.get_neighbors(i).into_iter().collect())
+        .collect();
+
+    // Rate at which site i copies a feature from any of its neighbors
+    let reactivity = |states: &Vec<usize>, i: usize| -> f64 {
+        neighbors[i].iter()
+            .map(|&j| model.interaction_rate(states[i], states[j], neighbors[i].len()))
+            .sum()
+    };
+
+    let mut reactivities: Vec<f64> = (0..states.len()).map(|i| reactivity(&states, i)).collect();
+    let mut total_reactivity: f64 = reactivities.iter().sum();
+
+    // Initialize state record
+    let mut states_record: Vec<usize> = vec![];
+    let mut times: Vec<f64> = vec![];
+
+    // Initialize timekeeping
+    let mut time_passed = 0.0;
+    let mut time_last_recorded = 0.0;
+    let mut time_last_change = 0.0;
+    let mut steps_recorded = 0; // Number of snapshots in states_record
+    let mut steps_taken = 0;
+
+    // Initialize location-finding distribution. If no site can interact, the model is frozen.
+    let mut distr_location = WeightedIndex::new(&reactivities).ok();
+
+    // * PHASE 2: Simulation loop * //
+    while let Some(distr) = distr_location.as_mut() {
+        if !halting_condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_change) {
+            break;
+        }
+
+        /* Update timekeeping */
+        steps_taken += 1;
+
+        // Generate time step (until next event)
+        let time_step: f64 = {
+            let standard_exp_object: StandardExponential = rng.gen();
+            standard_exp_object.0 / total_reactivity
+        };
+        let mean_reactivity = total_reactivity / states.len() as f64;
+
+        time_passed += time_step;
+
+        /* Record the state */
+        // As in `particle_system_solver`, the state before the update is recorded
+        for _ in 0..record_condition.how_often_record(time_passed, time_step, steps_taken,
+                                                      time_last_recorded, mean_reactivity) {
+            states_record.extend_from_slice(&states);
+            times.push(time_passed);
+            time_last_recorded = time_passed;
+            steps_recorded += 1;
+            if !halting_condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_change) {
+                break;
+            }
+        }
+
+        /* Find the interacting site and neighbor */
+        let site = distr.sample(&mut rng);
+        let neighbor_rates: Vec<f64> = neighbors[site].iter()
+            .map(|&j| model.interaction_rate(states[site], states[j], neighbors[site].len()))
+            .collect();
+        let neighbor = neighbors[site][WeightedIndex::new(neighbor_rates).unwrap().sample(&mut rng)];
+
+        /* Copy a uniformly random differing feature */
+        let mut features = model.features(states[site]);
+        let neighbor_features = model.features(states[neighbor]);
+        let differing: Vec<usize> = (0..model.nr_features)
+            .filter(|&k| features[k] != neighbor_features[k])
+            .collect();
+        let feature = differing[rng.gen_range(0..differing.len())];
+        features[feature] = neighbor_features[feature];
+        states[site] = model.encode(&features);
+        time_last_change = time_passed;
+
+        /* Update reactivities */
+        // The rates of the site and its neighbors change
+        let mut changed: HashSet<usize> = neighbors[site].iter().copied().collect();
+        changed.insert(site);
+        let mut changed: Vec<usize> = changed.into_iter().collect();
+        changed.sort_unstable(); // sorting is required for .update_weights()
+
+        for &i in &changed {
+            total_reactivity -= reactivities[i];
+            reactivities[i] = reactivity(&states, i);
+            total_reactivity += reactivities[i];
+        }
+
+        let changing_weights: Vec<(usize, &f64)> = changed.iter().map(|&i| (i, &reactivities[i])).collect();
+        match distr.update_weights(&changing_weights[..]) {
+            Ok(_) => {}
+            Err(WeightedError::AllWeightsZero) => { distr_location = None; } // The model is frozen
+            Err(e) => { panic!("Changing weights: {:?}, Error: {}", changing_weights, e) }
+        };
+    }
+
+    // * PHASE III: Cleanup * //
+
+    // Record final state
+    states_record.extend_from_slice(&states);
+    times.push(time_passed);
+    steps_recorded += 1;
+
+    Solution {
+        snapshots: states_record,
+        times,
+        recorded_indices: None,
+        final_state_counts: count_states(&states),
+        final_state: states,
+        time_simulated: time_passed,
+        steps_recorded,
+        steps_taken,
+        reactivity_trace: None,
+        events: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::solver::graph::grid_n_d::GridND;
+    use super::*;
+
+    #[test]
+    fn identical_neighbors_never_interact() {
+        let model = Axelrod { nr_features: 3, nr_traits: 2 };
+
+        for state in 0..model.nr_states() {
+            assert_eq!(model.overlap(state, state), 3);
+            assert_eq!(model.interaction_rate(state, state, 4), 0.0);
+        }
+        // Differing in one feature out of three
+        assert_eq!(model.interaction_rate(model.encode(&[0, 1, 1]), model.encode(&[1, 1, 1]), 4), 2.0 / 3.0 / 4.0);
+    }
+
+    #[test]
+    fn single_culture_is_frozen() {
+        let model = Axelrod { nr_features: 3, nr_traits: 2 };
+
+        let solution = axelrod_solver(&model, Box::new(GridND::from(vec![4, 4])), vec![5; 16],
+                                      HaltCondition::TimePassed(10.0), RecordCondition::Final(),
+                                      StdRng::seed_from_u64(1));
+
+        assert_eq!(solution.final_state, vec![5; 16]);
+    }
+}
@@ -0,0 +1,207 @@
+#[cfg(feature = "serde")]
+use std::fs::create_dir_all;
+#[cfg(feature = "serde")]
+use std::io;
+#[cfg(feature = "serde")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "serde")]
+use rand::rngs::StdRng;
+#[cfg(feature = "serde")]
+use rand::SeedableRng;
+#[cfg(feature = "serde")]
+use rayon::prelude::*;
+
+use crate::solver::{HaltCondition, particle_system_solver, RecordCondition, SolverOptions};
+use crate::solver::graph::Graph;
+use crate::solver::ips_rules::IPSRules;
+
+/// The rules, graph and initial condition of a single simulation.
+pub type System = (Box<dyn IPSRules>, Box<dyn Graph>, Vec<usize>);
+
+/// Configuration of an ensemble of simulations, to be passed into `run_ensemble`. Every run is
+/// simulated for `time_horizon` time units, and recorded every `record_interval` time units.
+pub struct EnsembleConfig {
+    /// Makes the rules, graph and initial condition of a single run. Called once per run, so random
+    /// graphs and initial conditions are drawn anew for every run. Shared between threads by
+    /// `run_replicates_to_disk`.
+    pub make_system: Box<dyn Fn() -> System + Sync>,
+    pub time_horizon: f64,
+    pub record_interval: f64,
+}
+
+/// Output of `run_ensemble`: statistics over the runs at each recorded time. A particle counts as
+/// infected if it is not in the default state 0.
+#[derive(Debug)]
+pub struct EnsembleStats {
+    /// The recorded times, multiples of the record interval up to the time horizon.
+    pub times: Vec<f64>,
+    /// The fraction of runs with at least one infected particle.
+    pub survival_probability: Vec<f64>,
+    /// The mean number of infected particles over all runs (including the extinct ones).
+    pub mean_infected: Vec<f64>,
+    /// The (population) variance of the number of infected particles over all runs.
+    pub variance_infected: Vec<f64>,
+}
+
+/// Simulate the system of `config` `n_runs` times, each run with a fresh random number generator,
+/// and collect the statistics of the number of infected particles over time. Useful to estimate
+/// the survival probability of the contact process.
+pub fn run_ensemble(config: EnsembleConfig, n_runs: usize) -> EnsembleStats {
+    assert!(n_runs > 0);
+
+    let nr_times = (config.time_horizon / config.record_interval).floor() as usize;
+    let times: Vec<f64> = (1..=nr_times).map(|i| i as f64 * config.record_interval).collect();
+
+    // Infected counts, indexed by run and then by time
+    let mut infected_counts: Vec<Vec<usize>> = Vec::with_capacity(n_runs);
+
+    for _ in 0..n_runs {
+        let (ips_rules, graph, initial_condition) = (config.make_system)();
+        let nr_points = graph.nr_points();
+
+        let solution = particle_system_solver(
+            ips_rules,
+            graph,
+            initial_condition,
+            HaltCondition::TimePassed(config.time_horizon),
+            RecordCondition::ConstantTime(config.record_interval),
+            SolverOptions::default(),
+            rand::thread_rng(),
+        );
+
+        let count_infected = |snapshot: &[usize]| snapshot.iter().filter(|&&state| state != 0).count();
+
+        // A run stops early when it is absorbed, in which case it stays in its final state. The last
+        // step may also overshoot the time horizon, so recorded times after it are left out.
+        let final_count = count_infected(&solution.final_state);
+        let mut counts: Vec<usize> = solution.snapshots.chunks(nr_points)
+            .take(nr_times)
+            .map(count_infected)
+            .collect();
+        counts.resize(nr_times, final_count);
+
+        infected_counts.push(counts);
+    }
+
+    let mut survival_probability = Vec::with_capacity(nr_times);
+    let mut mean_infected = Vec::with_capacity(nr_times);
+    let mut variance_infected = Vec::with_capacity(nr_times);
+
+    for time_index in 0..nr_times {
+        let counts: Vec<f64> = infected_counts.iter().map(|run| run[time_index] as f64).collect();
+
+        let nr_surviving = counts.iter().filter(|&&count| count > 0.0).count();
+        let mean = counts.iter().sum::<f64>() / n_runs as f64;
+        let variance = counts.iter().map(|count| (count - mean).powi(2)).sum::<f64>() / n_runs as f64;
+
+        survival_probability.push(nr_surviving as f64 / n_runs as f64);
+        mean_infected.push(mean);
+        variance_infected.push(variance);
+    }
+
+    EnsembleStats {
+        times,
+        survival_probability,
+        mean_infected,
+        variance_infected,
+    }
+}
+
+/// Simulate the system of `config` `n` times in parallel, and save the solution of every run as
+/// JSON (see `Solution::save_json`) to its own file `run_0001.json`, `run_0002.json`, ... in the
+/// directory `dir`, which is created if it does not exist. Every solution is written as soon as its
+/// run is done, so that only the runs in progress are kept in memory, also for thousands of runs.
+/// Run `i` is simulated with a random number generator seeded with `base_seed + i`, so the runs
+/// can be reproduced (random graphs and initial conditions of `make_system` aside).
+///
+/// # Outputs
+/// The paths of the saved files, in the order of the runs.
+///
+/// # Errors
+/// Returns an error if the directory could not be created, or if a solution could not be saved.
+#[cfg(feature = "serde")]
+pub fn run_replicates_to_disk(config: EnsembleConfig, n: usize, base_seed: u64, dir: &str) -> io::Result<Vec<PathBuf>> {
+    create_dir_all(dir)?;
+
+    (1..=n).into_par_iter()
+        .map(|run| {
+            let (ips_rules, graph, initial_condition) = (config.make_system)();
+
+            let solution = particle_system_solver(
+                ips_rules,
+                graph,
+                initial_condition,
+                HaltCondition::TimePassed(config.time_horizon),
+                RecordCondition::ConstantTime(config.record_interval),
+                SolverOptions::default(),
+                StdRng::seed_from_u64(base_seed + run as u64),
+            );
+
+            let path = Path::new(dir).join(format!("run_{:04}.json", run));
+            solution.save_json(&path.to_string_lossy())?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "serde")]
+    use std::fs::{read_dir, remove_dir_all};
+    #[cfg(feature = "serde")]
+    use crate::solver::Solution;
+    use crate::solver::graph::star::StarGraph;
+    use crate::solver::ips_rules::si_process::SIProcess;
+    use super::*;
+
+    /// SI process on a star with the rates `(birth_rate, death_rate, spontaneous_rate)`, starting
+    /// with only the center infected.
+    fn si_on_star((birth_rate, death_rate, spontaneous_rate): (f64, f64, f64)) -> EnsembleConfig {
+        EnsembleConfig {
+            make_system: Box::new(move || (
+                Box::new(SIProcess { birth_rate, death_rate, spontaneous_rate }),
+                Box::new(StarGraph::new(4)),
+                vec![1, 0, 0, 0, 0],
+            )),
+            time_horizon: 2.0,
+            record_interval: 0.5,
+        }
+    }
+
+    #[test]
+    fn survival_probability_is_nonincreasing() {
+        // Without spontaneous infections, extinction is final
+        let stats = run_ensemble(si_on_star((1.0, 1.0, 0.0)), 50);
+
+        assert_eq!(stats.times, vec![0.5, 1.0, 1.5, 2.0]);
+        assert!(stats.survival_probability.iter().all(|&probability| (0.0..=1.0).contains(&probability)));
+        assert!(stats.survival_probability.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn replicates_are_saved_to_disk() {
+        let dir = std::env::temp_dir().join("rust_particle_system_replicates");
+        let dir_name = dir.to_string_lossy();
+
+        let paths = run_replicates_to_disk(si_on_star((1.0, 1.0, 0.1)), 3, 7, &dir_name).unwrap();
+
+        let mut file_names: Vec<String> = read_dir(&dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        file_names.sort();
+        assert_eq!(file_names, vec!["run_0001.json", "run_0002.json", "run_0003.json"]);
+        let solutions: Vec<Solution> = paths.iter()
+            .map(|path| Solution::load_json(&path.to_string_lossy()).unwrap())
+            .collect();
+        assert!(solutions.iter().all(|solution| solution.final_state.len() == 5));
+
+        // The same seed gives the same runs
+        let paths = run_replicates_to_disk(si_on_star((1.0, 1.0, 0.1)), 3, 7, &dir_name).unwrap();
+        for (path, solution) in paths.iter().zip(solutions.iter()) {
+            assert_eq!(&Solution::load_json(&path.to_string_lossy()).unwrap(), solution);
+        }
+        remove_dir_all(&dir).unwrap();
+    }
+}
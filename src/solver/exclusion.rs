@@ -0,0 +1,182 @@
+use rand::distributions::{Distribution, WeightedError, WeightedIndex};
+use rand::Rng;
+
+use crate::solver::{count_states, HaltCondition, RecordCondition, Solution};
+use crate::solver::exponential_distribution::StandardExponential;
+use crate::solver::graph::Graph;
+use crate::solver::graph::ring_lattice::RingLattice;
+
+/// Simulator for the totally asymmetric simple exclusion process (TASEP) on a ring. Sites are
+/// either empty (state 0) or occupied by a particle (state 1). Every particle hops to the site on
+/// its right (from site `i` to site `i + 1`, cyclically) at rate `hop_rate`, but only if that site
+/// is empty.
+///
+/// This needs a separate solver because a hop changes two sites at once: the occupied site becomes
+/// empty and the empty site becomes occupied, in a single event. `particle_system_solver` only
+/// knows single-site transitions (see `IPSRules`), and simulating a hop as two independent
+/// transitions would not conserve the number of particles. Moreover the hop has a direction, which
+/// the `Graph` trait doesn't describe. The events are therefore the *bonds* `(i, i + 1)` with an
+/// occupied left site and an empty right site, each of which fires at rate `hop_rate`.
+///
+/// # Parameters
+/// * `ring`: The ring the particles live on. Has to be a ring lattice with `k = 1`, i.e., a
+///   circle.
+/// * `hop_rate`: The rate at which a particle hops to an empty site on its right.
+/// * `initial_condition`: Vector containing the initial states of the sites, 0 (empty) or 1
+///   (occupied).
+/// * `halting_condition`: As in `particle_system_solver`. For the quiet period, a hop counts as an
+///   infection, since it makes an empty site occupied.
+/// * `record_condition`: As in `particle_system_solver`.
+/// * `rng`: Random number generator. Most likely you want to input `rand::thread_rng()`, or a
+///   seeded generator for a reproducible simulation.
+///
+/// # Outputs
+/// A `Solution` in the same format as that of `particle_system_solver`, without reactivity trace
+/// and events (a hop is not a single-site transition).
+pub fn exclusion_solver<R: Rng>(
+    ring: &RingLattice,
+    hop_rate: f64,
+    initial_condition: Vec<usize>,
+    halting_condition: HaltCondition,
+    record_condition: RecordCondition,
+    mut rng: R,
+) -> Solution {
+    // * PHASE I: Initialization * //
+
+    assert_eq!(ring.k(), 1); // hopping to the right is only defined on a circle
+    let nr_points = ring.nr_points();
+
+    let mut states: Vec<usize> = initial_condition;
+    assert_eq!(states.len(), nr_points);
+    assert!(states.iter().all(|&state| state <= 1)); // sites are empty or occupied
+
+    // Reactivity of the bond from site i to the site on its right
+    let bond_reactivity = |states: &Vec<usize>, i: usize| -> f64 {
+        if states[i] == 1 && states[(i + 1) % nr_points] == 0 { hop_rate } else { 0.0 }
+    };
+
+    let mut reactivities: Vec<f64> = (0..nr_points).map(|i| bond_reactivity(&states, i)).collect();
+    let mut total_reactivity: f64 = reactivities.iter().sum();
+
+    // Initialize state record
+    let mut states_record: Vec<usize> = vec![];
+    let mut times: Vec<f64> = vec![];
+
+    // Initialize timekeeping
+    let mut time_passed = 0.0;
+    let mut time_last_recorded = 0.0;
+    let mut time_last_infection = 0.0;
+    let mut steps_recorded = 0; // Number of snapshots in states_record
+    let mut steps_taken = 0;
+
+    // Initialize bond-finding distribution. If no bond can fire (the ring is empty or full),
+    // there is nothing to simulate.
+    let mut distr_bond = WeightedIndex::new(&reactivities).ok();
+
+    // * PHASE 2: Simulation loop * //
+    while let Some(distr) = distr_bond.as_mut() {
+        if !halting_condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_infection) {
+            break;
+        }
+
+        /* Update timekeeping */
+        steps_taken += 1;
+
+        // Generate time step (until next event)
+        let time_step: f64 = {
+            let standard_exp_object: StandardExponential = rng.gen();
+            standard_exp_object.0 / total_reactivity
+        };
+        let mean_reactivity = total_reactivity / nr_points as f64;
+
+        time_passed += time_step;
+
+        /* Record the state */
+        // As in `particle_system_solver`, the state before the update is recorded
+        for _ in 0..record_condition.how_often_record(time_passed, time_step, steps_taken,
+                                                      time_last_recorded, mean_reactivity) {
+            states_record.extend_from_slice(&states);
+            times.push(time_passed);
+            time_last_recorded = time_passed;
+            steps_recorded += 1;
+            if !halting_condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_infection) {
+                break;
+            }
+        }
+
+        /* Find the bond where the hop occurs, and perform it */
+        let from = distr.sample(&mut rng);
+        let to = (from + 1) % nr_points;
+        states[from] = 0;
+        states[to] = 1;
+        time_last_infection = time_passed;
+
+        /* Update reactivities */
+        // Only the bonds containing `from` or `to` change: the one on the left of `from`, the
+        // one that fired, and the one on the right of `to`.
+        let mut changed_bonds = vec![(from + nr_points - 1) % nr_points, from, to];
+        changed_bonds.sort_unstable(); // sorting is required for .update_weights()
+
+        for &bond in &changed_bonds {
+            total_reactivity -= reactivities[bond];
+            reactivities[bond] = bond_reactivity(&states, bond);
+            total_reactivity += reactivities[bond];
+        }
+
+        let changing_weights: Vec<(usize, &f64)> = changed_bonds.iter()
+            .map(|&bond| (bond, &reactivities[bond]))
+            .collect();
+        match distr.update_weights(&changing_weights[..]) {
+            Ok(_) => {}
+            Err(WeightedError::AllWeightsZero) => { distr_bond = None; } // Can't happen for 0 < particles < sites
+            Err(e) => { panic!("Changing weights: {:?}, Error: {}", changing_weights, e) }
+        };
+    }
+
+    // * PHASE III: Cleanup * //
+
+    // Record final state
+    states_record.extend_from_slice(&states);
+    times.push(time_passed);
+    steps_recorded += 1;
+
+    Solution {
+        snapshots: states_record,
+        times,
+        recorded_indices: None,
+        final_state_counts: count_states(&states),
+        final_state: states,
+        time_simulated: time_passed,
+        steps_recorded,
+        steps_taken,
+        reactivity_trace: None,
+        events: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn particle_number_is_conserved() {
+        let initial_condition: Vec<usize> = (0..20).map(|i| (i < 8) as usize).collect();
+
+        let solution = exclusion_solver(
+            &RingLattice::new(20, 1),
+            1.0,
+            initial_condition,
+            HaltCondition::StepsTaken(199),
+            RecordCondition::EveryNthStep(1),
+            StdRng::seed_from_u64(1),
+        );
+
+        assert!(solution.steps_recorded > 100);
+        for snapshot in solution.snapshots.chunks(20) {
+            assert_eq!(snapshot.iter().sum::<usize>(), 8);
+        }
+        assert_eq!(solution.final_state_counts[&1], 8);
+    }
+}
@@ -1,8 +1,30 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+use std::{fs, io};
+
+use crate::solver::graph::signed_graph::EdgeSign;
 
 pub mod grid_n_d;
 pub mod erdos_renyi;
 pub mod diluted_lattice;
+pub mod cayley_tree;
+pub mod ring_lattice;
+pub mod sbm;
+pub mod random_geometric;
+pub mod dense;
+pub mod hypercube;
+pub mod directed;
+pub mod bipartite;
+pub mod star;
+pub mod kagome;
+pub mod combinators;
+pub mod configuration_model;
+pub mod weighted_adjacency;
+pub mod signed_graph;
+
+/// Largest graph for which the all-pairs graph distance computations (`Graph::eccentricities`,
+/// `Graph::diameter`) are allowed. These take a breadth-first search from every point.
+pub const MAX_POINTS_ALL_PAIRS: usize = 10_000;
 
 /// Graph trait. Implements number of points, and getting neighbors of a particular point.
 ///
@@ -14,17 +36,262 @@ pub mod diluted_lattice;
 /// Directed, does not allow multi-edges, does allow self-loops (by the format of the get_neighbors function).
 /// It's not entirely clear what a self-loop means in the context of an interacting particle system.
 ///
-/// For IPS applications, the edges will be undirected, and there will be no self-loops.
+/// The solver interprets `get_neighbors(i)` as "who affects `i`": the rates of the point `i` depend
+/// on the states of these points. For most graphs the edges are undirected, so this relation is
+/// symmetric. For directed graphs (e.g., followers on social media) it is not, and then
+/// `get_influenced` has to be overwritten as well. There should be no self-loops.
 ///
-/// Overwrite all methods for a graph implementation.
+/// Overwrite the following methods for a graph implementation:
+/// * `nr_points`
+/// * `get_neighbors`
+/// * `describe`
+/// * `get_influenced` (only for directed graphs)
+/// * `get_weighted_neighbors` and `is_weighted` (only for weighted graphs)
+/// * `get_signed_neighbors` and `is_signed` (only for signed graphs)
+/// * `degree_distribution` (optional, for efficiency when the neighbors are stored)
 pub trait Graph {
     /// Return the number of point (aka vertices, nodes) in the graph. A list of all points is
     /// then `0..graph.nr_points()`.
     fn nr_points(&self) -> usize;
 
-    /// Return a hash set of all the neighbors of a particular input point.
+    /// Return a hash set of all the neighbors of a particular input point, i.e., the points that
+    /// affect it.
     fn get_neighbors(&self, particle: usize) -> HashSet<usize>;
+
+    /// Return a hash set of all the points that a particular input point affects, i.e., the points
+    /// that have it as a neighbor. The solver updates the rates of these points when the input
+    /// point changes state.
+    ///
+    /// Overwrite for directed graphs, the default implementation is only correct for undirected
+    /// graphs.
+    fn get_influenced(&self, particle: usize) -> HashSet<usize> {
+        self.get_neighbors(particle)
+    }
     
     /// Print a description of the graph.
     fn describe(&self);
-}
\ No newline at end of file
+
+    /// Return the neighbors of a particular input point together with the weights of their edges,
+    /// i.e., how strongly each neighbor affects the point. The solver multiplies the contribution
+    /// of every neighbor to the rates of the point by this weight.
+    ///
+    /// Overwrite (together with `is_weighted`) for weighted graphs, the default implementation
+    /// gives every edge weight 1.0.
+    fn get_weighted_neighbors(&self, particle: usize) -> Vec<(usize, f64)> {
+        self.get_neighbors(particle).into_iter().map(|neighbor| (neighbor, 1.0)).collect()
+    }
+
+    /// Do the edges of the graph have weights? If not, the solver ignores
+    /// `get_weighted_neighbors`, which is faster.
+    fn is_weighted(&self) -> bool {
+        false
+    }
+
+    /// Return the neighbors of a particular input point together with the signs of their edges,
+    /// i.e., whether each neighbor is a friend or an enemy of the point. The solver passes the
+    /// states of friends and enemies separately to the rules (see
+    /// `IPSRules::get_enemy_mutation_rate`).
+    ///
+    /// Overwrite (together with `is_signed`) for signed graphs, the default implementation gives
+    /// every edge a positive sign.
+    fn get_signed_neighbors(&self, particle: usize) -> Vec<(usize, EdgeSign)> {
+        self.get_neighbors(particle).into_iter().map(|neighbor| (neighbor, EdgeSign::Positive)).collect()
+    }
+
+    /// Do the edges of the graph have signs? If not, the solver ignores `get_signed_neighbors`,
+    /// which is faster.
+    fn is_signed(&self) -> bool {
+        false
+    }
+
+    /// Return the degree distribution of the graph, as a map from a degree to the number of points
+    /// with that degree. The degree of a point is its number of neighbors (for directed graphs the
+    /// number of points that affect it).
+    ///
+    /// The default implementation calls `get_neighbors` on every point. Overwrite for efficiency
+    /// if the neighbors are stored, e.g., with `tally_degrees`.
+    fn degree_distribution(&self) -> HashMap<usize, usize> {
+        tally_degrees((0..self.nr_points()).map(|i| self.get_neighbors(i).len()))
+    }
+
+    /// Return the eccentricity of every point, i.e., the largest graph distance from that point to
+    /// any other point it is connected to. Points which cannot be reached are ignored, so on a
+    /// disconnected graph this is the eccentricity within the connected component.
+    ///
+    /// Does a breadth-first search from every point, so this is expensive; panics on graphs with
+    /// more than `MAX_POINTS_ALL_PAIRS` points.
+    ///
+    /// Do not overwrite, the default implementation is correct.
+    fn eccentricities(&self) -> Vec<usize> {
+        assert!(self.nr_points() <= MAX_POINTS_ALL_PAIRS,
+                "Graph too large for all-pairs breadth-first search!");
+
+        let mut eccentricities: Vec<usize> = Vec::with_capacity(self.nr_points());
+
+        for i in 0..self.nr_points() {
+            eccentricities.push(
+                bfs_distances(self, i).into_iter().flatten().max().unwrap_or(0)
+            );
+        }
+
+        eccentricities
+    }
+
+    /// Return the diameter of the graph, i.e., the largest eccentricity of any point. See
+    /// `eccentricities` for the treatment of disconnected graphs and the size limit.
+    ///
+    /// Do not overwrite, the default implementation is correct.
+    fn diameter(&self) -> usize {
+        self.eccentricities().into_iter().max().unwrap_or(0)
+    }
+}
+
+impl<G: Graph + ?Sized> Graph for Box<G> {
+    fn nr_points(&self) -> usize {
+        (**self).nr_points()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        (**self).get_neighbors(particle)
+    }
+
+    fn get_influenced(&self, particle: usize) -> HashSet<usize> {
+        (**self).get_influenced(particle)
+    }
+
+    fn describe(&self) {
+        (**self).describe()
+    }
+
+    fn get_weighted_neighbors(&self, particle: usize) -> Vec<(usize, f64)> {
+        (**self).get_weighted_neighbors(particle)
+    }
+
+    fn is_weighted(&self) -> bool {
+        (**self).is_weighted()
+    }
+
+    fn get_signed_neighbors(&self, particle: usize) -> Vec<(usize, EdgeSign)> {
+        (**self).get_signed_neighbors(particle)
+    }
+
+    fn is_signed(&self) -> bool {
+        (**self).is_signed()
+    }
+
+    fn degree_distribution(&self) -> HashMap<usize, usize> {
+        (**self).degree_distribution()
+    }
+}
+
+/// Count how often each degree occurs in a list of degrees of the points. For implementations of
+/// `Graph::degree_distribution`.
+pub fn tally_degrees<I: IntoIterator<Item=usize>>(degrees: I) -> HashMap<usize, usize> {
+    let mut distribution: HashMap<usize, usize> = HashMap::new();
+    for degree in degrees {
+        *distribution.entry(degree).or_insert(0) += 1;
+    }
+    distribution
+}
+
+/// Compute the graph distance (length of the shortest path) from the point `source` to every
+/// point in the graph, by breadth-first search. Points which cannot be reached from `source` get
+/// distance `None`.
+pub fn bfs_distances<G: Graph + ?Sized>(graph: &G, source: usize) -> Vec<Option<usize>> {
+    let mut distances: Vec<Option<usize>> = vec![None; graph.nr_points()];
+    distances[source] = Some(0);
+
+    let mut queue = VecDeque::from([source]);
+
+    while let Some(current) = queue.pop_front() {
+        let next_distance = distances[current].unwrap() + 1;
+
+        for neighbor in graph.get_neighbors(current) {
+            if distances[neighbor].is_none() {
+                distances[neighbor] = Some(next_distance);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Export the graph in the DOT format of GraphViz, as an undirected `graph { ... }`. Every edge is
+/// listed once (for directed graphs, an edge in either direction gives an undirected edge).
+/// Points without neighbors are listed separately so they show up in the drawing as well.
+/// # Example
+/// Draw the graph with GraphViz, e.g., `neato -Tpng graph.dot -o graph.png`.
+/// ```
+/// let dot = to_dot(&RingLattice::new(10, 2));
+/// ```
+pub fn to_dot(graph: &dyn Graph) -> String {
+    let mut edges: BTreeSet<(usize, usize)> = BTreeSet::new();
+    let mut isolated_points: Vec<usize> = vec![];
+
+    for i in 0..graph.nr_points() {
+        let neighbors = graph.get_neighbors(i);
+        if neighbors.is_empty() && graph.get_influenced(i).is_empty() {
+            isolated_points.push(i);
+        }
+        for j in neighbors {
+            edges.insert((i.min(j), i.max(j)));
+        }
+    }
+
+    let mut dot = String::from("graph {\n");
+    for i in isolated_points {
+        writeln!(dot, "  {};", i).unwrap();
+    }
+    for (i, j) in edges {
+        writeln!(dot, "  {} -- {};", i, j).unwrap();
+    }
+    dot.push_str("}\n");
+
+    dot
+}
+
+/// Write the graph in the DOT format of GraphViz to the file `path`. See `to_dot`.
+pub fn write_dot(graph: &dyn Graph, path: &str) -> io::Result<()> {
+    fs::write(path, to_dot(graph))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solver::graph::dense::DenseGraph;
+    use crate::solver::graph::grid_n_d::GridND;
+    use super::*;
+
+    #[test]
+    fn path_diameter_and_eccentricities() {
+        let path = GridND::from((vec![6], vec![false]));
+
+        let eccentricities = path.eccentricities();
+
+        assert_eq!(path.diameter(), 5);
+        assert_eq!(eccentricities, vec![5, 4, 3, 3, 4, 5]);
+        let largest = eccentricities.iter().max().unwrap();
+        assert_eq!(eccentricities[0], *largest);
+        assert_eq!(eccentricities[5], *largest);
+    }
+
+    #[test]
+    fn complete_graph_has_diameter_1() {
+        let complete_graph = DenseGraph::from_matrix((0..5).map(|i| (0..5).map(|j| i != j).collect()).collect());
+
+        assert_eq!(complete_graph.diameter(), 1);
+        assert_eq!(complete_graph.eccentricities(), vec![1; 5]);
+    }
+
+    #[test]
+    fn triangle_has_three_dot_edges() {
+        let triangle = GridND::from(vec![3]);
+        let path = std::env::temp_dir().join("rust_particle_system_triangle.dot");
+
+        write_dot(&triangle, path.to_str().unwrap()).unwrap();
+
+        let dot = fs::read_to_string(&path).unwrap();
+        assert_eq!(dot, "graph {\n  0 -- 1;\n  0 -- 2;\n  1 -- 2;\n}\n");
+        fs::remove_file(path).unwrap();
+    }
+}
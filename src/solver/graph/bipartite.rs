@@ -0,0 +1,56 @@
+use std::collections::HashSet;
+use crate::solver::graph::Graph;
+
+/// Complete bipartite graph K_{m,n}. The first `m` points form one part and the last `n` points the
+/// other; every point is connected to all points of the other part, and to none of its own part.
+pub struct CompleteBipartite {
+    m: usize,
+    n: usize,
+}
+
+impl Graph for CompleteBipartite {
+    fn nr_points(&self) -> usize {
+        self.m + self.n
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        if particle < self.m {
+            (self.m..self.m + self.n).collect()
+        } else {
+            (0..self.m).collect()
+        }
+    }
+
+    fn describe(&self) {
+        println!("Complete bipartite graph with parts of {} and {} points.", self.m, self.n);
+    }
+}
+
+impl CompleteBipartite {
+    /// Construct the complete bipartite graph with parts of `m` and `n` points.
+    pub fn new(m: usize, n: usize) -> CompleteBipartite {
+        assert!(m >= 1 && n >= 1); // otherwise there are no edges
+
+        CompleteBipartite {
+            m,
+            n,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_part_only_neighbors_second_part() {
+        let graph = CompleteBipartite::new(3, 5);
+
+        for particle in 0..3 {
+            let neighbors = graph.get_neighbors(particle);
+            assert_eq!(neighbors.len(), 5);
+            assert!(neighbors.iter().all(|&neighbor| neighbor >= 3));
+        }
+        assert_eq!(graph.get_neighbors(7), HashSet::from([0, 1, 2]));
+    }
+}
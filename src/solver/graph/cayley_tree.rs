@@ -0,0 +1,96 @@
+use std::collections::{HashMap, HashSet};
+use crate::solver::graph::{Graph, tally_degrees};
+
+/// Cayley tree (finite part of a Bethe lattice): the root has `coordination` children, and every
+/// other internal node has `coordination - 1` children, so that all internal nodes have degree
+/// `coordination`. The leaves are at distance `depth` from the root.
+///
+/// Points are numbered breadth-first: the root is 0, followed by its children, followed by their
+/// children, etc.
+pub struct CayleyTree {
+    coordination: usize,
+    depth: usize,
+    /// Neighbors (parent and children) of each point.
+    adjacency: Vec<HashSet<usize>>,
+    nr_leaves: usize,
+}
+
+impl Graph for CayleyTree {
+    fn nr_points(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        self.adjacency[particle].clone()
+    }
+
+    fn degree_distribution(&self) -> HashMap<usize, usize> {
+        tally_degrees(self.adjacency.iter().map(HashSet::len))
+    }
+
+    fn describe(&self) {
+        println!("Cayley tree with coordination number {} and depth {}, of {} points of which {} \
+        are leaves.",
+                 self.coordination, self.depth, self.nr_points(), self.nr_leaves);
+    }
+}
+
+impl CayleyTree {
+    /// Construct a Cayley tree from the coordination number (degree of the internal nodes) and the
+    /// depth (distance from the root to the leaves).
+    pub fn new(coordination: usize, depth: usize) -> CayleyTree {
+        assert!(coordination >= 2); // coordination 1 has no internal nodes besides the root
+
+        let mut adjacency: Vec<HashSet<usize>> = vec![HashSet::new()]; // start with the root
+        let mut current_level: Vec<usize> = vec![0];
+
+        // Grow the tree level by level, attaching the children of each point of the current level
+        for level in 0..depth {
+            let nr_children = if level == 0 { coordination } else { coordination - 1 };
+            let mut next_level = vec![];
+
+            for parent in current_level {
+                for _ in 0..nr_children {
+                    let child = adjacency.len();
+                    adjacency.push(HashSet::from([parent]));
+                    adjacency[parent].insert(child);
+                    next_level.push(child);
+                }
+            }
+
+            current_level = next_level;
+        }
+
+        CayleyTree {
+            coordination,
+            depth,
+            adjacency,
+            nr_leaves: current_level.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_count_matches_closed_form() {
+        for coordination in 2..6 {
+            for depth in 0..5 {
+                let tree = CayleyTree::new(coordination, depth);
+
+                // 1 + c + c(c - 1) + ... + c(c - 1)^(d - 1) points, a geometric series
+                let nr_points = if coordination == 2 {
+                    1 + 2 * depth
+                } else {
+                    1 + coordination * ((coordination - 1).pow(depth as u32) - 1) / (coordination - 2)
+                };
+                assert_eq!(tree.nr_points(), nr_points);
+                let nr_leaves = if depth == 0 { 1 } else { coordination * (coordination - 1).pow(depth as u32 - 1) };
+                assert_eq!(tree.nr_leaves, nr_leaves);
+            }
+        }
+        assert_eq!(CayleyTree::new(3, 3).nr_points(), 22);
+    }
+}
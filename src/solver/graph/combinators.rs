@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use crate::solver::graph::Graph;
+use crate::solver::graph::signed_graph::EdgeSign;
+
+/// Cartesian product of the graphs `a` and `b`. The points are the pairs `(i, j)` of a point `i`
+/// of `a` and a point `j` of `b`, where `(i, j)` has index `i + a.nr_points() * j`. Two points are
+/// connected if they agree in one component and are connected in the other. E.g., the product of
+/// two circles is a torus, the same as a 2D `GridND`.
+pub struct CartesianProduct<A: Graph, B: Graph> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A: Graph, B: Graph> Graph for CartesianProduct<A, B> {
+    fn nr_points(&self) -> usize {
+        self.a.nr_points() * self.b.nr_points()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        let nr_points_a = self.a.nr_points();
+        let (i, j) = (particle % nr_points_a, particle / nr_points_a);
+
+        self.a.get_neighbors(i).into_iter().map(|neighbor| neighbor + nr_points_a * j)
+            .chain(self.b.get_neighbors(j).into_iter().map(|neighbor| i + nr_points_a * neighbor))
+            .collect()
+    }
+
+    fn get_influenced(&self, particle: usize) -> HashSet<usize> {
+        let nr_points_a = self.a.nr_points();
+        let (i, j) = (particle % nr_points_a, particle / nr_points_a);
+
+        self.a.get_influenced(i).into_iter().map(|neighbor| neighbor + nr_points_a * j)
+            .chain(self.b.get_influenced(j).into_iter().map(|neighbor| i + nr_points_a * neighbor))
+            .collect()
+    }
+
+    fn get_weighted_neighbors(&self, particle: usize) -> Vec<(usize, f64)> {
+        let nr_points_a = self.a.nr_points();
+        let (i, j) = (particle % nr_points_a, particle / nr_points_a);
+
+        self.a.get_weighted_neighbors(i).into_iter().map(|(neighbor, weight)| (neighbor + nr_points_a * j, weight))
+            .chain(self.b.get_weighted_neighbors(j).into_iter().map(|(neighbor, weight)| (i + nr_points_a * neighbor, weight)))
+            .collect()
+    }
+
+    fn is_weighted(&self) -> bool {
+        self.a.is_weighted() || self.b.is_weighted()
+    }
+
+    fn get_signed_neighbors(&self, particle: usize) -> Vec<(usize, EdgeSign)> {
+        let nr_points_a = self.a.nr_points();
+        let (i, j) = (particle % nr_points_a, particle / nr_points_a);
+
+        self.a.get_signed_neighbors(i).into_iter().map(|(neighbor, sign)| (neighbor + nr_points_a * j, sign))
+            .chain(self.b.get_signed_neighbors(j).into_iter().map(|(neighbor, sign)| (i + nr_points_a * neighbor, sign)))
+            .collect()
+    }
+
+    fn is_signed(&self) -> bool {
+        self.a.is_signed() || self.b.is_signed()
+    }
+
+    fn describe(&self) {
+        println!("Cartesian product with {} points of the graphs:", self.nr_points());
+        self.a.describe();
+        self.b.describe();
+    }
+}
+
+/// Disjoint union of the graphs `a` and `b`: the points of `a` followed by the points of `b`, with
+/// the point `j` of `b` having index `a.nr_points() + j`. There are no edges between the two parts.
+/// E.g., two separate communities.
+pub struct DisjointUnion<A: Graph, B: Graph> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A: Graph, B: Graph> Graph for DisjointUnion<A, B> {
+    fn nr_points(&self) -> usize {
+        self.a.nr_points() + self.b.nr_points()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        let nr_points_a = self.a.nr_points();
+        if particle < nr_points_a {
+            self.a.get_neighbors(particle)
+        } else {
+            self.b.get_neighbors(particle - nr_points_a).into_iter()
+                .map(|neighbor| neighbor + nr_points_a)
+                .collect()
+        }
+    }
+
+    fn get_influenced(&self, particle: usize) -> HashSet<usize> {
+        let nr_points_a = self.a.nr_points();
+        if particle < nr_points_a {
+            self.a.get_influenced(particle)
+        } else {
+            self.b.get_influenced(particle - nr_points_a).into_iter()
+                .map(|neighbor| neighbor + nr_points_a)
+                .collect()
+        }
+    }
+
+    fn get_weighted_neighbors(&self, particle: usize) -> Vec<(usize, f64)> {
+        let nr_points_a = self.a.nr_points();
+        if particle < nr_points_a {
+            self.a.get_weighted_neighbors(particle)
+        } else {
+            self.b.get_weighted_neighbors(particle - nr_points_a).into_iter()
+                .map(|(neighbor, weight)| (neighbor + nr_points_a, weight))
+                .collect()
+        }
+    }
+
+    fn is_weighted(&self) -> bool {
+        self.a.is_weighted() || self.b.is_weighted()
+    }
+
+    fn get_signed_neighbors(&self, particle: usize) -> Vec<(usize, EdgeSign)> {
+        let nr_points_a = self.a.nr_points();
+        if particle < nr_points_a {
+            self.a.get_signed_neighbors(particle)
+        } else {
+            self.b.get_signed_neighbors(particle - nr_points_a).into_iter()
+                .map(|(neighbor, sign)| (neighbor + nr_points_a, sign))
+                .collect()
+        }
+    }
+
+    fn is_signed(&self) -> bool {
+        self.a.is_signed() || self.b.is_signed()
+    }
+
+    fn describe(&self) {
+        println!("Disjoint union with {} points of the graphs:", self.nr_points());
+        self.a.describe();
+        self.b.describe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solver::graph::grid_n_d::GridND;
+    use crate::solver::graph::signed_graph::SignedGraph;
+    use crate::solver::graph::weighted_adjacency::WeightedAdjacency;
+    use super::*;
+
+    #[test]
+    fn product_of_3_cycles_is_3x3_torus() {
+        let product = CartesianProduct { a: GridND::from(vec![3]), b: GridND::from(vec![3]) };
+        let torus = GridND::from(vec![3, 3]);
+
+        assert_eq!(product.nr_points(), 9);
+        for i in 0..9 {
+            assert_eq!(product.get_neighbors(i), torus.get_neighbors(i), "point {}", i);
+        }
+    }
+
+    #[test]
+    fn union_has_no_edges_between_parts() {
+        let union = DisjointUnion { a: GridND::from(vec![3]), b: GridND::from(vec![4]) };
+
+        assert_eq!(union.nr_points(), 7);
+        assert_eq!(union.get_neighbors(0), HashSet::from([1, 2]));
+        assert_eq!(union.get_neighbors(3), HashSet::from([4, 6]));
+    }
+
+    #[test]
+    fn union_keeps_weights() {
+        let union = DisjointUnion { a: GridND::from(vec![3]), b: WeightedAdjacency::new(2, &[(0, 1, 2.5)]) };
+
+        assert!(union.is_weighted());
+        assert_eq!(union.get_weighted_neighbors(3), vec![(4, 2.5)]);
+        let mut unweighted = union.get_weighted_neighbors(0);
+        unweighted.sort_by_key(|&(neighbor, _)| neighbor);
+        assert_eq!(unweighted, vec![(1, 1.0), (2, 1.0)]);
+    }
+
+    #[test]
+    fn product_keeps_signs() {
+        // Two copies of an enemy pair, joined by friendly edges
+        let enemies = SignedGraph::new(2, &[(0, 1, EdgeSign::Negative)]);
+        let product = CartesianProduct { a: enemies, b: GridND::from((vec![2], vec![false])) };
+
+        assert!(product.is_signed());
+        let mut neighbors = product.get_signed_neighbors(2);
+        neighbors.sort_by_key(|&(neighbor, _)| neighbor);
+        assert_eq!(neighbors, vec![(0, EdgeSign::Positive), (3, EdgeSign::Negative)]);
+    }
+}
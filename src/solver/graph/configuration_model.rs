@@ -0,0 +1,95 @@
+use std::collections::{HashMap, HashSet};
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::solver::graph::{Graph, tally_degrees};
+
+/// Configuration model: a random graph with a prescribed degree sequence. Every point `i` gets
+/// `degrees[i]` half-edges (stubs), and the stubs are paired uniformly at random into edges.
+///
+/// The pairing may give self-loops and multi-edges, which a `Graph` can't have. These are removed,
+/// so the points involved have a lower degree than requested. For sparse degree sequences there are
+/// only a few of them.
+pub struct ConfigurationModel {
+    adjacency: Vec<HashSet<usize>>,
+    /// Sum of the requested degrees, i.e., twice the number of edges before removal
+    degree_sum: usize,
+    /// Number of self-loops and duplicate edges that were removed
+    nr_removed_edges: usize,
+}
+
+impl Graph for ConfigurationModel {
+    fn nr_points(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        self.adjacency[particle].clone()
+    }
+
+    fn degree_distribution(&self) -> HashMap<usize, usize> {
+        tally_degrees(self.adjacency.iter().map(HashSet::len))
+    }
+
+    fn describe(&self) {
+        println!("Configuration model graph with {} points and degree sum {} ({} edges), of which \
+        {} self-loops and multi-edges were removed.",
+                 self.nr_points(), self.degree_sum, self.degree_sum / 2, self.nr_removed_edges);
+    }
+}
+
+impl ConfigurationModel {
+    /// Construct a random graph with the degree sequence `degrees` (indexed by point) by pairing
+    /// half-edges. The sum of the degrees has to be even.
+    pub fn new<R: Rng>(degrees: Vec<usize>, mut rng: R) -> ConfigurationModel {
+        let degree_sum: usize = degrees.iter().sum();
+        assert!(degree_sum.is_multiple_of(2)); // every edge has two ends
+
+        // List every point as often as its degree, and pair the stubs after shuffling
+        let mut stubs: Vec<usize> = degrees.iter().enumerate()
+            .flat_map(|(i, &degree)| std::iter::repeat_n(i, degree))
+            .collect();
+        stubs.shuffle(&mut rng);
+
+        let mut adjacency: Vec<HashSet<usize>> = vec![HashSet::new(); degrees.len()];
+        let mut nr_removed_edges = 0;
+        for pair in stubs.chunks(2) {
+            let (i, j) = (pair[0], pair[1]);
+            if i == j || adjacency[i].contains(&j) { // self-loop or multi-edge
+                nr_removed_edges += 1;
+            } else {
+                adjacency[i].insert(j);
+                adjacency[j].insert(i);
+            }
+        }
+
+        ConfigurationModel {
+            adjacency,
+            degree_sum,
+            nr_removed_edges,
+        }
+    }
+
+    /// Return the number of self-loops and multi-edges which were removed from the pairing.
+    pub fn nr_removed_edges(&self) -> usize {
+        self.nr_removed_edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn degrees_as_requested_without_removals() {
+        let degrees: Vec<usize> = (0..40).map(|i| 2 + i % 2).collect();
+
+        let graph = ConfigurationModel::new(degrees.clone(), StdRng::seed_from_u64(1));
+
+        assert_eq!(graph.nr_removed_edges(), 0); // for this seed
+        for (i, &degree) in degrees.iter().enumerate() {
+            assert_eq!(graph.get_neighbors(i).len(), degree, "point {}", i);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+use crate::solver::graph::Graph;
+
+/// Graph given by a dense (symmetric) adjacency matrix. Entry `[i][j]` is true if and only if the
+/// points `i` and `j` are connected.
+pub struct DenseGraph {
+    adjacency: Vec<Vec<bool>>,
+}
+
+impl Graph for DenseGraph {
+    fn nr_points(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        self.adjacency[particle].iter()
+            .enumerate()
+            .filter(|(_, &connected)| connected)
+            .map(|(neighbor, _)| neighbor)
+            .collect()
+    }
+
+    fn describe(&self) {
+        // every edge is counted twice in a symmetric matrix
+        let nr_entries: usize = self.adjacency.iter()
+            .map(|row| row.iter().filter(|&&connected| connected).count())
+            .sum();
+
+        println!("Graph from an adjacency matrix with {} nodes and {} edges.",
+                 self.adjacency.len(), nr_entries / 2);
+    }
+}
+
+impl DenseGraph {
+    /// Construct a graph from its adjacency matrix. The matrix has to be square, symmetric since the
+    /// graph is undirected, and have a false diagonal since there should be no self-loops.
+    pub fn from_matrix(adj: Vec<Vec<bool>>) -> DenseGraph {
+        let nr_points = adj.len();
+        for row in &adj {
+            assert_eq!(row.len(), nr_points, "Adjacency matrix is not square.");
+        }
+        for (i, row) in adj.iter().enumerate() {
+            assert!(!row[i], "Adjacency matrix has a self-loop at {}.", i);
+            for (j, &connected) in row.iter().enumerate().take(i) {
+                assert_eq!(connected, adj[j][i], "Adjacency matrix is not symmetric at ({}, {}).", i, j);
+            }
+        }
+
+        DenseGraph {
+            adjacency: adj,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_from_matrix() {
+        let graph = DenseGraph::from_matrix(vec![
+            vec![false, true, false],
+            vec![true, false, true],
+            vec![false, true, false],
+        ]);
+
+        assert_eq!(graph.nr_points(), 3);
+        assert_eq!(graph.get_neighbors(0), HashSet::from([1]));
+        assert_eq!(graph.get_neighbors(1), HashSet::from([0, 2]));
+        assert_eq!(graph.get_neighbors(2), HashSet::from([1]));
+    }
+
+    #[test]
+    #[should_panic(expected = "self-loop")]
+    fn self_loop_is_rejected() {
+        DenseGraph::from_matrix(vec![vec![true, false], vec![false, false]]);
+    }
+}
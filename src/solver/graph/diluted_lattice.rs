@@ -1,16 +1,18 @@
 use std::collections::HashSet;
 use rand::distributions::{Bernoulli, Distribution};
-use rand::rngs::ThreadRng;
+use rand::Rng;
 use crate::Graph;
 
-/// i is connected to j with probability if i,j are adjacent in the corresponding lattice
+/// i is connected to j with probability if i,j are adjacent in the corresponding lattice. The
+/// probability may differ between horizontal (x) and vertical (y) edges.
 pub struct DilutedLattice {
     nr_points: usize,
     dim_x: usize,
     step_x: usize,
     dim_y: usize,
     step_y: usize,
-    probability: f64,
+    p_horizontal: f64,
+    p_vertical: f64,
     is_edge: Vec<bool>, // A mask over the actual lattice's edges
 }
 
@@ -74,22 +76,41 @@ impl Graph for DilutedLattice {
     }
 
     fn describe(&self) {
-        println!("Diluted two-dimensional {} by {} toroidal lattice: two adjacent points i and j in \
-        the full lattice are connected by an edge with probability {}. Also known as a bond percolation.",
-                 self.dim_x, self.dim_y, self.probability);
+        if self.p_horizontal == self.p_vertical {
+            println!("Diluted two-dimensional {} by {} toroidal lattice: two adjacent points i and j in \
+            the full lattice are connected by an edge with probability {}. Also known as a bond percolation.",
+                     self.dim_x, self.dim_y, self.p_horizontal);
+        } else {
+            println!("Anisotropically diluted two-dimensional {} by {} toroidal lattice: two adjacent \
+            points i and j in the full lattice are connected by an edge with probability {} if they \
+            are horizontal neighbors, and with probability {} if they are vertical neighbors. Also \
+            known as an anisotropic bond percolation.",
+                     self.dim_x, self.dim_y, self.p_horizontal, self.p_vertical);
+        }
     }
 }
 
 impl DilutedLattice {
     /// Construct new diluted lattice from x-dimension, y-dimension, and probability that a certain
     /// edge is in the lattice.
-    pub fn new(dim_x: usize, dim_y: usize, probability: f64, rng: ThreadRng) -> DilutedLattice {
-        let bernoulli_dist = Bernoulli::new(probability).unwrap();
-        let mut sampler = bernoulli_dist.sample_iter(rng);
+    pub fn new<R: Rng>(dim_x: usize, dim_y: usize, probability: f64, rng: R) -> DilutedLattice {
+        DilutedLattice::new_anisotropic(dim_x, dim_y, probability, probability, rng)
+    }
+
+    /// Construct new diluted lattice from x-dimension, y-dimension, and separate probabilities
+    /// that a horizontal (x) edge and a vertical (y) edge are in the lattice. For anisotropic
+    /// percolation.
+    pub fn new_anisotropic<R: Rng>(dim_x: usize, dim_y: usize, p_horizontal: f64, p_vertical: f64,
+                                   mut rng: R) -> DilutedLattice {
+        let horizontal_dist = Bernoulli::new(p_horizontal).unwrap();
+        let vertical_dist = Bernoulli::new(p_vertical).unwrap();
 
         let mut is_edge = vec![]; // First all horizontal (x) edges, then all vertical (y) edges
-        for _ in 0..2 * dim_x * dim_y { // There are 2 times as many edges as points
-            is_edge.push(sampler.next().unwrap())
+        for _ in 0..dim_x * dim_y { // There is one horizontal edge per point
+            is_edge.push(horizontal_dist.sample(&mut rng))
+        }
+        for _ in 0..dim_x * dim_y { // and one vertical edge per point
+            is_edge.push(vertical_dist.sample(&mut rng))
         }
 
         let step_x = 1;
@@ -101,8 +122,120 @@ impl DilutedLattice {
             step_x,
             dim_y,
             step_y,
-            probability,
+            p_horizontal,
+            p_vertical,
             is_edge,
         }
     }
-}
\ No newline at end of file
+
+    /// The edges present in this realization of the lattice, as pairs `(i, j)` where `j` is the
+    /// right or the bottom neighbor of `i` in the full lattice. Save these to reproduce the
+    /// realization with `from_edges`.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges = vec![];
+
+        for particle in 0..self.nr_points {
+            if self.is_edge[particle] {
+                edges.push((particle, self.right_of(particle)));
+            }
+        }
+        for particle in 0..self.nr_points {
+            if self.is_edge[self.nr_points + particle] {
+                edges.push((particle, self.below(particle)));
+            }
+        }
+
+        edges
+    }
+
+    /// Construct a diluted lattice from x-dimension, y-dimension, and the edges which are present,
+    /// e.g., as given by `edges`, to reproduce a specific realization. The edges are undirected,
+    /// and have to be edges of the full lattice. The probabilities shown by `describe` are the
+    /// fractions of the horizontal and vertical edges which are present.
+    pub fn from_edges(dim_x: usize, dim_y: usize, edges: &[(usize, usize)]) -> DilutedLattice {
+        let nr_points = dim_x * dim_y;
+        let mut lattice = DilutedLattice {
+            nr_points,
+            dim_x,
+            step_x: 1,
+            dim_y,
+            step_y: dim_x,
+            p_horizontal: 0.0,
+            p_vertical: 0.0,
+            is_edge: vec![false; 2 * nr_points], // as in new_anisotropic
+        };
+
+        for &(i, j) in edges {
+            assert!(i < nr_points && j < nr_points, "Edge ({}, {}) is not in the graph.", i, j);
+
+            let index = if lattice.right_of(i) == j {
+                i
+            } else if lattice.right_of(j) == i {
+                j
+            } else if lattice.below(i) == j {
+                nr_points + i
+            } else if lattice.below(j) == i {
+                nr_points + j
+            } else {
+                panic!("Edge ({}, {}) is not an edge of the lattice.", i, j)
+            };
+            lattice.is_edge[index] = true;
+        }
+
+        let nr_horizontal = lattice.is_edge[..nr_points].iter().filter(|&&present| present).count();
+        let nr_vertical = lattice.is_edge[nr_points..].iter().filter(|&&present| present).count();
+        lattice.p_horizontal = nr_horizontal as f64 / nr_points as f64;
+        lattice.p_vertical = nr_vertical as f64 / nr_points as f64;
+
+        lattice
+    }
+
+    /// The right neighbor of `particle` in the full lattice.
+    fn right_of(&self, particle: usize) -> usize {
+        if particle % self.dim_x == self.dim_x - 1 {
+            particle + self.step_x - self.dim_x
+        } else {
+            particle + self.step_x
+        }
+    }
+
+    /// The bottom neighbor of `particle` in the full lattice.
+    fn below(&self, particle: usize) -> usize {
+        if particle / self.dim_x == self.dim_y - 1 {
+            particle + self.step_y - self.nr_points
+        } else {
+            particle + self.step_y
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn only_horizontal_edges() {
+        let lattice = DilutedLattice::new_anisotropic(5, 4, 1.0, 0.0, StdRng::seed_from_u64(1));
+
+        // The left and right neighbors on the same row, wrapping around
+        for particle in 0..20 {
+            let row_start = particle - particle % 5;
+            let left = row_start + (particle + 4) % 5;
+            let right = row_start + (particle + 1) % 5;
+            assert_eq!(lattice.get_neighbors(particle), HashSet::from([left, right]), "point {}", particle);
+        }
+    }
+
+    #[test]
+    fn edges_round_trip() {
+        let lattice = DilutedLattice::new(6, 5, 0.5, StdRng::seed_from_u64(2));
+
+        let rebuilt = DilutedLattice::from_edges(6, 5, &lattice.edges());
+
+        for particle in 0..30 {
+            assert_eq!(rebuilt.get_neighbors(particle), lattice.get_neighbors(particle), "point {}", particle);
+        }
+    }
+}
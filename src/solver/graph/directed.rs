@@ -0,0 +1,93 @@
+use std::collections::{HashMap, HashSet};
+use crate::solver::graph::{Graph, tally_degrees};
+
+/// Directed graph given by its edges, for modelling asymmetric influence. An edge `(i, j)` means
+/// that `i` affects `j` (e.g., `j` follows `i`), but not the other way around. Accordingly,
+/// `get_neighbors(j)` returns the in-neighbors of `j`, its influencers.
+pub struct DirectedGraph {
+    /// The influencers of each point.
+    in_neighbors: Vec<HashSet<usize>>,
+    /// The points each point influences.
+    out_neighbors: Vec<HashSet<usize>>,
+}
+
+impl Graph for DirectedGraph {
+    fn nr_points(&self) -> usize {
+        self.in_neighbors.len()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        self.in_neighbors[particle].clone()
+    }
+
+    fn degree_distribution(&self) -> HashMap<usize, usize> {
+        tally_degrees(self.in_neighbors.iter().map(HashSet::len))
+    }
+
+    fn get_influenced(&self, particle: usize) -> HashSet<usize> {
+        self.out_neighbors[particle].clone()
+    }
+
+    fn describe(&self) {
+        let nr_edges: usize = self.in_neighbors.iter().map(|influencers| influencers.len()).sum();
+
+        println!("Directed graph with {} nodes and {} edges.", self.nr_points(), nr_edges);
+    }
+}
+
+impl DirectedGraph {
+    /// Construct a directed graph on `nr_points` points from its edges, where `(i, j)` means that
+    /// `i` affects `j`. Duplicate edges are ignored, self-loops are not allowed.
+    pub fn from_edges(nr_points: usize, edges: &[(usize, usize)]) -> DirectedGraph {
+        let mut in_neighbors: Vec<HashSet<usize>> = vec![HashSet::new(); nr_points];
+        let mut out_neighbors: Vec<HashSet<usize>> = vec![HashSet::new(); nr_points];
+
+        for &(from, to) in edges {
+            assert!(from < nr_points && to < nr_points, "Edge ({}, {}) is not in the graph!", from, to);
+            assert_ne!(from, to, "Self-loops are not allowed!");
+
+            in_neighbors[to].insert(from);
+            out_neighbors[from].insert(to);
+        }
+
+        DirectedGraph {
+            in_neighbors,
+            out_neighbors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::solver::{HaltCondition, particle_system_solver, RecordCondition, SolverOptions};
+    use crate::solver::ips_rules::si_process::SIProcess;
+    use super::*;
+
+    #[test]
+    fn directed_triangle_is_asymmetric() {
+        let triangle = DirectedGraph::from_edges(3, &[(0, 1), (1, 2), (2, 0)]);
+
+        for i in 0..3 {
+            assert_eq!(triangle.get_neighbors(i), HashSet::from([(i + 2) % 3]));
+            assert_eq!(triangle.get_influenced(i), HashSet::from([(i + 1) % 3]));
+        }
+    }
+
+    #[test]
+    fn infection_spreads_along_edges_only() {
+        let final_state = |initial_condition: Vec<usize>| particle_system_solver(
+            Box::new(SIProcess { birth_rate: 1.0, death_rate: 0.0, spontaneous_rate: 0.0 }),
+            Box::new(DirectedGraph::from_edges(3, &[(0, 1), (1, 2)])),
+            initial_condition,
+            HaltCondition::TimePassed(1000.0),
+            RecordCondition::Final(),
+            SolverOptions::default(),
+            StdRng::seed_from_u64(1),
+        ).final_state;
+
+        assert_eq!(final_state(vec![1, 0, 0]), vec![1, 1, 1]);
+        assert_eq!(final_state(vec![0, 1, 0]), vec![0, 1, 1]);
+    }
+}
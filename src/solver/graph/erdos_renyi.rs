@@ -1,14 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use rand::distributions::{Bernoulli, Distribution};
-use rand::rngs::ThreadRng;
-use crate::solver::graph::Graph;
+use rand::Rng;
+use crate::solver::graph::{Graph, tally_degrees};
 
 
 pub struct ErdosRenyi {
-    // The description of an E-R graph is not unique given a list of cliques. This leaves room for
-    // an optimization with less, bigger cliques.
-    cliques: Vec<HashSet<usize>>,
-    // The order of the cliques does not matter, but we're only checking if x is a member of each clique. So that has to be a hashset.
+    // Neighbors of each point, assembled once from the cliques in the constructor so that lookups
+    // are O(degree) instead of a scan over all cliques.
+    adjacency: Vec<HashSet<usize>>,
     nr_points: usize,
     probability: f64,
 }
@@ -19,19 +18,11 @@ impl Graph for ErdosRenyi {
     }
 
     fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
-        let mut running_neighbours: HashSet<usize> = HashSet::new();
-        for clique in self.cliques.iter() {
-            if clique.contains(&particle) {
-                for neigh in clique {
-                    if neigh != &particle {
-                        running_neighbours.insert(neigh.clone());
-                    }
-                }
-            }
-        }
+        self.adjacency[particle].clone()
+    }
 
-        running_neighbours.remove(&particle);
-        running_neighbours
+    fn degree_distribution(&self) -> HashMap<usize, usize> {
+        tally_degrees(self.adjacency.iter().map(HashSet::len))
     }
 
     fn describe(&self) {
@@ -42,9 +33,11 @@ impl Graph for ErdosRenyi {
 }
 
 impl ErdosRenyi {
-    pub fn new(nr_points: usize, probability: f64, mut rng: ThreadRng) -> ErdosRenyi {
+    pub fn new<R: Rng>(nr_points: usize, probability: f64, mut rng: R) -> ErdosRenyi {
         let bernoulli_dist = Bernoulli::new(probability).unwrap();
 
+        // The description of an E-R graph is not unique given a list of cliques. This leaves room
+        // for an optimization with less, bigger cliques.
         let mut cliques: Vec<HashSet<usize>> = vec![];
 
         // Loop over all unordered pairs of points, and determine randomly if they're connected
@@ -56,10 +49,68 @@ impl ErdosRenyi {
             }
         }
 
+        let adjacency = Self::adjacency_from_cliques(&cliques, nr_points);
+
         ErdosRenyi {
-            cliques,
+            adjacency,
             nr_points,
             probability,
         }
     }
-}
\ No newline at end of file
+
+    /// Assemble the neighbor sets of all points from a list of cliques: every point in a clique is
+    /// a neighbor of every other point in that clique.
+    fn adjacency_from_cliques(cliques: &[HashSet<usize>], nr_points: usize) -> Vec<HashSet<usize>> {
+        let mut adjacency: Vec<HashSet<usize>> = vec![HashSet::new(); nr_points];
+
+        for clique in cliques {
+            for i in clique {
+                for j in clique {
+                    if i != j {
+                        adjacency[*i].insert(*j);
+                    }
+                }
+            }
+        }
+
+        adjacency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn adjacency_is_symmetric() {
+        let graph = ErdosRenyi::new(12, 0.3, StdRng::seed_from_u64(1));
+
+        for i in 0..graph.nr_points() {
+            assert!(!graph.get_neighbors(i).contains(&i));
+            for j in graph.get_neighbors(i) {
+                assert!(graph.get_neighbors(j).contains(&i));
+            }
+        }
+    }
+
+    #[test]
+    fn adjacency_matches_clique_scan() {
+        let cliques = vec![HashSet::from([0, 1]), HashSet::from([1, 2, 3]), HashSet::from([3, 4])];
+
+        let adjacency = ErdosRenyi::adjacency_from_cliques(&cliques, 6);
+
+        // The neighbors of a point are the other points of all cliques containing it
+        for (particle, neighbors) in adjacency.iter().enumerate() {
+            let mut scanned: HashSet<usize> = cliques.iter()
+                .filter(|clique| clique.contains(&particle))
+                .flatten()
+                .copied()
+                .collect();
+            scanned.remove(&particle);
+            assert_eq!(*neighbors, scanned);
+        }
+        assert!(adjacency[5].is_empty());
+    }
+}
@@ -1,6 +1,18 @@
 use std::collections::HashSet;
 use crate::solver::graph::Graph;
 
+/// Which points of the lattice are neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// The points one step away along one of the axes: `2d` neighbors in `d` dimensions (4 in 2D).
+    VonNeumann,
+    /// The points at most one step away along every axis, so including the diagonals. Each of the
+    /// `d` coordinates changes by -1, 0 or 1, but not all by 0, giving `3^d - 1` neighbors (8 in
+    /// 2D, 26 in 3D). Fewer at the boundary of acyclic dimensions, and in cyclic dimensions of
+    /// size 2 (where the steps -1 and 1 lead to the same point).
+    Moore,
+}
+
 /// N-dimensional lattice graph, with some coordinates cyclic or acyclic.
 #[derive(Debug)]
 pub struct GridND {
@@ -18,6 +30,9 @@ pub struct GridND {
     /// How many points are there in this graph?
     /// For looping over all points
     nr_points: usize,
+
+    /// Which points are neighbors. Von Neumann unless constructed with `new_moore`.
+    neighborhood: Neighborhood,
 }
 
 
@@ -47,6 +62,7 @@ impl From<(Vec<usize>, Vec<bool>)> for GridND {
 
         // Make sure that we have enough glue-data to specify the entire GridND
         assert_eq!(dimensions.len(), glue.len());
+        assert!(!dimensions.is_empty()); // a grid needs at least one dimension
         assert!(!dimensions.contains(&0usize)); // dimension 0 is meaningless
         assert!(!dimensions.contains(&1usize)); // dimension 1 is trivial, just use less dimensions
 
@@ -65,6 +81,7 @@ impl From<(Vec<usize>, Vec<bool>)> for GridND {
             step_sizes,
             glue,
             nr_points,
+            neighborhood: Neighborhood::VonNeumann,
         }
     }
 }
@@ -85,6 +102,155 @@ impl From<Vec<usize>> for GridND {
     }
 }
 
+impl GridND {
+    /// Construct an nD grid which is cyclic in every dimension, i.e., a torus. Same as
+    /// `GridND::from(dimensions)`.
+    pub fn torus(dimensions: Vec<usize>) -> GridND {
+        GridND::from(dimensions)
+    }
+
+    /// Construct an nD grid which is acyclic in every dimension, i.e., a (hyper)rectangle with a
+    /// boundary on every side.
+    pub fn open_box(dimensions: Vec<usize>) -> GridND {
+        let glue = vec![false; dimensions.len()];
+        GridND::from((dimensions, glue))
+    }
+
+    /// Construct a 2D grid which is cyclic in the first (x) direction only, i.e., the wall of a
+    /// cylinder (tube) of circumference `dim_x` and height `dim_y`.
+    pub fn cylinder(dim_x: usize, dim_y: usize) -> GridND {
+        GridND::from((vec![dim_x, dim_y], vec![true, false]))
+    }
+
+    /// Construct an nD grid with the Moore neighborhood, where diagonal points are neighbors too.
+    /// The parameters are as in `GridND::from((dimensions, glue))`.
+    /// # Example
+    /// 40x40 toroidal grid where every point has 8 neighbors
+    /// ```
+    /// let g = GridND::new_moore(vec![40, 40], vec![true, true])
+    /// ```
+    pub fn new_moore(dimensions: Vec<usize>, glue: Vec<bool>) -> GridND {
+        GridND {
+            neighborhood: Neighborhood::Moore,
+            ..GridND::from((dimensions, glue))
+        }
+    }
+
+    /// Neighbors in the Moore neighborhood: all points reached by changing every coordinate by -1,
+    /// 0 or 1 (not all 0).
+    fn get_moore_neighbors(&self, particle: usize) -> HashSet<usize> {
+        // Start with the point itself, and for each dimension in turn, move all found points by
+        // -1, 0 and 1 in that dimension
+        let mut points: Vec<usize> = vec![particle];
+
+        for (dimension_index, step_size) in self.step_sizes.iter().enumerate() {
+            let current_dimension = self.dimensions[dimension_index];
+            let mut moved_points = Vec::with_capacity(3 * points.len());
+
+            for point in points {
+                let current_coordinate = point / step_size % current_dimension;
+                moved_points.push(point);
+
+                // step in the positive direction
+                if current_coordinate + 1 < current_dimension {
+                    moved_points.push(point + step_size);
+                } else if self.glue[dimension_index] { // loop around
+                    moved_points.push(point + step_size - step_size * current_dimension);
+                }
+
+                // step in the negative direction
+                if current_coordinate > 0 {
+                    moved_points.push(point - step_size);
+                } else if self.glue[dimension_index] { // loop around
+                    moved_points.push(point + step_size * current_dimension - step_size);
+                }
+            }
+
+            points = moved_points;
+        }
+
+        let mut neighbors: HashSet<usize> = points.into_iter().collect();
+        neighbors.remove(&particle);
+
+        neighbors
+    }
+
+    /// Convert the index of a point to its coordinates in the grid, one per dimension. The first
+    /// coordinate varies fastest, e.g., in a 4x10 grid the point 13 has coordinates `[1, 3]`.
+    pub fn index_to_coords(&self, index: usize) -> Vec<usize> {
+        assert!(index < self.nr_points, "Point {} is not in the grid!", index);
+
+        self.step_sizes.iter().zip(self.dimensions.iter())
+            .map(|(step_size, dimension)| index / step_size % dimension)
+            .collect()
+    }
+
+    /// Convert coordinates in the grid (one per dimension) to the index of the point. Returns
+    /// `None` if the number of coordinates is not the number of dimensions, or if some coordinate
+    /// is out of bounds.
+    /// # Example
+    /// Infect the point with coordinates (10, 20, 5) of a 3D grid
+    /// ```
+    /// different_particles.insert(grid.coords_to_index(&[10, 20, 5]).expect("Not in the grid!"), 1);
+    /// ```
+    pub fn coords_to_index(&self, coords: &[usize]) -> Option<usize> {
+        if coords.len() != self.dimensions.len() {
+            return None;
+        }
+
+        let mut index = 0;
+        for ((coord, step_size), dimension) in coords.iter().zip(self.step_sizes.iter()).zip(self.dimensions.iter()) {
+            if coord >= dimension {
+                return None;
+            }
+            index += coord * step_size;
+        }
+
+        Some(index)
+    }
+
+    /// The description printed by `describe`.
+    fn description(&self) -> String {
+        let mut description = match self.dimensions.len() {
+            0 => {
+                unreachable!("Grid graphs have at least one dimension by construction!")
+            }
+            1 => {
+                if self.glue[0] {
+                    format!("Circle graph with {} points.", self.dimensions[0])
+                } else {
+                    format!("Line segment graph with {} points.", self.dimensions[0])
+                }
+            }
+            2 => {
+                if self.glue[0] && self.glue[1] {
+                    format!("2D toroidal graph, of size {}x{}.",
+                            self.dimensions[0], self.dimensions[1])
+                } else if self.glue[0] {
+                    format!("2D cylinder graph, where the first dimension is cyclic, of size {}x{}",
+                            self.dimensions[0], self.dimensions[1])
+                } else if self.glue[1] {
+                    format!("2D cylinder graph, where the second dimension is cyclic, of size {}x{}",
+                            self.dimensions[0], self.dimensions[1])
+                } else {
+                    format!("Rectangular graph, of size {}x{}",
+                            self.dimensions[0], self.dimensions[1])
+                }
+            }
+            _ => {
+                format!("General {}D grid graph, glued at {:?}, of size {:?}", self.dimensions.len(),
+                        self.glue, self.dimensions)
+            }
+        };
+
+        if self.neighborhood == Neighborhood::Moore {
+            description.push_str("\nDiagonal points are neighbors too (Moore neighborhood).");
+        }
+
+        description
+    }
+}
+
 impl Graph for GridND {
     fn nr_points(&self) -> usize {
         self.nr_points
@@ -92,6 +258,10 @@ impl Graph for GridND {
 
     // Finding the neighbors of a particular inspection point on the regular grid (hard logic, think deeply)
     fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        if self.neighborhood == Neighborhood::Moore {
+            return self.get_moore_neighbors(particle);
+        }
+
         let mut neighbors: HashSet<usize> = HashSet::new();
 
         for (dimension_index, step_size) in self.step_sizes.iter().enumerate() {
@@ -128,34 +298,83 @@ impl Graph for GridND {
     }
 
     fn describe(&self) {
-        match self.dimensions.len() {
-            0 => {
-                panic!("Wrongly constructed grid graph!")
-            }
-            1 => {
-                if self.glue[0] {
-                    println!("Circle graph with {} points.", self.dimensions[0])
-                }
-            }
-            2 => {
-                if self.glue[0] && self.glue[1] {
-                    println!("2D toroidal graph, of size {}x{}.",
-                             self.dimensions[0], self.dimensions[1])
-                } else if self.glue[0] {
-                    println!("2D cylinder graph, where the first dimension is cyclic, of size {}x{}",
-                             self.dimensions[0], self.dimensions[1])
-                } else if self.glue[1] {
-                    println!("2D cylinder graph, where the second dimension is cyclic, of size {}x{}",
-                             self.dimensions[0], self.dimensions[1])
-                } else {
-                    println!("Rectangular graph, of size {}x{}",
-                             self.dimensions[0], self.dimensions[1])
-                }
-            }
-            _ => {
-                println!("General {}D grid graph, glued at {:?}, of size {:?}", self.dimensions.len(),
-                         self.glue, self.dimensions)
+        println!("{}", self.description());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinates_round_trip_in_3d() {
+        let grid = GridND::from(vec![4, 3, 5]);
+
+        for coords in [[0, 0, 0], [1, 0, 0], [0, 1, 0], [0, 0, 1], [3, 2, 4], [2, 1, 3]] {
+            let index = grid.coords_to_index(&coords).expect("The coordinates are in the grid");
+            assert_eq!(grid.index_to_coords(index), coords.to_vec());
+        }
+        for index in 0..grid.nr_points() {
+            assert_eq!(grid.coords_to_index(&grid.index_to_coords(index)), Some(index));
+        }
+        assert_eq!(grid.coords_to_index(&[3, 2, 4]), Some(59)); // the last point
+    }
+
+    #[test]
+    fn coordinates_round_trip_in_1d_2d_and_4d() {
+        for dimensions in [vec![7], vec![5, 4], vec![2, 3, 2, 3]] {
+            let grid = GridND::from(dimensions.clone());
+
+            for index in 0..grid.nr_points() {
+                assert_eq!(grid.coords_to_index(&grid.index_to_coords(index)), Some(index));
             }
+            // The corners with the smallest and the largest coordinates
+            let last: Vec<usize> = dimensions.iter().map(|dimension| dimension - 1).collect();
+            assert_eq!(grid.coords_to_index(&vec![0; dimensions.len()]), Some(0));
+            assert_eq!(grid.coords_to_index(&last), Some(grid.nr_points() - 1));
+            assert_eq!(grid.index_to_coords(grid.nr_points() - 1), last);
         }
     }
+
+    #[test]
+    fn coordinates_outside_grid() {
+        let grid = GridND::from(vec![4, 3, 5]);
+
+        assert_eq!(grid.coords_to_index(&[4, 0, 0]), None);
+        assert_eq!(grid.coords_to_index(&[0, 0, 5]), None);
+        assert_eq!(grid.coords_to_index(&[0, 0]), None);
+    }
+
+    #[test]
+    fn acyclic_1d_grid_is_line_segment() {
+        let segment = GridND::from((vec![10], vec![false]));
+        let circle = GridND::from(vec![10]);
+
+        assert_eq!(segment.description(), "Line segment graph with 10 points.");
+        assert_eq!(circle.description(), "Circle graph with 10 points.");
+    }
+
+    #[test]
+    fn interior_neighbors_von_neumann_and_moore() {
+        let von_neumann = GridND::open_box(vec![5, 5]);
+        let moore = GridND::new_moore(vec![5, 5], vec![false, false]);
+        // The point (2, 2), and its neighbors (x, y) with index x + 5 * y
+        let center = 12;
+
+        assert_eq!(von_neumann.get_neighbors(center), HashSet::from([7, 11, 13, 17]));
+        assert_eq!(moore.get_neighbors(center), HashSet::from([6, 7, 8, 11, 13, 16, 17, 18]));
+        // 3^d - 1 neighbors in d dimensions
+        assert_eq!(GridND::new_moore(vec![3, 3, 3], vec![false; 3]).get_neighbors(13).len(), 26);
+    }
+
+    #[test]
+    fn cylinder_wraps_only_x() {
+        let cylinder = GridND::cylinder(4, 3);
+
+        // (0, 1) is next to (3, 1) around the cylinder, but (1, 0) is not next to (1, 2)
+        assert_eq!(cylinder.get_neighbors(4), HashSet::from([0, 5, 7, 8]));
+        assert_eq!(cylinder.get_neighbors(1), HashSet::from([0, 2, 5]));
+        assert_eq!(GridND::torus(vec![4, 3]).get_neighbors(1), HashSet::from([0, 2, 5, 9]));
+        assert_eq!(GridND::open_box(vec![4, 3]).get_neighbors(4), HashSet::from([0, 5, 8]));
+    }
 }
@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+use crate::solver::graph::Graph;
+
+/// The n-dimensional hypercube Q_n. The points are the n-bit strings (stored as the bits of the
+/// index), two points are connected if they differ in exactly one bit.
+pub struct Hypercube {
+    dimension: usize,
+}
+
+impl Graph for Hypercube {
+    fn nr_points(&self) -> usize {
+        1 << self.dimension
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        // flip every bit in turn
+        (0..self.dimension).map(|bit| particle ^ (1 << bit)).collect()
+    }
+
+    fn describe(&self) {
+        println!("Hypercube of dimension {} with {} vertices.", self.dimension, self.nr_points());
+    }
+}
+
+impl Hypercube {
+    /// Construct the hypercube of the given dimension, which has `2^dimension` points.
+    pub fn new(dimension: usize) -> Hypercube {
+        assert!(dimension < usize::BITS as usize); // otherwise the points can't be indexed
+
+        Hypercube {
+            dimension,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_vertex_has_dimension_neighbors() {
+        let graph = Hypercube::new(4);
+
+        assert_eq!(graph.nr_points(), 16);
+        for i in 0..graph.nr_points() {
+            let neighbors = graph.get_neighbors(i);
+            assert_eq!(neighbors.len(), 4);
+            for j in neighbors {
+                assert!(j < 16);
+                assert!(graph.get_neighbors(j).contains(&i));
+            }
+        }
+    }
+}
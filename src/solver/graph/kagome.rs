@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+use crate::solver::graph::Graph;
+
+/// Kagome lattice: a lattice of corner-sharing triangles, where every point has four neighbors.
+/// The triangular Bravais lattice is spanned by `a1 = (1, 0)` and `a2 = (1/2, sqrt(3)/2)`, and its
+/// unit cell `(x, y)` contains three points: `A` at `x a1 + y a2`, `B` halfway to the next cell in
+/// the `a1` direction, and `C` halfway to the next cell in the `a2` direction. The points `A, B, C`
+/// of a cell form an upward triangle, and `B(x, y)`, `A(x + 1, y)` and `C(x + 1, y - 1)` form a
+/// downward triangle.
+///
+/// The point with sublattice `s` (0 for `A`, 1 for `B`, 2 for `C`) of the cell `(x, y)` has index
+/// `3 * (x + dim_x * y) + s`.
+pub struct KagomeLattice {
+    dim_x: usize,
+    dim_y: usize,
+    cyclic: bool,
+}
+
+impl Graph for KagomeLattice {
+    fn nr_points(&self) -> usize {
+        3 * self.dim_x * self.dim_y
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        let sublattice = particle % 3;
+        let cell = particle / 3;
+        let (x, y) = ((cell % self.dim_x) as isize, (cell / self.dim_x) as isize);
+
+        // The neighbors as (cell offset in x, cell offset in y, sublattice): two in the own
+        // triangle of the cell, and two in a triangle shared with the neighboring cells.
+        let offsets: [(isize, isize, usize); 4] = match sublattice {
+            0 => [(0, 0, 1), (0, 0, 2), (-1, 0, 1), (0, -1, 2)],
+            1 => [(0, 0, 0), (0, 0, 2), (1, 0, 0), (1, -1, 2)],
+            _ => [(0, 0, 0), (0, 0, 1), (0, 1, 0), (-1, 1, 1)],
+        };
+
+        offsets.iter()
+            .filter_map(|&(dx, dy, s)| self.cell_index(x + dx, y + dy).map(|c| 3 * c + s))
+            .collect()
+    }
+
+    fn describe(&self) {
+        println!("Kagome lattice of {} by {} unit cells ({} points), with {} boundary.",
+                 self.dim_x, self.dim_y, self.nr_points(),
+                 if self.cyclic { "toroidal" } else { "open" });
+    }
+}
+
+impl KagomeLattice {
+    /// Construct the kagome lattice of `dim_x` by `dim_y` unit cells (so `3 * dim_x * dim_y`
+    /// points). If `cyclic`, the lattice wraps around in both directions (a torus), and every point
+    /// has four neighbors. Otherwise the points at the boundary have fewer neighbors.
+    pub fn new(dim_x: usize, dim_y: usize, cyclic: bool) -> KagomeLattice {
+        // on a cycle of length 1 the neighboring cells are the cell itself
+        assert!(dim_x >= 2 && dim_y >= 2);
+
+        KagomeLattice {
+            dim_x,
+            dim_y,
+            cyclic,
+        }
+    }
+
+    /// Index of the cell `(x, y)`, wrapped around if the lattice is cyclic. `None` if the cell is
+    /// outside of an open lattice.
+    fn cell_index(&self, x: isize, y: isize) -> Option<usize> {
+        let (dim_x, dim_y) = (self.dim_x as isize, self.dim_y as isize);
+        if self.cyclic {
+            Some((x.rem_euclid(dim_x) + dim_x * y.rem_euclid(dim_y)) as usize)
+        } else if (0..dim_x).contains(&x) && (0..dim_y).contains(&y) {
+            Some((x + dim_x * y) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interior_points_have_four_neighbors() {
+        let graph = KagomeLattice::new(4, 4, false);
+
+        for i in 0..graph.nr_points() {
+            let cell = i / 3;
+            let (x, y) = (cell % 4, cell / 4);
+            let neighbors = graph.get_neighbors(i);
+            if (1..3).contains(&x) && (1..3).contains(&y) {
+                assert_eq!(neighbors.len(), 4, "point {}", i);
+            }
+            for j in neighbors {
+                assert!(graph.get_neighbors(j).contains(&i));
+            }
+        }
+        let torus = KagomeLattice::new(3, 3, true);
+        assert!((0..torus.nr_points()).all(|i| torus.get_neighbors(i).len() == 4));
+    }
+}
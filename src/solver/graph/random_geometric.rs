@@ -0,0 +1,88 @@
+use std::collections::{HashMap, HashSet};
+use rand::Rng;
+use crate::solver::graph::{Graph, tally_degrees};
+
+/// Random geometric graph: the points are placed uniformly at random in the unit square, and two
+/// different points are connected by an edge if their (Euclidean) distance is at most `radius`.
+pub struct RandomGeometric {
+    radius: f64,
+    /// Position of each point in the unit square
+    coordinates: Vec<(f64, f64)>,
+    adjacency: Vec<HashSet<usize>>,
+}
+
+impl Graph for RandomGeometric {
+    fn nr_points(&self) -> usize {
+        self.coordinates.len()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        self.adjacency[particle].clone()
+    }
+
+    fn degree_distribution(&self) -> HashMap<usize, usize> {
+        tally_degrees(self.adjacency.iter().map(HashSet::len))
+    }
+
+    fn describe(&self) {
+        println!("Random geometric graph with {} points placed uniformly in the unit square: two \
+        different points are connected by an edge if they are at most {} apart.",
+                 self.nr_points(), self.radius);
+    }
+}
+
+impl RandomGeometric {
+    /// Construct a random geometric graph from the number of points and the connection radius.
+    pub fn new<R: Rng>(nr_points: usize, radius: f64, mut rng: R) -> RandomGeometric {
+        let mut coordinates: Vec<(f64, f64)> = vec![];
+        for _ in 0..nr_points {
+            coordinates.push((rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0)));
+        }
+
+        RandomGeometric::from_points(coordinates, radius)
+    }
+
+    /// Construct a geometric graph on prescribed points, connecting two different points if they
+    /// are at most `radius` apart.
+    pub fn from_points(coordinates: Vec<(f64, f64)>, radius: f64) -> RandomGeometric {
+        let mut adjacency: Vec<HashSet<usize>> = vec![HashSet::new(); coordinates.len()];
+
+        // Loop over all unordered pairs of points, and connect them if they're close enough
+        for i in 0..coordinates.len() {
+            for j in 0..i {
+                let (x_i, y_i) = coordinates[i];
+                let (x_j, y_j) = coordinates[j];
+
+                if (x_i - x_j).hypot(y_i - y_j) <= radius {
+                    adjacency[i].insert(j);
+                    adjacency[j].insert(i);
+                }
+            }
+        }
+
+        RandomGeometric {
+            radius,
+            coordinates,
+            adjacency,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connected_iff_within_radius() {
+        // Two points at distance 0.5
+        let points = vec![(0.1, 0.2), (0.4, 0.6)];
+
+        let close = RandomGeometric::from_points(points.clone(), 0.51);
+        let far = RandomGeometric::from_points(points, 0.49);
+
+        assert_eq!(close.get_neighbors(0), HashSet::from([1]));
+        assert_eq!(close.get_neighbors(1), HashSet::from([0]));
+        assert!(far.get_neighbors(0).is_empty());
+        assert!(far.get_neighbors(1).is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+use crate::solver::graph::Graph;
+
+/// Ring of points where every point is connected to its `k` nearest neighbors on either side, so
+/// every point has degree `2k`. For `k = 1` this is the circle graph.
+pub struct RingLattice {
+    nr_points: usize,
+    k: usize,
+}
+
+impl Graph for RingLattice {
+    fn nr_points(&self) -> usize {
+        self.nr_points
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        let mut neighbors: HashSet<usize> = HashSet::new();
+
+        for step in 1..=self.k {
+            neighbors.insert((particle + step) % self.nr_points);
+            // add nr_points before subtracting to stay nonnegative
+            neighbors.insert((particle + self.nr_points - step) % self.nr_points);
+        }
+
+        neighbors
+    }
+
+    fn describe(&self) {
+        println!("Ring lattice with {} points, each connected to its {} nearest neighbors on either \
+        side (degree {}).",
+                 self.nr_points, self.k, 2 * self.k);
+    }
+}
+
+impl RingLattice {
+    /// Construct a ring lattice from the number of points and the number of neighbors on each side.
+    pub fn new(nr_points: usize, k: usize) -> RingLattice {
+        assert!(k >= 1); // k = 0 gives a graph without edges
+        assert!(2 * k < nr_points); // otherwise the neighbors on the two sides overlap
+
+        RingLattice {
+            nr_points,
+            k,
+        }
+    }
+
+    /// The number of neighbors on each side of a point.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn every_point_has_2k_neighbors() {
+        for k in 1..4 {
+            let ring = RingLattice::new(10, k);
+
+            for i in 0..ring.nr_points() {
+                let neighbors = ring.get_neighbors(i);
+                assert_eq!(neighbors.len(), 2 * k);
+                assert!(!neighbors.contains(&i));
+                assert!(neighbors.contains(&((i + k) % 10)) && neighbors.contains(&((i + 10 - k) % 10)));
+            }
+        }
+    }
+
+    #[test]
+    fn every_point_has_same_degree() {
+        let ring = RingLattice::new(12, 3);
+
+        assert_eq!(ring.degree_distribution(), HashMap::from([(6, 12)]));
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::{HashMap, HashSet};
+use rand::distributions::{Bernoulli, Distribution};
+use rand::Rng;
+use crate::solver::graph::{Graph, tally_degrees};
+
+/// Stochastic block model: the points are partitioned into blocks (communities), and two different
+/// points i and j are connected with probability `p_in` if they are in the same block, and with
+/// probability `p_out` otherwise.
+///
+/// Points are numbered block by block: the first block consists of the points
+/// `0..block_sizes[0]`, the second of the next `block_sizes[1]` points, etc.
+pub struct StochasticBlockModel {
+    block_sizes: Vec<usize>,
+    p_in: f64,
+    p_out: f64,
+    adjacency: Vec<HashSet<usize>>,
+}
+
+impl Graph for StochasticBlockModel {
+    fn nr_points(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        self.adjacency[particle].clone()
+    }
+
+    fn degree_distribution(&self) -> HashMap<usize, usize> {
+        tally_degrees(self.adjacency.iter().map(HashSet::len))
+    }
+
+    fn describe(&self) {
+        println!("Stochastic block model with blocks of sizes {:?}: two different points i and j are \
+        connected by an edge with probability {} if they are in the same block, and with probability \
+        {} otherwise.",
+                 self.block_sizes, self.p_in, self.p_out);
+    }
+}
+
+impl StochasticBlockModel {
+    /// Construct a stochastic block model from the sizes of the blocks, and the probabilities of
+    /// an edge within a block and between blocks.
+    pub fn new<R: Rng>(block_sizes: Vec<usize>, p_in: f64, p_out: f64, mut rng: R) -> StochasticBlockModel {
+        let bernoulli_in = Bernoulli::new(p_in).unwrap();
+        let bernoulli_out = Bernoulli::new(p_out).unwrap();
+
+        // Find out which block each point is in
+        let mut block_of_point: Vec<usize> = vec![];
+        for (block, size) in block_sizes.iter().enumerate() {
+            block_of_point.extend(vec![block; *size]);
+        }
+        let nr_points = block_of_point.len();
+
+        let mut adjacency: Vec<HashSet<usize>> = vec![HashSet::new(); nr_points];
+
+        // Loop over all unordered pairs of points, and determine randomly if they're connected
+        for i in 0..nr_points {
+            for j in 0..i {
+                let connected = if block_of_point[i] == block_of_point[j] {
+                    bernoulli_in.sample(&mut rng)
+                } else {
+                    bernoulli_out.sample(&mut rng)
+                };
+
+                if connected {
+                    adjacency[i].insert(j);
+                    adjacency[j].insert(i);
+                }
+            }
+        }
+
+        StochasticBlockModel {
+            block_sizes,
+            p_in,
+            p_out,
+            adjacency,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn certain_blocks_are_disjoint_cliques() {
+        let graph = StochasticBlockModel::new(vec![3, 1, 4], 1.0, 0.0, StdRng::seed_from_u64(1));
+
+        let blocks: Vec<HashSet<usize>> = vec![(0..3).collect(), (3..4).collect(), (4..8).collect()];
+        for block in blocks {
+            for &particle in &block {
+                let mut expected = block.clone();
+                expected.remove(&particle);
+                assert_eq!(graph.get_neighbors(particle), expected, "point {}", particle);
+            }
+        }
+    }
+}
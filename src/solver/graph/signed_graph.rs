@@ -0,0 +1,72 @@
+use std::collections::{HashMap, HashSet};
+use crate::solver::graph::{Graph, tally_degrees};
+
+/// Sign of an edge of a `SignedGraph`: whether the endpoints are friends or enemies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeSign {
+    Positive,
+    Negative,
+}
+
+/// Graph with signed (undirected) edges, stored as adjacency lists. Neighbors connected by a
+/// positive edge are friends, neighbors connected by a negative edge are enemies, as in the
+/// structural balance theory of social networks. The rules decide how the influence of enemies
+/// differs (see `IPSRules::get_enemy_mutation_rate`), e.g., in the signed voter process friends
+/// are copied and enemies are disagreed with.
+pub struct SignedGraph {
+    neighbors: Vec<Vec<(usize, EdgeSign)>>,
+}
+
+impl Graph for SignedGraph {
+    fn nr_points(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        self.neighbors[particle].iter().map(|&(neighbor, _)| neighbor).collect()
+    }
+
+    fn describe(&self) {
+        let nr_edges = self.neighbors.iter().map(|neighs| neighs.len()).sum::<usize>() / 2;
+        let nr_negative = self.neighbors.iter().flatten()
+            .filter(|&&(_, sign)| sign == EdgeSign::Negative)
+            .count() / 2;
+
+        println!("Signed graph with {} nodes and {} edges, of which {} negative.",
+                 self.neighbors.len(), nr_edges, nr_negative);
+    }
+
+    fn get_signed_neighbors(&self, particle: usize) -> Vec<(usize, EdgeSign)> {
+        self.neighbors[particle].clone()
+    }
+
+    fn is_signed(&self) -> bool {
+        true
+    }
+
+    fn degree_distribution(&self) -> HashMap<usize, usize> {
+        tally_degrees(self.neighbors.iter().map(|neighs| neighs.len()))
+    }
+}
+
+impl SignedGraph {
+    /// Construct a graph with `nr_points` points from its edges `(i, j, sign)`. The edges are
+    /// undirected, so every edge should be listed once. There should be no self-loops or
+    /// multi-edges.
+    pub fn new(nr_points: usize, edges: &[(usize, usize, EdgeSign)]) -> SignedGraph {
+        let mut neighbors: Vec<Vec<(usize, EdgeSign)>> = vec![vec![]; nr_points];
+
+        for &(i, j, sign) in edges {
+            assert!(i < nr_points && j < nr_points, "Edge ({}, {}) is not in the graph.", i, j);
+            assert_ne!(i, j, "Self-loop at {}.", i);
+            assert!(neighbors[i].iter().all(|&(neighbor, _)| neighbor != j), "Multi-edge ({}, {}).", i, j);
+
+            neighbors[i].push((j, sign));
+            neighbors[j].push((i, sign));
+        }
+
+        SignedGraph {
+            neighbors,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::HashSet;
+use crate::solver::graph::Graph;
+
+/// Star graph: the hub (point 0) is connected to all leaves (points `1..=nr_leaves`), and the
+/// leaves are only connected to the hub.
+pub struct StarGraph {
+    nr_leaves: usize,
+}
+
+impl Graph for StarGraph {
+    fn nr_points(&self) -> usize {
+        self.nr_leaves + 1
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        if particle == 0 {
+            (1..=self.nr_leaves).collect()
+        } else {
+            HashSet::from([0])
+        }
+    }
+
+    fn describe(&self) {
+        println!("Star graph with a hub and {} leaves.", self.nr_leaves);
+    }
+}
+
+impl StarGraph {
+    /// Construct the star graph with the given number of leaves.
+    pub fn new(nr_leaves: usize) -> StarGraph {
+        assert!(nr_leaves >= 1); // otherwise there are no edges
+
+        StarGraph {
+            nr_leaves,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hub_neighbors_every_leaf() {
+        let graph = StarGraph::new(6);
+
+        assert_eq!(graph.nr_points(), 7);
+        assert_eq!(graph.get_neighbors(0).len(), 6);
+        for leaf in 1..=6 {
+            assert_eq!(graph.get_neighbors(leaf), HashSet::from([0]));
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::{HashMap, HashSet};
+use crate::solver::graph::{Graph, tally_degrees};
+
+/// Graph with weighted (undirected) edges, stored as adjacency lists. A neighbor connected by an
+/// edge of weight `w` contributes `w` times as much to the rates of a point as a neighbor in an
+/// unweighted graph, e.g., to model strong and weak ties in a social network.
+pub struct WeightedAdjacency {
+    neighbors: Vec<Vec<(usize, f64)>>,
+}
+
+impl Graph for WeightedAdjacency {
+    fn nr_points(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    fn get_neighbors(&self, particle: usize) -> HashSet<usize> {
+        self.neighbors[particle].iter().map(|&(neighbor, _)| neighbor).collect()
+    }
+
+    fn describe(&self) {
+        let nr_edges = self.neighbors.iter().map(|neighs| neighs.len()).sum::<usize>() / 2;
+        let total_weight = self.neighbors.iter().flatten().map(|&(_, weight)| weight).sum::<f64>() / 2.0;
+
+        println!("Weighted graph with {} nodes and {} edges, of total weight {}.",
+                 self.neighbors.len(), nr_edges, total_weight);
+    }
+
+    fn get_weighted_neighbors(&self, particle: usize) -> Vec<(usize, f64)> {
+        self.neighbors[particle].clone()
+    }
+
+    fn is_weighted(&self) -> bool {
+        true
+    }
+
+    fn degree_distribution(&self) -> HashMap<usize, usize> {
+        tally_degrees(self.neighbors.iter().map(|neighs| neighs.len()))
+    }
+}
+
+impl WeightedAdjacency {
+    /// Construct a graph with `nr_points` points from its edges `(i, j, weight)`. The edges are
+    /// undirected, so every edge should be listed once. Weights have to be positive, and there
+    /// should be no self-loops or multi-edges.
+    pub fn new(nr_points: usize, edges: &[(usize, usize, f64)]) -> WeightedAdjacency {
+        let mut neighbors: Vec<Vec<(usize, f64)>> = vec![vec![]; nr_points];
+
+        for &(i, j, weight) in edges {
+            assert!(i < nr_points && j < nr_points, "Edge ({}, {}) is not in the graph.", i, j);
+            assert_ne!(i, j, "Self-loop at {}.", i);
+            assert!(weight > 0.0, "Edge ({}, {}) has non-positive weight {}.", i, j, weight);
+            assert!(neighbors[i].iter().all(|&(neighbor, _)| neighbor != j), "Multi-edge ({}, {}).", i, j);
+
+            neighbors[i].push((j, weight));
+            neighbors[j].push((i, weight));
+        }
+
+        WeightedAdjacency {
+            neighbors,
+        }
+    }
+}
@@ -2,8 +2,22 @@ use std::collections::{HashMap};
 
 pub mod si_process;
 pub mod voter_process;
+pub mod linear_voter;
+pub mod signed_voter;
 pub mod two_si_process;
+pub mod asymmetric_two_si;
 pub mod sir_process;
+pub mod sirs_process;
+pub mod seir_partial;
+pub mod anti_voter;
+pub mod deffuant;
+pub mod moran_process;
+pub mod noisy_voter;
+pub mod potts;
+pub mod schelling;
+pub mod forest_fire;
+pub mod multistrain;
+pub mod time_dependent;
 
 /// Trait encoding the rules for the evolution of an interacting particle system.
 /// To be implemented on an enum.
@@ -14,6 +28,11 @@ pub mod sir_process;
 /// * `get_neighbor_mutation_rate`
 /// * `describe`
 ///
+/// The rates of these systems are additive in the neighbors: the rate of a particle is the sum of
+/// the contributions of its individual neighbors. For systems where this is not the case (e.g.,
+/// Glauber dynamics), instead overwrite `get_mutation_rate` and `is_neighbor_additive`, and let
+/// `get_vacuum_mutation_rate` and `get_neighbor_mutation_rate` return 0.0.
+///
 /// The word `reactivity` is reserved for transition of one state to any other state, meaning the
 /// rate at which any update occurs. The word `mutation` is reserved for transition of one state
 /// to a particular other state.
@@ -76,13 +95,7 @@ pub trait IPSRules {
 
         // Condition over to which state `goal` self will transition
         for goal in self.all_states() {
-            // Each goal has an associated vacuum rate
-            running_rate += self.get_vacuum_mutation_rate(current, goal);
-            // as well as a contribution due to the neighbors, which depends on how many of
-            // which neighbor there are.
-            for (neigh_state, neigh_count) in neighbor_counts.into_iter() {
-                running_rate += (*neigh_count as f64) * self.get_neighbor_mutation_rate(current, goal, *neigh_state)
-            }
+            running_rate += self.get_mutation_rate(current, goal, neighbor_counts);
         }
 
         running_rate
@@ -91,7 +104,8 @@ pub trait IPSRules {
     /// Returns the rate at which a particle in a given state `current` changes to a particular state
     /// `other` due to the influence of all of its neighbors.
     ///
-    /// Do not overwrite, the default implementation is correct.
+    /// Do not overwrite, unless the rates are not additive in the neighbors (see
+    /// `is_neighbor_additive`).
     fn get_mutation_rate(&self, current: usize, goal: usize, neighbor_counts: &HashMap<usize, usize>) -> f64 {
         // Start with the vacuum rate of changing self to goal
         let mut running_rate = self.get_vacuum_mutation_rate(current, goal);
@@ -103,5 +117,100 @@ pub trait IPSRules {
         running_rate
     }
 
+    /// Returns the rate at which a particle in a given state `current` changes to a particular state
+    /// `goal` on a weighted graph, where `neighbor_weights` gives for each state the total weight of
+    /// the edges to the neighbors in that state (see `Graph::get_weighted_neighbors`). Every
+    /// neighbor contributes its `get_neighbor_mutation_rate` times the weight of its edge, so for
+    /// weights 1.0 this is `get_mutation_rate`.
+    ///
+    /// Do not overwrite. Only meaningful if the rates are additive in the neighbors.
+    fn get_weighted_mutation_rate(&self, current: usize, goal: usize, neighbor_weights: &HashMap<usize, f64>) -> f64 {
+        let mut running_rate = self.get_vacuum_mutation_rate(current, goal);
+        for (neigh_state, neigh_weight) in neighbor_weights.iter() {
+            running_rate += neigh_weight * self.get_neighbor_mutation_rate(current, goal, *neigh_state)
+        }
+
+        running_rate
+    }
+
+    /// Returns the rate at which a particle in a given state `current` changes to any other state on
+    /// a weighted graph, see `get_weighted_mutation_rate`.
+    ///
+    /// Do not overwrite, the default implementation is correct.
+    fn get_weighted_reactivity(&self, current: usize, neighbor_weights: &HashMap<usize, f64>) -> f64 {
+        let mut running_rate = 0.0;
+
+        for goal in self.all_states() {
+            running_rate += self.get_weighted_mutation_rate(current, goal, neighbor_weights);
+        }
+
+        running_rate
+    }
+
+    /// Returns the increase in rate at which a particle in a given state `current` changes to a
+    /// state `goal` due to the presence of an enemy in the state `sender`, i.e., a neighbor across
+    /// a negative edge of a signed graph (see `Graph::get_signed_neighbors`).
+    ///
+    /// Overwrite for systems where enemies have a different influence than friends (e.g., the
+    /// signed voter process), the default implementation ignores the sign of the edge.
+    fn get_enemy_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        self.get_neighbor_mutation_rate(current, goal, sender)
+    }
+
+    /// Returns the rate at which a particle in a given state `current` changes to a particular state
+    /// `goal` on a signed graph, where `friend_counts` and `enemy_counts` give the number of
+    /// neighbors in each state across positive and negative edges respectively. Friends contribute
+    /// their `get_neighbor_mutation_rate`, enemies their `get_enemy_mutation_rate`.
+    ///
+    /// Do not overwrite. Only meaningful if the rates are additive in the neighbors.
+    fn get_signed_mutation_rate(&self, current: usize, goal: usize, friend_counts: &HashMap<usize, usize>,
+                                enemy_counts: &HashMap<usize, usize>) -> f64 {
+        let mut running_rate = self.get_mutation_rate(current, goal, friend_counts);
+        for (neigh_state, neigh_count) in enemy_counts.iter() {
+            running_rate += (*neigh_count as f64) * self.get_enemy_mutation_rate(current, goal, *neigh_state)
+        }
+
+        running_rate
+    }
+
+    /// Returns the rate at which a particle in a given state `current` changes to any other state on
+    /// a signed graph, see `get_signed_mutation_rate`.
+    ///
+    /// Do not overwrite, the default implementation is correct.
+    fn get_signed_reactivity(&self, current: usize, friend_counts: &HashMap<usize, usize>,
+                             enemy_counts: &HashMap<usize, usize>) -> f64 {
+        let mut running_rate = 0.0;
+
+        for goal in self.all_states() {
+            running_rate += self.get_signed_mutation_rate(current, goal, friend_counts, enemy_counts);
+        }
+
+        running_rate
+    }
+
+    /// Returns the first time after `time` at which the rates change, for rules whose rates depend
+    /// on the simulated time (see `TimeDependentRules`), or `None` if they don't change anymore.
+    /// The solver then calls `set_time` at that time, and recomputes all reactivities.
+    ///
+    /// Do not overwrite, unless the rates depend on time.
+    fn next_rate_change(&self, _time: f64) -> Option<f64> {
+        None
+    }
+
+    /// Set the rates to those in effect at the simulated time `time`.
+    ///
+    /// Do not overwrite, unless the rates depend on time.
+    fn set_time(&mut self, _time: f64) {}
+
+    /// Are the rates additive in the neighbors, i.e., given by `get_vacuum_mutation_rate` and
+    /// `get_neighbor_mutation_rate`? If so, the solver can update the reactivities of the neighbors
+    /// of a changed particle incrementally. Otherwise they are recomputed from scratch, which is
+    /// slower.
+    ///
+    /// Overwrite (returning false) only together with `get_mutation_rate`.
+    fn is_neighbor_additive(&self) -> bool {
+        true
+    }
+
     fn describe(&self);
 }
\ No newline at end of file
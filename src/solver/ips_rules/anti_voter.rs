@@ -0,0 +1,63 @@
+use crate::{Coloration, IPSRules};
+use crate::solver::ips_rules::voter_process::party_color;
+
+// 0: first party, 1: second party, etc. Parameters described in main.rs.
+// Anticonformist counterpart of the voter process: a particle switches to a party at a rate
+// proportional to the number of its neighbors *not* in that party, so parties that are a minority
+// in the neighborhood are favored.
+pub struct AntiVoter {
+    pub nr_parties: usize,
+    pub rate: f64,
+}
+
+impl IPSRules for AntiVoter {
+    fn all_states(&self) -> Vec<usize> {
+        (0..self.nr_parties).collect()
+    }
+
+    fn get_vacuum_mutation_rate(&self, _: usize, _: usize) -> f64 {
+        0.0
+    }
+
+    // The rate to `goal` is `rate` times the number of neighbors not in `goal`. This is a sum of
+    // contributions of the individual neighbors, so it fits in the neighbor mutation rate (which
+    // keeps the incremental reactivity updates in the solver correct).
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        if current == goal { // No change
+            0.0
+        } else if sender == goal { // A neighbor in the goal party does not push towards it
+            0.0
+        } else { // Remains: current != goal != sender
+            self.rate
+        }
+    }
+
+    fn describe(&self) {
+        println!("Antivoter (anticonformist) process with {} parties, and rate {} per neighbor \
+        not in the party being switched to.",
+                 self.nr_parties, self.rate)
+    }
+}
+
+impl Coloration for AntiVoter {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        party_color(state, self.nr_parties)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn surrounded_particle_switches_away_fastest() {
+        let rules = AntiVoter { nr_parties: 2, rate: 1.0 };
+
+        let surrounded = rules.get_mutation_rate(0, 1, &HashMap::from([(0, 4)]));
+        let mixed = rules.get_mutation_rate(0, 1, &HashMap::from([(0, 2), (1, 2)]));
+        let outnumbered = rules.get_mutation_rate(0, 1, &HashMap::from([(1, 4)]));
+
+        assert_eq!((surrounded, mixed, outnumbered), (4.0, 2.0, 0.0));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{Coloration, IPSRules};
+
+// 0: no party (neutral), 1: first species, 2: second species. Parameters described in main.rs.
+// The two SI process where every rate can be different for the two species: `birth1` and `death1`
+// are the birth and death rates of species 1, `compete12` is the rate at which a neighbor of
+// species 1 converts a particle of species 2, and vice versa.
+pub struct AsymmetricTwoSI {
+    pub birth1: f64,
+    pub death1: f64,
+    pub birth2: f64,
+    pub death2: f64,
+    pub compete12: f64,
+    pub compete21: f64,
+}
+
+impl IPSRules for AsymmetricTwoSI {
+    fn all_states(&self) -> Vec<usize> {
+        vec![0, 1, 2]
+    }
+
+    fn get_vacuum_mutation_rate(&self, current: usize, goal: usize) -> f64 {
+        match (current, goal) {
+            (1, 0) => { self.death1 } // death
+            (2, 0) => { self.death2 }
+            _ => { 0.0 }
+        }
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        match (current, goal, sender) {
+            (0, 1, 1) => { self.birth1 } // birth
+            (0, 2, 2) => { self.birth2 }
+            (2, 1, 1) => { self.compete12 } // species 1 converts species 2
+            (1, 2, 2) => { self.compete21 }
+            _ => { 0.0 }
+        }
+    }
+
+    fn describe(&self) {
+        println!("SI model with two invasive species (states 1 and 2) with different rates, competing \
+        indirectly via the available space, and directly via conversion (i.e., combat). Species 1 \
+        has birth rate {} and death rate {}, species 2 has birth rate {} and death rate {}. Species 1 \
+        converts species 2 at rate {}, and species 2 converts species 1 at rate {}.",
+                 self.birth1, self.death1, self.birth2, self.death2, self.compete12, self.compete21)
+    }
+}
+
+impl Coloration for AsymmetricTwoSI {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        match state {
+            0 => { [0, 0, 0, 255] }
+            1 => { [180, 12, 13, 255] }
+            2 => { [16, 128, 16, 255] }
+            _ => { panic!("Invalid state in coloration.") }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn species_have_distinct_birth_rates() {
+        let rules = AsymmetricTwoSI { birth1: 2.0, death1: 1.0, birth2: 0.5, death2: 1.0, compete12: 0.0, compete21: 0.0 };
+        // An empty particle with one neighbor of each species
+        let neighbors = HashMap::from([(1, 1), (2, 1)]);
+
+        assert_eq!(rules.get_mutation_rate(0, 1, &neighbors), 2.0);
+        assert_eq!(rules.get_mutation_rate(0, 2, &neighbors), 0.5);
+        assert_eq!(rules.get_reactivity(0, &neighbors), 2.5);
+    }
+}
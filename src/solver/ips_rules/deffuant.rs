@@ -0,0 +1,71 @@
+use crate::{Coloration, IPSRules};
+
+// 0, 1, ..., nr_bins - 1: discretized opinions. Parameters described in main.rs.
+// Bounded-confidence opinion dynamics: a neighbor only influences a particle if their opinions
+// differ by at most `confidence` bins, in which case the particle moves one bin towards the
+// neighbor's opinion at rate `mu`.
+pub struct Deffuant {
+    pub nr_bins: usize,
+    pub confidence: usize,
+    pub mu: f64,
+}
+
+impl IPSRules for Deffuant {
+    fn all_states(&self) -> Vec<usize> {
+        (0..self.nr_bins).collect()
+    }
+
+    fn get_vacuum_mutation_rate(&self, _: usize, _: usize) -> f64 {
+        0.0
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        if current.abs_diff(sender) > self.confidence { // Opinions too far apart to interact
+            0.0
+        } else if sender > current && goal == current + 1 { // One step up towards the sender
+            self.mu
+        } else if sender < current && goal + 1 == current { // One step down towards the sender
+            self.mu
+        } else { // Remains: equal opinions, or a goal which is not one step towards the sender
+            0.0
+        }
+    }
+
+    fn describe(&self) {
+        println!("Deffuant bounded-confidence model with {} opinion bins. Neighbors at most {} \
+        bins apart move one bin towards each other at rate {}.",
+                 self.nr_bins, self.confidence, self.mu)
+    }
+}
+
+impl Coloration for Deffuant {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        // Grayscale gradient from black (opinion 0) to white (opinion nr_bins - 1)
+        let brightness = (255.0 * state as f64 / (self.nr_bins - 1).max(1) as f64).round() as u8;
+        [brightness, brightness, brightness, 255]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_neighbors_within_confidence_interact() {
+        let rules = Deffuant { nr_bins: 10, confidence: 2, mu: 1.0 };
+
+        assert_eq!(rules.get_neighbor_mutation_rate(4, 5, 6), 1.0); // at the boundary
+        assert_eq!(rules.get_neighbor_mutation_rate(4, 5, 7), 0.0); // just beyond it
+        assert_eq!(rules.get_neighbor_mutation_rate(4, 3, 2), 1.0);
+        assert_eq!(rules.get_neighbor_mutation_rate(4, 3, 1), 0.0);
+    }
+
+    #[test]
+    fn only_step_towards_sender() {
+        let rules = Deffuant { nr_bins: 10, confidence: 2, mu: 1.0 };
+
+        assert_eq!(rules.get_neighbor_mutation_rate(4, 3, 6), 0.0); // away from the sender
+        assert_eq!(rules.get_neighbor_mutation_rate(4, 6, 6), 0.0); // more than one step
+        assert_eq!(rules.get_neighbor_mutation_rate(4, 5, 4), 0.0); // equal opinions
+    }
+}
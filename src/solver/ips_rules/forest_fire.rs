@@ -0,0 +1,66 @@
+use crate::{Coloration, IPSRules};
+
+// 0: Empty, 1: Tree, 2: Burning. Parameters described in main.rs.
+// Drossel-Schwabl forest-fire model. Trees grow on empty sites, are struck by lightning, and catch
+// fire from burning neighbors at rate 1 per burning neighbor (which sets the time unit). Burning
+// trees burn down, leaving an empty site.
+pub struct ForestFire {
+    pub growth_rate: f64,
+    pub lightning_rate: f64,
+    pub burn_rate: f64,
+}
+
+impl IPSRules for ForestFire {
+    fn all_states(&self) -> Vec<usize> {
+        vec![0, 1, 2]
+    }
+
+    fn get_vacuum_mutation_rate(&self, current: usize, goal: usize) -> f64 {
+        match (current, goal) {
+            (0, 1) => { self.growth_rate }
+            (1, 2) => { self.lightning_rate }
+            (2, 0) => { self.burn_rate }
+            _ => { 0.0 }
+        }
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        match (current, goal, sender) {
+            (1, 2, 2) => { 1.0 }
+            _ => { 0.0 }
+        }
+    }
+
+    fn describe(&self) {
+        println!("Forest-fire model, with growth rate {}, lightning rate {} and burn rate {}",
+                 self.growth_rate, self.lightning_rate, self.burn_rate)
+    }
+}
+
+impl Coloration for ForestFire {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        match state {
+            0 => { [101, 67, 33, 255] } // brown
+            1 => { [34, 139, 34, 255] } // green
+            2 => { [255, 140, 0, 255] } // orange
+            _ => {
+                panic!("State not colored!")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn ignition_rate_with_two_burning_neighbors() {
+        let rules = ForestFire { growth_rate: 0.5, lightning_rate: 0.125, burn_rate: 1.0 };
+
+        let ignition = rules.get_mutation_rate(1, 2, &HashMap::from([(0, 1), (1, 1), (2, 2)]));
+
+        assert_eq!(ignition, 2.125); // lightning plus one per burning neighbor
+    }
+}
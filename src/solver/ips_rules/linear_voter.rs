@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use crate::{Coloration, IPSRules};
+use crate::solver::ips_rules::voter_process::party_color;
+
+// 0: first party, 1: second party, etc. Parameters described in main.rs.
+// The linear voter model: a particle copies the party of a uniformly random neighbor at rate 1, so
+// it joins a party at rate equal to the fraction of its neighbors in that party. Unlike in the
+// voter process, the total rate of a particle doesn't grow with its number of neighbors, which
+// matters on graphs with heterogeneous degrees. Particles without neighbors never change.
+pub struct LinearVoter {
+    pub nr_parties: usize,
+}
+
+impl IPSRules for LinearVoter {
+    fn all_states(&self) -> Vec<usize> {
+        (0..self.nr_parties).collect()
+    }
+
+    // Not used, the rates are not additive in the neighbors
+    fn get_vacuum_mutation_rate(&self, _: usize, _: usize) -> f64 {
+        0.0
+    }
+
+    // Not used, the rates are not additive in the neighbors
+    fn get_neighbor_mutation_rate(&self, _: usize, _: usize, _: usize) -> f64 {
+        0.0
+    }
+
+    fn get_mutation_rate(&self, current: usize, goal: usize, neighbor_counts: &HashMap<usize, usize>) -> f64 {
+        if current == goal { // No change
+            return 0.0;
+        }
+
+        let nr_neighbors: usize = neighbor_counts.values().sum();
+        if nr_neighbors == 0 { // Nobody to copy from
+            return 0.0;
+        }
+
+        *neighbor_counts.get(&goal).unwrap_or(&0) as f64 / nr_neighbors as f64
+    }
+
+    fn is_neighbor_additive(&self) -> bool {
+        false
+    }
+
+    fn describe(&self) {
+        println!("Linear voter process with {} parties, where particles copy a random neighbor at rate 1.",
+                 self.nr_parties)
+    }
+}
+
+impl Coloration for LinearVoter {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        party_color(state, self.nr_parties)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_are_neighbor_fractions() {
+        let rules = LinearVoter { nr_parties: 3 };
+        // Three of the four neighbors in party 1
+        let neighbors = HashMap::from([(1, 3), (2, 1)]);
+
+        assert_eq!(rules.get_mutation_rate(0, 1, &neighbors), 0.75);
+        assert_eq!(rules.get_mutation_rate(0, 2, &neighbors), 0.25);
+        assert_eq!(rules.get_reactivity(0, &neighbors), 1.0);
+        assert_eq!(rules.get_reactivity(0, &HashMap::new()), 0.0);
+    }
+}
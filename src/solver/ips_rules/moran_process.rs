@@ -0,0 +1,58 @@
+use crate::{Coloration, IPSRules};
+use crate::solver::ips_rules::voter_process::party_color;
+
+// 0: first type, 1: second type, etc. Parameters described in main.rs.
+// Moran process on a graph: particles reproduce proportional to their fitness, and the offspring
+// replaces a neighbor. Seen from the replaced particle, this is a voter process where the influence
+// of a neighbor is weighted by the fitness of its type.
+pub struct MoranProcess {
+    pub fitness: Vec<f64>,
+}
+
+impl IPSRules for MoranProcess {
+    fn all_states(&self) -> Vec<usize> {
+        (0..self.fitness.len()).collect()
+    }
+
+    fn get_vacuum_mutation_rate(&self, _: usize, _: usize) -> f64 {
+        0.0
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        if current == goal { // No change
+            0.0
+        } else if sender == goal { // Offspring of the neighbor replaces the particle
+            self.fitness[sender]
+        } else { // Remains: the neighbor is not of the goal type
+            0.0
+        }
+    }
+
+    fn describe(&self) {
+        println!("Moran process with {} types, with fitness {:?}.",
+                 self.fitness.len(), self.fitness)
+    }
+}
+
+impl Coloration for MoranProcess {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        party_color(state, self.fitness.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn fitter_type_replaces_faster() {
+        let rules = MoranProcess { fitness: vec![1.0, 2.0] };
+
+        // Two neighbors of the other type
+        let invaded_by_fit = rules.get_mutation_rate(0, 1, &HashMap::from([(1, 2)]));
+        let invaded_by_unfit = rules.get_mutation_rate(1, 0, &HashMap::from([(0, 2)]));
+
+        assert_eq!((invaded_by_fit, invaded_by_unfit), (4.0, 2.0));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{Coloration, IPSRules};
+use crate::solver::ips_rules::voter_process::party_color;
+
+// 0: Susceptible, 1: Infected by the first strain, 2: Infected by the second strain, etc.
+// Parameters described in main.rs.
+// Multitype contact process: every strain spreads to susceptible neighbors at its own birth rate,
+// and infected particles recover at the death rate of their strain. There is no direct conversion
+// between strains, they only compete for the susceptible particles.
+pub struct MultiStrainContact {
+    pub birth_rates: Vec<f64>,
+    pub death_rates: Vec<f64>,
+}
+
+impl IPSRules for MultiStrainContact {
+    fn all_states(&self) -> Vec<usize> {
+        (0..=self.birth_rates.len()).collect()
+    }
+
+    fn get_vacuum_mutation_rate(&self, current: usize, goal: usize) -> f64 {
+        if current > 0 && goal == 0 { // Recovery of an infected particle
+            self.death_rates[current - 1]
+        } else {
+            0.0
+        }
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        if current == 0 && goal > 0 && sender == goal { // Infection by a neighbor of that strain
+            self.birth_rates[goal - 1]
+        } else {
+            0.0
+        }
+    }
+
+    fn describe(&self) {
+        println!("Multitype contact process with {} strains, with birth rates {:?} and death rates \
+        {:?}.", self.birth_rates.len(), self.birth_rates, self.death_rates)
+    }
+}
+
+impl Coloration for MultiStrainContact {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        match state {
+            0 => { [0, 0, 0, 255] }
+            _ => { party_color(state, self.birth_rates.len() + 1) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn neighbor_only_spreads_own_strain() {
+        let rules = MultiStrainContact { birth_rates: vec![1.0, 0.5], death_rates: vec![0.25, 0.25] };
+        let neighbors = HashMap::from([(2, 3)]);
+
+        assert_eq!(rules.get_mutation_rate(0, 1, &neighbors), 0.0);
+        assert_eq!(rules.get_mutation_rate(0, 2, &neighbors), 1.5);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{Coloration, IPSRules};
+use crate::solver::ips_rules::voter_process::party_color;
+
+// 0: first party, 1: second party, etc. Parameters described in main.rs.
+// Voter process where particles additionally switch party spontaneously. The noise prevents the
+// process from being absorbed in consensus, so it has a nontrivial stationary distribution.
+pub struct NoisyVoter {
+    pub nr_parties: usize,
+    pub copy_rate: f64,
+    pub noise_rate: f64,
+}
+
+impl IPSRules for NoisyVoter {
+    fn all_states(&self) -> Vec<usize> {
+        (0..self.nr_parties).collect()
+    }
+
+    // The total noise rate is divided evenly over the other parties
+    fn get_vacuum_mutation_rate(&self, current: usize, goal: usize) -> f64 {
+        if current == goal { // No change
+            0.0
+        } else {
+            self.noise_rate / (self.nr_parties - 1) as f64
+        }
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        if current == goal { // No change
+            0.0
+        } else if sender == goal { // Copy the party of the neighbor
+            self.copy_rate
+        } else { // Remains: the neighbor is not in the goal party
+            0.0
+        }
+    }
+
+    fn describe(&self) {
+        println!("Noisy voter process with {} parties, copy rate {} and noise rate {}.",
+                 self.nr_parties, self.copy_rate, self.noise_rate)
+    }
+}
+
+impl Coloration for NoisyVoter {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        party_color(state, self.nr_parties)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn no_configuration_is_absorbing() {
+        let rules = NoisyVoter { nr_parties: 3, copy_rate: 1.0, noise_rate: 0.5 };
+
+        // Every state of a particle with four neighbors, in particular the consensus
+        for current in 0..3 {
+            for nr_first in 0..=4 {
+                for nr_second in 0..=4 - nr_first {
+                    let neighbors = HashMap::from([(0, nr_first), (1, nr_second), (2, 4 - nr_first - nr_second)]);
+                    let reactivity: f64 = (0..3).map(|goal| rules.get_mutation_rate(current, goal, &neighbors)).sum();
+                    assert!(reactivity > 0.0, "{} with neighbors {:?}", current, neighbors);
+                }
+            }
+        }
+    }
+}
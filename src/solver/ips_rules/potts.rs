@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use crate::{Coloration, IPSRules};
+use crate::solver::ips_rules::voter_process::party_color;
+
+// 0: first spin value, 1: second spin value, etc. Parameters described in main.rs.
+// q-state Potts model with Glauber dynamics. The energy is minus the number of pairs of aligned
+// (equal) neighbors. A spin flips to each other value at rate `1 / (1 + exp(beta * dE))`, where
+// `dE` is the change in energy, i.e., the number of neighbors aligned with the current value minus
+// the number aligned with the new value. At low temperature (large `beta`) domains coarsen, at
+// high temperature the spins are disordered.
+pub struct PottsModel {
+    pub q: usize,
+    pub beta: f64,
+}
+
+impl IPSRules for PottsModel {
+    fn all_states(&self) -> Vec<usize> {
+        (0..self.q).collect()
+    }
+
+    // Not used, the rates are not additive in the neighbors
+    fn get_vacuum_mutation_rate(&self, _: usize, _: usize) -> f64 {
+        0.0
+    }
+
+    // Not used, the rates are not additive in the neighbors
+    fn get_neighbor_mutation_rate(&self, _: usize, _: usize, _: usize) -> f64 {
+        0.0
+    }
+
+    fn get_mutation_rate(&self, current: usize, goal: usize, neighbor_counts: &HashMap<usize, usize>) -> f64 {
+        if current == goal { // No change
+            return 0.0;
+        }
+
+        let aligned_current = *neighbor_counts.get(&current).unwrap_or(&0) as f64;
+        let aligned_goal = *neighbor_counts.get(&goal).unwrap_or(&0) as f64;
+        let energy_change = aligned_current - aligned_goal;
+
+        1.0 / (1.0 + (self.beta * energy_change).exp())
+    }
+
+    fn is_neighbor_additive(&self) -> bool {
+        false
+    }
+
+    fn describe(&self) {
+        println!("{}-state Potts model with Glauber dynamics, at inverse temperature {}.",
+                 self.q, self.beta)
+    }
+}
+
+impl Coloration for PottsModel {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        party_color(state, self.q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glauber_rates_for_three_states() {
+        // At this temperature, exp(beta * dE) = 2^dE
+        let rules = PottsModel { q: 3, beta: 2.0_f64.ln() };
+
+        let balanced = HashMap::from([(0, 2), (1, 1), (2, 1)]);
+        let surrounded_by_1 = HashMap::from([(0, 1), (1, 3)]);
+        let expected = [
+            (&balanced, 1, 1.0 / 3.0), // dE = 2 - 1
+            (&balanced, 2, 1.0 / 3.0),
+            (&surrounded_by_1, 1, 0.8), // dE = 1 - 3
+            (&surrounded_by_1, 2, 1.0 / 3.0), // dE = 1 - 0
+            (&surrounded_by_1, 0, 0.0), // no change
+        ];
+
+        for (neighbor_counts, goal, rate) in expected {
+            let computed = rules.get_mutation_rate(0, goal, neighbor_counts);
+            assert!((computed - rate).abs() < 1e-12, "{:?} to {}: {}", neighbor_counts, goal, computed);
+        }
+    }
+}
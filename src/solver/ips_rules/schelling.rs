@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use crate::{Coloration, IPSRules};
+use crate::solver::ips_rules::voter_process::party_color;
+
+// 0: empty, 1: agent of the first type, 2: agent of the second type. Parameters described in main.rs.
+// Schelling segregation model, in its open city form (agents move out of the system and newcomers
+// move in, since particles can't move in this framework). An agent is unhappy if the fraction of
+// agents of its own type among its occupied neighbors is below `tolerance`, and unhappy agents
+// move out at rate 1. Agents without occupied neighbors are happy. Empty sites are taken at rate 1
+// by a newcomer of a uniformly random type.
+pub struct Schelling {
+    pub tolerance: f64,
+}
+
+impl Schelling {
+    /// Is an agent in state `current` (1 or 2) with these neighbors unhappy?
+    pub fn is_unhappy(&self, current: usize, neighbor_counts: &HashMap<usize, usize>) -> bool {
+        let same_type = *neighbor_counts.get(&current).unwrap_or(&0);
+        let occupied = neighbor_counts.iter()
+            .filter(|(state, _)| **state != 0)
+            .map(|(_, count)| count)
+            .sum::<usize>();
+
+        occupied > 0 && (same_type as f64) < self.tolerance * occupied as f64
+    }
+}
+
+impl IPSRules for Schelling {
+    fn all_states(&self) -> Vec<usize> {
+        vec![0, 1, 2]
+    }
+
+    // Not used, the rates are not additive in the neighbors
+    fn get_vacuum_mutation_rate(&self, _: usize, _: usize) -> f64 {
+        0.0
+    }
+
+    // Not used, the rates are not additive in the neighbors
+    fn get_neighbor_mutation_rate(&self, _: usize, _: usize, _: usize) -> f64 {
+        0.0
+    }
+
+    fn get_mutation_rate(&self, current: usize, goal: usize, neighbor_counts: &HashMap<usize, usize>) -> f64 {
+        match (current, goal) {
+            (0, 1) | (0, 2) => { 0.5 } // A newcomer moves in
+            (1, 0) | (2, 0) if self.is_unhappy(current, neighbor_counts) => { 1.0 } // An unhappy agent moves out
+            _ => { 0.0 } // Agents don't change type
+        }
+    }
+
+    fn is_neighbor_additive(&self) -> bool {
+        false
+    }
+
+    fn describe(&self) {
+        println!("Schelling segregation model (open city), where agents with less than a fraction \
+        {} of neighbors of their own type move out.",
+                 self.tolerance)
+    }
+}
+
+impl Coloration for Schelling {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        match state {
+            0 => { [255, 255, 255, 255] } // white
+            _ => { party_color(state - 1, 2) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn happy_at_tolerance_boundary() {
+        let rules = Schelling { tolerance: 0.5 };
+
+        // Exactly half of the occupied neighbors are of the same type
+        assert!(!rules.is_unhappy(1, &HashMap::from([(1, 2), (2, 2)])));
+        assert!(!rules.is_unhappy(1, &HashMap::from([(0, 2), (1, 1), (2, 1)]))); // empty sites don't count
+        assert!(rules.is_unhappy(1, &HashMap::from([(1, 1), (2, 3)])));
+        assert_eq!(rules.get_mutation_rate(1, 0, &HashMap::from([(1, 2), (2, 2)])), 0.0);
+        assert_eq!(rules.get_mutation_rate(1, 0, &HashMap::from([(1, 1), (2, 3)])), 1.0);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{Coloration, IPSRules};
+
+// 0: Susceptible, 1: Exposed, 2: Infected, 3: Removed. Parameters described in main.rs.
+pub struct SEIRPartial {
+    pub birth_i: f64,
+    pub birth_e_fraction: f64,
+    pub incubation_rate: f64,
+    pub recovery_rate: f64,
+}
+
+impl IPSRules for SEIRPartial {
+    fn all_states(&self) -> Vec<usize> {
+        vec![0, 1, 2, 3]
+    }
+
+    fn get_vacuum_mutation_rate(&self, current: usize, goal: usize) -> f64 {
+        match (current, goal) {
+            (1, 2) => { self.incubation_rate } // exposed becomes infectious
+            (2, 3) => { self.recovery_rate } // removal
+            _ => { 0.0 }
+        }
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        match (current, goal, sender) {
+            (0, 1, 2) => { self.birth_i } // exposure by an infected neighbor
+            (0, 1, 1) => { self.birth_i * self.birth_e_fraction } // exposure by an exposed neighbor
+            _ => { 0.0 }
+        }
+    }
+
+    fn describe(&self) {
+        println!("Susceptible-Exposed-Infected-Removed process where the exposed are partially \
+        infectious. Infected neighbors expose susceptible particles at rate {}, exposed neighbors at \
+        a fraction {} of that rate. Exposed particles become infected at incubation rate {}, and \
+        infected particles are removed at recovery rate {}.",
+                 self.birth_i, self.birth_e_fraction, self.incubation_rate, self.recovery_rate)
+    }
+}
+
+impl Coloration for SEIRPartial {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        match state {
+            0 => { [0, 0, 0, 255] }
+            1 => { [219, 97, 0, 255] }
+            2 => { [180, 12, 13, 255] }
+            3 => { [97, 97, 97, 255] }
+            _ => {
+                panic!("State not colored!")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn exposed_neighbors_infect_at_reduced_rate() {
+        let rules = SEIRPartial { birth_i: 2.0, birth_e_fraction: 0.25, incubation_rate: 1.0, recovery_rate: 1.0 };
+
+        let by_exposed = rules.get_mutation_rate(0, 1, &HashMap::from([(1, 2)]));
+        let by_infected = rules.get_mutation_rate(0, 1, &HashMap::from([(2, 2)]));
+
+        assert!(by_exposed > 0.0);
+        assert!(by_exposed < by_infected);
+        assert_eq!(by_exposed, 0.25 * by_infected);
+    }
+}
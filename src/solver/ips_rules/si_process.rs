@@ -5,6 +5,9 @@ use crate::visualization::{Coloration};
 pub struct SIProcess {
     pub death_rate: f64,
     pub birth_rate: f64,
+    /// Rate at which susceptible particles get infected without infected neighbors (an external
+    /// field). Prevents absorption in the all-susceptible state if positive.
+    pub spontaneous_rate: f64,
 }
 
 impl IPSRules for SIProcess {
@@ -15,6 +18,7 @@ impl IPSRules for SIProcess {
     fn get_vacuum_mutation_rate(&self, current: usize, goal: usize) -> f64 {
         match (current, goal) {
             (1, 0) => { self.death_rate } // death
+            (0, 1) => { self.spontaneous_rate } // spontaneous infection
             _ => { 0.0 }
         }
     }
@@ -27,8 +31,13 @@ impl IPSRules for SIProcess {
     }
 
     fn describe(&self) {
-        println!("Susceptible-Infected (aka contact) process with birth rate {} and death rate {}.",
-                 self.birth_rate, self.death_rate)
+        if self.spontaneous_rate == 0.0 {
+            println!("Susceptible-Infected (aka contact) process with birth rate {} and death rate {}.",
+                     self.birth_rate, self.death_rate)
+        } else {
+            println!("Susceptible-Infected (aka contact) process with birth rate {}, death rate {}, \
+            and spontaneous infection rate {}.", self.birth_rate, self.death_rate, self.spontaneous_rate)
+        }
     }
 }
 
@@ -42,4 +51,17 @@ impl Coloration for SIProcess {
             panic!("State color not defined!")
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn isolated_particle_infected_spontaneously() {
+        let rules = SIProcess { death_rate: 1.0, birth_rate: 2.0, spontaneous_rate: 0.25 };
+
+        assert_eq!(rules.get_mutation_rate(0, 1, &HashMap::new()), 0.25);
+        assert_eq!(rules.get_reactivity(0, &HashMap::new()), 0.25);
+    }
+}
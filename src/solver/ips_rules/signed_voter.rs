@@ -0,0 +1,50 @@
+use crate::{Coloration, IPSRules};
+use crate::solver::ips_rules::voter_process::party_color;
+
+// 0: first party, 1: second party, etc. Parameters described in main.rs.
+// The voter process on a signed graph (see `SignedGraph`): a particle joins the party of a friend
+// at rate `change_rate`, as in the voter process, and leaves the party of an enemy at rate
+// `change_rate`, for a uniformly random other party. So an enemy in party `b` makes a particle
+// join each party other than `b` at rate `change_rate / (nr_parties - 1)`; for two parties, the
+// particle takes the opposite party. On an unsigned graph this is the voter process.
+pub struct SignedVoter {
+    pub nr_parties: usize,
+    pub change_rate: f64,
+}
+
+impl IPSRules for SignedVoter {
+    fn all_states(&self) -> Vec<usize> {
+        (0..self.nr_parties).collect()
+    }
+
+    fn get_vacuum_mutation_rate(&self, _: usize, _: usize) -> f64 {
+        0.0
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        if current != goal && goal == sender {
+            self.change_rate
+        } else {
+            0.0
+        }
+    }
+
+    fn get_enemy_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        if current != goal && goal != sender {
+            self.change_rate / (self.nr_parties - 1) as f64
+        } else {
+            0.0
+        }
+    }
+
+    fn describe(&self) {
+        println!("Signed voter process with {} parties, and change rate {}.",
+                 self.nr_parties, self.change_rate)
+    }
+}
+
+impl Coloration for SignedVoter {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        party_color(state, self.nr_parties)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{Coloration, IPSRules};
+
+// 0: Susceptible, 1: Infected, 2: Removed (recovered). Parameters described in main.rs.
+// The SIR process with waning immunity: removed particles become susceptible again.
+pub struct SIRSProcess {
+    pub birth_rate: f64,
+    pub death_rate: f64,
+    pub waning_rate: f64,
+}
+
+impl IPSRules for SIRSProcess {
+    fn all_states(&self) -> Vec<usize> {
+        vec![0, 1, 2]
+    }
+
+    fn get_vacuum_mutation_rate(&self, current: usize, goal: usize) -> f64 {
+        match (current, goal) {
+            (1, 2) => { self.death_rate }
+            (2, 0) => { self.waning_rate }
+            _ => { 0.0 }
+        }
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        match (current, goal, sender) {
+            (0, 1, 1) => { self.birth_rate }
+            _ => { 0.0 }
+        }
+    }
+
+    fn describe(&self) {
+        println!("Susceptible-Infected-Removed-Susceptible process, with birth rate {}, death \
+        (removal) rate {} and waning rate {}", self.birth_rate, self.death_rate, self.waning_rate)
+    }
+}
+
+impl Coloration for SIRSProcess {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        match state {
+            0 => { [0, 0, 0, 255] }
+            1 => { [180, 12, 13, 255] }
+            2 => { [97, 97, 97, 255] }
+            _ => {
+                panic!("State not colored!")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn isolated_removed_particle_wanes() {
+        let rules = SIRSProcess { birth_rate: 1.0, death_rate: 0.5, waning_rate: 0.25 };
+
+        assert_eq!(rules.get_mutation_rate(2, 0, &HashMap::new()), 0.25);
+        assert_eq!(rules.get_reactivity(2, &HashMap::new()), 0.25);
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::IPSRules;
+
+/// Function giving the rules in effect at a particular (simulated) time.
+pub type RulesSchedule = Box<dyn Fn(f64) -> Box<dyn IPSRules>>;
+
+/// Wrapper for rules whose rates change over (simulated) time, e.g., to model a lockdown which
+/// lowers the infection rate from some time on. The rates are piecewise constant: the rules in
+/// effect are given by `schedule`, which is only consulted at the start and at the times
+/// `change_times`.
+///
+/// Changing the rates invalidates the reactivities of all particles, so at every change time the
+/// solver recomputes them all, which takes as long as its initialization. Keep the number of
+/// change times small compared to the number of steps. The change times are in the units of the
+/// simulated time, i.e., scaled by `SolverOptions::time_scale` if given.
+///
+/// All rules given by `schedule` should have the same states and the same additivity (see
+/// `IPSRules::is_neighbor_additive`).
+pub struct TimeDependentRules {
+    schedule: RulesSchedule,
+    change_times: Vec<f64>,
+    current: Box<dyn IPSRules>,
+}
+
+impl IPSRules for TimeDependentRules {
+    fn all_states(&self) -> Vec<usize> {
+        self.current.all_states()
+    }
+
+    fn get_vacuum_mutation_rate(&self, current: usize, goal: usize) -> f64 {
+        self.current.get_vacuum_mutation_rate(current, goal)
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        self.current.get_neighbor_mutation_rate(current, goal, sender)
+    }
+
+    fn get_enemy_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        self.current.get_enemy_mutation_rate(current, goal, sender)
+    }
+
+    fn get_mutation_rate(&self, current: usize, goal: usize, neighbor_counts: &HashMap<usize, usize>) -> f64 {
+        self.current.get_mutation_rate(current, goal, neighbor_counts)
+    }
+
+    fn is_neighbor_additive(&self) -> bool {
+        self.current.is_neighbor_additive()
+    }
+
+    fn next_rate_change(&self, time: f64) -> Option<f64> {
+        self.change_times.iter().copied().find(|&change_time| change_time > time)
+    }
+
+    fn set_time(&mut self, time: f64) {
+        let rules = (self.schedule)(time);
+        assert_eq!(rules.all_states(), self.current.all_states(), "The states of the rules changed!");
+        self.current = rules;
+    }
+
+    fn describe(&self) {
+        print!("Time-dependent rules, changing at times {:?}, starting as: ", self.change_times);
+        self.current.describe()
+    }
+}
+
+impl TimeDependentRules {
+    /// Rules given by `schedule` at each time, which change at the times `change_times`.
+    /// # Example
+    /// An SI process where a lockdown at time 10 halves the infection rate
+    /// ```
+    /// let rules = TimeDependentRules::new(Box::new(|time| {
+    ///     let birth_rate = if time < 10.0 { 2.0 } else { 1.0 };
+    ///     Box::new(SIProcess { birth_rate, death_rate: 1.0, spontaneous_rate: 0.0 })
+    /// }), vec![10.0]);
+    /// ```
+    pub fn new(schedule: RulesSchedule, mut change_times: Vec<f64>) -> TimeDependentRules {
+        change_times.sort_by(f64::total_cmp);
+        let current = schedule(0.0);
+
+        TimeDependentRules {
+            schedule,
+            change_times,
+            current,
+        }
+    }
+
+    /// All rates of `rules` multiplied by the factor `scale(time)`, which changes at the times
+    /// `change_times`. The factor has to be nonnegative.
+    pub fn scaled(rules: Box<dyn IPSRules>, scale: impl Fn(f64) -> f64 + 'static, change_times: Vec<f64>) -> TimeDependentRules {
+        let rules: Rc<dyn IPSRules> = Rc::from(rules);
+
+        TimeDependentRules::new(Box::new(move |time| {
+            let factor = scale(time);
+            assert!(factor >= 0.0, "Negative rate factor {} at time {}.", factor, time);
+            Box::new(ScaledRules { rules: rules.clone(), factor })
+        }), change_times)
+    }
+}
+
+/// All rates of `rules` multiplied by `factor`.
+struct ScaledRules {
+    rules: Rc<dyn IPSRules>,
+    factor: f64,
+}
+
+impl IPSRules for ScaledRules {
+    fn all_states(&self) -> Vec<usize> {
+        self.rules.all_states()
+    }
+
+    fn get_vacuum_mutation_rate(&self, current: usize, goal: usize) -> f64 {
+        self.factor * self.rules.get_vacuum_mutation_rate(current, goal)
+    }
+
+    fn get_neighbor_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        self.factor * self.rules.get_neighbor_mutation_rate(current, goal, sender)
+    }
+
+    fn get_enemy_mutation_rate(&self, current: usize, goal: usize, sender: usize) -> f64 {
+        self.factor * self.rules.get_enemy_mutation_rate(current, goal, sender)
+    }
+
+    fn get_mutation_rate(&self, current: usize, goal: usize, neighbor_counts: &HashMap<usize, usize>) -> f64 {
+        self.factor * self.rules.get_mutation_rate(current, goal, neighbor_counts)
+    }
+
+    fn is_neighbor_additive(&self) -> bool {
+        self.rules.is_neighbor_additive()
+    }
+
+    fn describe(&self) {
+        print!("rates multiplied by {} of ", self.factor);
+        self.rules.describe()
+    }
+}
@@ -33,45 +33,51 @@ impl IPSRules for VoterProcess {
 
 impl Coloration for VoterProcess {
     fn get_color(&self, state: usize) -> [u8; 4] {
-        if self.nr_parties <= 10 { // From matplotlib tableau palette
-            match state {
-                0 => {
-                    [4, 88, 147, 255] // blue
-                }
-                1 => {
-                    [219, 97, 0, 255] // orange
-                }
-                2 => {
-                    [16, 128, 16, 255] // green
-                }
-                3 => {
-                    [180, 12, 13, 255] // red
-                }
-                4 => {
-                    [116, 74, 156, 255] // purple
-                }
-                5 => {
-                    [109, 57, 46, 255] // brown
-                }
-                6 => {
-                    [193, 88, 160, 255] // pink
-                }
-                7 => {
-                    [97, 97, 97, 255] // gray
-                }
-                8 => {
-                    [154, 156, 7, 255] // olive
-                }
-                9 => {
-                    [0, 157, 174, 255] // cyan
-                }
-                _ => {
-                    [255, 255, 255, 255] // white
-                }
+        party_color(state, self.nr_parties)
+    }
+}
+
+/// Color of the party `state` out of `nr_parties` parties. Shared by the processes where the states
+/// are parties (voter-like processes).
+pub fn party_color(state: usize, nr_parties: usize) -> [u8; 4] {
+    if nr_parties <= 10 { // From matplotlib tableau palette
+        match state {
+            0 => {
+                [4, 88, 147, 255] // blue
+            }
+            1 => {
+                [219, 97, 0, 255] // orange
+            }
+            2 => {
+                [16, 128, 16, 255] // green
+            }
+            3 => {
+                [180, 12, 13, 255] // red
+            }
+            4 => {
+                [116, 74, 156, 255] // purple
+            }
+            5 => {
+                [109, 57, 46, 255] // brown
+            }
+            6 => {
+                [193, 88, 160, 255] // pink
+            }
+            7 => {
+                [97, 97, 97, 255] // gray
+            }
+            8 => {
+                [154, 156, 7, 255] // olive
+            }
+            9 => {
+                [0, 157, 174, 255] // cyan
+            }
+            _ => {
+                [255, 255, 255, 255] // white
             }
-        } else {
-            let brightness = (255.0 * state as f64 / self.nr_parties as f64).floor() as u8;
-            [brightness, brightness, brightness, 255]
         }
+    } else {
+        let brightness = (255.0 * state as f64 / nr_parties as f64).floor() as u8;
+        [brightness, brightness, brightness, 255]
     }
 }
\ No newline at end of file
@@ -0,0 +1,182 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use crate::solver::{count_states, HaltCondition, RecordCondition, Solution};
+use crate::solver::exponential_distribution::StandardExponential;
+
+/// Simulator for the voter process (see `VoterProcess`) on the complete graph with `nr_points`
+/// points. On the complete graph every particle is a neighbor of every other particle, so the
+/// dynamics only depend on the number of particles in each party: a particle of party `a` joins
+/// party `b` at rate `change_rate` times the number of particles of party `b`. This solver only
+/// keeps track of these counts, and takes `O(nr_parties)` time per step instead of the
+/// `O(nr_points)` neighbor updates of `particle_system_solver` on a `DenseGraph`.
+///
+/// The particles are exchangeable, so the snapshots only carry information through their counts.
+/// They are given as the configuration where the particles are sorted by party: first all
+/// particles of party 0, then all of party 1, etc.
+///
+/// # Parameters
+/// * `nr_points`: The number of particles, i.e., the number of points of the complete graph.
+/// * `nr_parties`: The number of parties.
+/// * `change_rate`: The rate at which a particle is convinced by another particle of a different
+///   party.
+/// * `initial_counts`: The number of particles in each party at the start. Has to have length
+///   `nr_parties` and sum to `nr_points`.
+/// * `halting_condition`: As in `particle_system_solver`.
+/// * `record_condition`: As in `particle_system_solver`.
+/// * `rng`: Random number generator. Most likely you want to input `rand::thread_rng()`, or a
+///   seeded generator for a reproducible simulation.
+///
+/// # Outputs
+/// A `Solution` in the same format as that of `particle_system_solver`, with sorted snapshots as
+/// described above, and without reactivity trace and events.
+pub fn mean_field_voter_solver<R: Rng>(
+    nr_points: usize,
+    nr_parties: usize,
+    change_rate: f64,
+    initial_counts: Vec<usize>,
+    halting_condition: HaltCondition,
+    record_condition: RecordCondition,
+    mut rng: R,
+) -> Solution {
+    // * PHASE I: Initialization * //
+
+    let mut counts: Vec<usize> = initial_counts;
+    assert_eq!(counts.len(), nr_parties);
+    assert_eq!(counts.iter().sum::<usize>(), nr_points);
+
+    // Rate at which some particle of party a changes its party: each of the counts[a] particles is
+    // convinced by the nr_points - counts[a] particles of other parties.
+    let party_reactivity = |counts: &Vec<usize>, a: usize| -> f64 {
+        change_rate * (counts[a] * (nr_points - counts[a])) as f64
+    };
+
+    // Initialize state record
+    let mut states_record: Vec<usize> = vec![];
+    let mut times: Vec<f64> = vec![];
+
+    // Initialize timekeeping
+    let mut time_passed = 0.0;
+    let mut time_last_recorded = 0.0;
+    let mut time_last_infection = 0.0;
+    let mut steps_recorded = 0; // Number of snapshots in states_record
+    let mut steps_taken = 0;
+
+    // * PHASE 2: Simulation loop * //
+    while halting_condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_infection) {
+        let reactivities: Vec<f64> = (0..nr_parties).map(|a| party_reactivity(&counts, a)).collect();
+        let total_reactivity: f64 = reactivities.iter().sum();
+
+        // Consensus is absorbing
+        if total_reactivity <= 0.0 {
+            break;
+        }
+
+        /* Update timekeeping */
+        steps_taken += 1;
+
+        // Generate time step (until next event)
+        let time_step: f64 = {
+            let standard_exp_object: StandardExponential = rng.gen();
+            standard_exp_object.0 / total_reactivity
+        };
+        let mean_reactivity = total_reactivity / nr_points as f64;
+
+        time_passed += time_step;
+
+        /* Record the state */
+        // As in `particle_system_solver`, the state before the update is recorded
+        for _ in 0..record_condition.how_often_record(time_passed, time_step, steps_taken,
+                                                      time_last_recorded, mean_reactivity) {
+            states_record.extend(sorted_configuration(&counts));
+            times.push(time_passed);
+            time_last_recorded = time_passed;
+            steps_recorded += 1;
+            if !halting_condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_infection) {
+                break;
+            }
+        }
+
+        /* Find the party that loses a particle, and the party that gains it */
+        // Given the party `from` of the changing particle, it joins party `to` with probability
+        // proportional to the number of particles in `to`.
+        let from = WeightedIndex::new(&reactivities).unwrap().sample(&mut rng);
+        let to = WeightedIndex::new(
+            (0..nr_parties).map(|b| if b == from { 0 } else { counts[b] })
+        ).unwrap().sample(&mut rng);
+
+        counts[from] -= 1;
+        counts[to] += 1;
+        if from == 0 {
+            time_last_infection = time_passed;
+        }
+    }
+
+    // * PHASE III: Cleanup * //
+
+    // Record final state
+    let final_state = sorted_configuration(&counts);
+    states_record.extend_from_slice(&final_state);
+    times.push(time_passed);
+    steps_recorded += 1;
+
+    Solution {
+        snapshots: states_record,
+        times,
+        recorded_indices: None,
+        final_state_counts: count_states(&final_state),
+        final_state,
+        time_simulated: time_passed,
+        steps_recorded,
+        steps_taken,
+        reactivity_trace: None,
+        events: vec![],
+    }
+}
+
+/// The configuration with the given number of particles in each party, sorted by party.
+fn sorted_configuration(counts: &[usize]) -> Vec<usize> {
+    counts.iter().enumerate()
+        .flat_map(|(party, &count)| std::iter::repeat_n(party, count))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::solver::{particle_system_solver, SolverOptions};
+    use crate::solver::graph::dense::DenseGraph;
+    use crate::solver::ips_rules::voter_process::VoterProcess;
+    use super::*;
+
+    #[test]
+    fn consensus_times_match_general_solver() {
+        let nr_runs = 1000;
+        let complete_graph = || DenseGraph::from_matrix((0..8).map(|i| (0..8).map(|j| i != j).collect()).collect());
+
+        let mut mean_field_total = 0.0;
+        let mut general_total = 0.0;
+        for run in 0..nr_runs {
+            let mean_field = mean_field_voter_solver(8, 2, 1.0, vec![4, 4], HaltCondition::TimePassed(1000.0),
+                                                     RecordCondition::Final(), StdRng::seed_from_u64(run));
+            let general = particle_system_solver(
+                Box::new(VoterProcess { nr_parties: 2, change_rate: 1.0 }),
+                Box::new(complete_graph()),
+                vec![0, 0, 0, 0, 1, 1, 1, 1],
+                HaltCondition::TimePassed(1000.0),
+                RecordCondition::Final(),
+                SolverOptions::default(),
+                StdRng::seed_from_u64(run),
+            );
+            assert_eq!(mean_field.final_state_counts.len(), 1);
+            assert_eq!(general.final_state_counts.len(), 1);
+            mean_field_total += mean_field.time_simulated;
+            general_total += general.time_simulated;
+        }
+
+        let mean_field_mean = mean_field_total / nr_runs as f64;
+        let general_mean = general_total / nr_runs as f64;
+        assert!((mean_field_mean / general_mean - 1.0).abs() < 0.1, "{} versus {}", mean_field_mean, general_mean);
+    }
+}
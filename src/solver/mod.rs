@@ -1,81 +1,380 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
+#[cfg(feature = "serde")]
+use std::fs::File;
+#[cfg(feature = "serde")]
+use std::io::{self, BufReader, BufWriter};
 
 use rand::distributions::{Distribution, WeightedError, WeightedIndex};
 use rand::Rng;
-use rand::rngs::ThreadRng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+use crate::solver::assemble_initial_condition::validate_initial_condition;
 use crate::solver::exponential_distribution::StandardExponential;
 use crate::solver::graph::Graph;
+use crate::solver::graph::signed_graph::EdgeSign;
 use crate::solver::ips_rules::IPSRules;
 
 pub mod ips_rules;
 pub mod graph;
 pub mod assemble_initial_condition;
+pub mod exclusion;
+pub mod tau_leap;
+pub mod ensemble;
+#[cfg(feature = "serde")]
+pub mod sweep;
+pub mod analysis;
+pub mod axelrod;
+pub mod mean_field;
+pub mod synchronous;
 
 mod exponential_distribution;
 
 /// Enum to be passed into `particle_system_solver` that determines the simulation halting
 /// condition. Implements `HaltCondition::should_continue`.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum HaltCondition {
     /// Stop the simulation after a certain amount of time has passed. Physical in
     /// the sense that an experiment took this amount of time.
     TimePassed(f64),
-    /// Stop the simulation after a certain amount of steps have been recorded.
+    /// Stop the simulation after a certain amount of steps have been recorded. The final state is
+    /// always recorded, so the output then contains exactly this many snapshots (at least one).
     /// Useful for discrete-time particle systems.
     StepsRecorded(u64),
     /// Stop the simulation after a certain amount of steps have been taken.
     /// Useful for discrete-time particle systems.
     StepsTaken(u64),
+    /// Stop the simulation when no new infection has occurred for a certain amount of time, where
+    /// a new infection is a particle leaving the default state 0 (e.g., a birth in the contact
+    /// process). Detects effective extinction of a dying epidemic before full absorption.
+    QuietPeriod(f64),
+    /// Stop the simulation as soon as any of the conditions would stop it, e.g., after a certain
+    /// amount of time or a maximum number of steps, whichever comes first. Continues forever if
+    /// empty.
+    Any(Vec<HaltCondition>),
+    /// Stop the simulation only once all of the conditions would stop it, e.g., after at least a
+    /// certain amount of time and at least a minimum number of steps. Every condition keeps its
+    /// own semantics, e.g., `StepsTaken(n)` is met after `n + 1` steps. Stops immediately if
+    /// empty.
+    All(Vec<HaltCondition>),
 }
 
 impl HaltCondition {
     /// Given the halting condition `self`, should the simulation continue given all the parameters
-    /// of the current state of the simulation?
-    pub fn should_continue(&self, time_passed: f64, steps_recorded: u64, steps_taken: u64) -> bool {
+    /// of the current state of the simulation? `time_last_infection` is the time at which a
+    /// particle last left the state 0 (or 0.0 if that has not happened yet).
+    pub fn should_continue(&self, time_passed: f64, steps_recorded: u64, steps_taken: u64,
+                           time_last_infection: f64) -> bool {
         return match self {
             HaltCondition::TimePassed(limit) => {
                 time_passed < *limit
             }
             HaltCondition::StepsRecorded(limit) => {
-                steps_recorded < *limit
+                // Reserve one snapshot for the final state, which is recorded after halting
+                steps_recorded + 1 < *limit
             }
             HaltCondition::StepsTaken(limit) => {
                 steps_taken <= *limit
             }
+            HaltCondition::QuietPeriod(window) => {
+                time_passed - time_last_infection <= *window
+            }
+            HaltCondition::Any(conditions) => {
+                // `all` stops at the first condition that doesn't continue
+                conditions.iter().all(|condition| {
+                    condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_infection)
+                })
+            }
+            HaltCondition::All(conditions) => {
+                conditions.iter().any(|condition| {
+                    condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_infection)
+                })
+            }
         };
     }
 }
 
 /// Enum to be passed into `particle_system_solver` that determines the recording condition.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RecordCondition {
     /// Record the state after a constant amount of time has passed.
     ConstantTime(f64),
+    /// Record the state after a constant amount of time has passed, but skip the copies of a
+    /// snapshot within a single step. When a single step spans multiple intervals, the state is
+    /// recorded only once instead of once per interval. Consecutive snapshots can still be
+    /// identical across steps which don't change the state, i.e., the rate changes of
+    /// time-dependent rules. The output is therefore shorter than for `ConstantTime`, and its
+    /// snapshots are no longer evenly spaced in time: each snapshot stands for at least the given
+    /// amount of time.
+    ConstantTimeDeduplicated(f64),
     /// Record the state every nth step. Useful for discrete-time particle systems.
     EveryNthStep(usize),
     /// Only record the final state.
     Final(),
+    /// Record the state at an interval between `min_interval` and `max_interval` which adapts to
+    /// how active the system is: when the mean reactivity per particle is `rho`, the state is
+    /// recorded once `min_interval + (max_interval - min_interval) * exp(-rho)` time has passed
+    /// since the previous recording. Records densely when a lot happens and sparsely when the
+    /// system is quiescent. At most one snapshot is recorded per step.
+    AdaptiveRate { min_interval: f64, max_interval: f64 },
+    /// Keep a uniformly random sample of the states after the steps, of at most the given number
+    /// of snapshots (at least 1) including the final state, by reservoir sampling. The memory use
+    /// is then bounded however long the simulation runs. The snapshots are output in chronological
+    /// order, with their times in `Solution::times`. Only `particle_system_solver` samples, the
+    /// other solvers record every step.
+    ReservoirSample(usize),
 }
 
 impl RecordCondition {
     /// Given the record condition `self`, how often should the previous state be recorded?
-    /// Called at the end of every step.
-    pub fn how_often_record(&self, time_passed: f64, time_step: f64, steps_taken: u64) -> usize {
+    /// Called at the end of every step. The parameters `time_last_recorded` and `mean_reactivity`
+    /// (the total reactivity during the step divided by the number of particles) are only used by
+    /// the adaptive record condition.
+    pub fn how_often_record(&self, time_passed: f64, time_step: f64, steps_taken: u64,
+                            time_last_recorded: f64, mean_reactivity: f64) -> usize {
         match self {
             RecordCondition::ConstantTime(time_interval) => {
                 ((time_passed / time_interval).floor() - ((time_passed - time_step) / time_interval).floor())
                     as usize
             }
+            RecordCondition::ConstantTimeDeduplicated(time_interval) => {
+                (RecordCondition::ConstantTime(*time_interval)
+                    .how_often_record(time_passed, time_step, steps_taken, time_last_recorded, mean_reactivity)
+                    > 0) as usize
+            }
             RecordCondition::EveryNthStep(n) => {
                 ((steps_taken as usize) % n == 0) as usize
             }
             RecordCondition::Final() => { 0 }
+            RecordCondition::AdaptiveRate { min_interval, max_interval } => {
+                let interval = min_interval + (max_interval - min_interval) * (-mean_reactivity).exp();
+                (time_passed - time_last_recorded >= interval) as usize
+            }
+            RecordCondition::ReservoirSample(_) => { 1 } // every step is a candidate
+        }
+    }
+
+    /// An upper bound on the number of snapshots (including the final state) recorded under the
+    /// record condition `self` and the halting condition `halting_condition`, if it is known
+    /// before simulating. `None` if it depends on the course of the simulation.
+    pub fn projected_nr_snapshots(&self, halting_condition: &HaltCondition) -> Option<u64> {
+        match (self, halting_condition) {
+            (RecordCondition::Final(), _) => Some(1),
+            (RecordCondition::ReservoirSample(size), _) => Some(*size as u64),
+            // Bounded by the bound of any of the conditions, so by the smallest one
+            (_, HaltCondition::Any(conditions)) => {
+                conditions.iter().filter_map(|condition| self.projected_nr_snapshots(condition)).min()
+            }
+            // Every condition can keep the simulation going past the bound of the others
+            (_, HaltCondition::All(_)) => None,
+            (_, HaltCondition::StepsRecorded(limit)) => Some((*limit).max(1)),
+            (RecordCondition::ConstantTime(time_interval)
+            | RecordCondition::ConstantTimeDeduplicated(time_interval), HaltCondition::TimePassed(limit)) => {
+                Some((limit / time_interval).ceil() as u64 + 1)
+            }
+            // The simulation takes `limit + 1` steps
+            (RecordCondition::EveryNthStep(n), HaltCondition::StepsTaken(limit)) => {
+                Some((limit + 1) / *n as u64 + 1)
+            }
+            _ => None,
         }
     }
 }
 
+/// Error raised when the snapshots of a simulation would take more than `max_bytes` bytes of
+/// memory (see `SolverOptions::max_record_bytes`). `projected_bytes` is the size of the snapshots
+/// the simulation was projected to record, or has recorded when it was stopped.
+#[derive(Debug, PartialEq)]
+pub struct RecordTooLarge {
+    pub projected_bytes: usize,
+    pub max_bytes: usize,
+}
+
+impl std::fmt::Display for RecordTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The snapshots take {} bytes, more than the allowed {} bytes!", self.projected_bytes, self.max_bytes)
+    }
+}
+
+impl std::error::Error for RecordTooLarge {}
+
+/// Check whether recording the snapshots of `snapshot_length` particles under the given halting
+/// and record conditions stays within `max_bytes` bytes, before simulating. The projected size is
+/// the number of snapshots (see `RecordCondition::projected_nr_snapshots`) times
+/// `snapshot_length` times the size of a state.
+///
+/// # Errors
+/// Returns the projected size if it exceeds `max_bytes`. If the number of snapshots is not known
+/// in advance, the check passes.
+pub fn check_record_size(halting_condition: &HaltCondition, record_condition: &RecordCondition,
+                         snapshot_length: usize, max_bytes: usize) -> Result<(), RecordTooLarge> {
+    let projected_bytes = match record_condition.projected_nr_snapshots(halting_condition) {
+        Some(nr_snapshots) => (nr_snapshots as usize)
+            .saturating_mul(snapshot_length)
+            .saturating_mul(size_of::<usize>()),
+        None => return Ok(()),
+    };
+
+    if projected_bytes > max_bytes {
+        Err(RecordTooLarge { projected_bytes, max_bytes })
+    } else {
+        Ok(())
+    }
+}
+
+/// Callback of `SolverOptions::on_record`, called with the time and the current state.
+pub type RecordCallback<'a> = &'a mut dyn FnMut(f64, &[usize]);
+
+/// Optional extra output of `particle_system_solver`. The default records nothing extra.
+#[derive(Default)]
+pub struct SolverOptions<'a> {
+    /// Record the total reactivity at the time of each snapshot.
+    pub record_reactivity_trace: bool,
+    /// Record every transition as an `Event`.
+    pub record_events: bool,
+    /// Only record the particles with these indices (in this order) in the snapshots, e.g., a
+    /// window of interest in a large grid. Records all particles if `None`.
+    pub record_region: Option<Vec<usize>>,
+    /// Multiply all rates of each particle by a factor, to make some particles (e.g.,
+    /// super-spreaders) more reactive than others. Indexed by particle. All factors are 1 if
+    /// `None`.
+    pub rate_multipliers: Option<Vec<f64>>,
+    /// Particles which never change state (e.g., zealots in the voter process), but still influence
+    /// their neighbors. Their reactivity is zero, so that they are never selected.
+    pub frozen: HashSet<usize>,
+    /// Particles which are removed from the particle system (e.g., vaccinated sites in the contact
+    /// process): they never change state, and unlike frozen particles they don't influence their
+    /// neighbors either, as if they were not in the graph. Their state in the snapshots is that of
+    /// the initial condition.
+    pub immunized: HashSet<usize>,
+    /// Multiply every time step by this factor, to express the simulated time in other (e.g.,
+    /// physical) units. The halting and record conditions and the recorded times are then in these
+    /// units, but the rates (e.g., in the reactivity trace) are not rescaled. The factor is 1 if
+    /// `None`.
+    pub time_scale: Option<f64>,
+    /// Only start recording once this much time has passed, to discard the transient from the
+    /// initial condition (e.g., for measurements in the stationary state). The simulation itself
+    /// starts from the initial condition as usual, and the final state is always recorded. The
+    /// default 0 records from the start.
+    pub burn_in_time: f64,
+    /// Called with the time and the current state (of all particles) whenever a snapshot is
+    /// recorded, including the final state. For analysis on the fly: combined with an empty
+    /// `record_region` the snapshots are empty, so that the memory use doesn't grow with the
+    /// number of snapshots.
+    pub on_record: Option<RecordCallback<'a>>,
+    /// Sample the state to which a particle transitions only among the states it can transition
+    /// to (those with nonzero rate), instead of among all states. Much faster for rules with many
+    /// states of which only a few are reachable at a time (e.g., the voter process with many
+    /// parties). Gives the same transitions for the same random number generator.
+    pub sparse_to_state: bool,
+    /// Refuse to record snapshots taking more than this many bytes, to not run out of memory by
+    /// accident. Stops before simulating if the projected size of the snapshots (see
+    /// `check_record_size`) is too large, and otherwise as soon as the recorded snapshots outgrow
+    /// the limit, with an error from `particle_system_solver_checked` (the other solvers panic).
+    /// No limit if `None`.
+    pub max_record_bytes: Option<usize>,
+}
+
+impl Debug for SolverOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SolverOptions")
+            .field("record_reactivity_trace", &self.record_reactivity_trace)
+            .field("record_events", &self.record_events)
+            .field("record_region", &self.record_region)
+            .field("rate_multipliers", &self.rate_multipliers)
+            .field("frozen", &self.frozen)
+            .field("immunized", &self.immunized)
+            .field("time_scale", &self.time_scale)
+            .field("burn_in_time", &self.burn_in_time)
+            .field("on_record", &self.on_record.is_some())
+            .field("sparse_to_state", &self.sparse_to_state)
+            .field("max_record_bytes", &self.max_record_bytes)
+            .finish()
+    }
+}
+
+/// A single transition of the particle system: at time `time`, the particle at `site` changed from
+/// `old_state` to `new_state`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Event {
+    pub time: f64,
+    pub site: usize,
+    pub old_state: usize,
+    pub new_state: usize,
+}
+
+/// Output of `particle_system_solver`: a record of how the particle system developed, along with
+/// some statistics of the simulation. With the `serde` feature, it can be saved to and loaded from
+/// JSON, to separate the simulation from the visualization.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Solution {
+    /// Snapshots of the particle system at different times. If `n` steps have been recorded of a
+    /// system with `x` particles, the length of this vector is `nx`. The `i`th snapshot
+    /// (`0 <= i < n`) can be found at indices `ix` to `(i+1)x-1`. If only a region was recorded,
+    /// `x` is the number of particles in the region instead.
+    pub snapshots: Vec<usize>,
+    /// The simulated time at which each snapshot was recorded, in order (the final state is
+    /// recorded at the end of the simulation).
+    pub times: Vec<f64>,
+    /// The indices of the particles recorded in the snapshots, in order, if only a region was
+    /// recorded. `None` if all particles were recorded.
+    pub recorded_indices: Option<Vec<usize>>,
+    /// The final state of all particles, also if only a region was recorded. The `i`th entry is the
+    /// state of particle `i`.
+    pub final_state: Vec<usize>,
+    /// The number of particles in each state in the final state. States without particles are
+    /// left out, so the length is the number of distinct states that remain.
+    pub final_state_counts: HashMap<usize, usize>,
+    /// The total simulated time.
+    pub time_simulated: f64,
+    /// The total number of steps recorded, i.e., the number of snapshots.
+    pub steps_recorded: u64,
+    /// The total number of steps simulated, including the changes of the rates.
+    pub steps_taken: u64,
+    /// If requested, the total reactivity at the time of each snapshot, as `(time, total_reactivity)`
+    /// pairs. A total reactivity declining to zero signals the approach to an absorbing state.
+    pub reactivity_trace: Option<Vec<(f64, f64)>>,
+    /// If requested, every transition in the order they happened. Replaying them on the initial
+    /// condition gives the final state. There is one event per step taken, except for the steps at
+    /// which the rates change (see `IPSRules::next_rate_change`), where no particle transitions.
+    /// Empty if not requested.
+    pub events: Vec<Event>,
+}
+
+impl Solution {
+    /// An estimate of the memory taken by the solution in bytes, i.e., of the snapshots, times,
+    /// final state, reactivity trace and events. Dominated by the snapshots for most simulations.
+    pub fn estimated_bytes(&self) -> usize {
+        size_of::<usize>() * (self.snapshots.len() + self.final_state.len()
+            + self.recorded_indices.as_ref().map_or(0, |indices| indices.len())
+            + 2 * self.final_state_counts.len())
+            + size_of::<f64>() * self.times.len()
+            + size_of::<(f64, f64)>() * self.reactivity_trace.as_ref().map_or(0, |trace| trace.len())
+            + size_of::<Event>() * self.events.len()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Solution {
+    /// Save the solution as JSON to the file `path`.
+    pub fn save_json(&self, path: &str) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    /// Load a solution from the JSON file `path`, as saved by `save_json`.
+    pub fn load_json(path: &str) -> io::Result<Solution> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
 /// Interacting particle system simulator. The inputs define a particular particle system, the
 /// output is a record of how that particular particle system might develop (note that this is
 /// nondeterministic).
@@ -83,7 +382,9 @@ impl RecordCondition {
 /// # Parameters
 /// * `ips_rules`: Defines the evolution rules of the interaction particle system.
 /// * `graph`: Graph which defines neighboring states (e.g., line, circle, torus, GridND). Has to
-/// implement `Graph` trait.
+///   implement `Graph` trait. On a weighted (e.g., `WeightedAdjacency`) or signed (`SignedGraph`)
+///   graph the contribution of a neighbor depends on its edge, which needs additive rules: weights
+///   multiply it, and for negative edges the rules give the contribution of enemies.
 /// * `initial_condition`: Vector containing the initial states of the particles. States are
 /// represented by integers. If applicable, 0 is the default state.
 /// * `halting_condition`: HaltCondition enum which determines under what conditions the simulation
@@ -91,17 +392,18 @@ impl RecordCondition {
 /// * `record_condition`: RecordCondition enum which determines under what conditions the state
 /// of the simulation is recorded into the output (e.g., record every step, record every 1.0 time
 /// unit).
-/// * `rng`: ThreadRng input. Most likely you want to input `rand::thread_rng()`.
+/// * `options`: SolverOptions struct which determines what is recorded besides the snapshots (e.g.,
+///   the total reactivity, every transition).
+/// * `rng`: Random number generator. Most likely you want to input `rand::thread_rng()`, or a
+///   seeded generator for a reproducible simulation.
 ///
 /// # Outputs
-/// A tuple consisting of
-/// * A vector which contains snapshots of the particle system at different times. If `n` steps have
-/// been recorded of a system with `x` particles, the length of the output vector is `nx`. The `i`th
-/// snapshot (`0 <= i <= n`) can be found at indices `ix` to `(i+1)x-1`.
-/// * A vector containing only the final state, in the format above.
-/// * The total simulated time (f64)
-/// * The total number of steps recorded (u64)
-/// * The total number of steps simulated (u64)
+/// A `Solution` containing the recorded snapshots, the final state, and statistics of the
+/// simulation.
+///
+/// # Panics
+/// If the snapshots take more memory than `SolverOptions::max_record_bytes`. Use
+/// `particle_system_solver_checked` to get an error instead.
 ///
 /// # Example
 /// Simulate the two voter process for 100.0 time units on a 40x40 toroidal grid, with random
@@ -131,20 +433,87 @@ impl RecordCondition {
 ///     initial_condition,
 ///     HaltCondition::TimePassed(100.0),
 ///     RecordCondition::ConstantTime(0.1),
+///     SolverOptions::default(),
 ///     rand::thread_rng(),
 /// );
 ///
 /// // put the output into a pretty gif
-/// save_as_gif(solution, "voter_process.gif", 40, 40, 20)
+/// save_as_gif(coloration, solution.snapshots, "voter_process.gif", [40, 40], 1600, 20, false, None).unwrap();
 /// ```
-pub fn particle_system_solver(
+pub fn particle_system_solver<R: Rng>(
     ips_rules: Box<dyn IPSRules>,
     graph: Box<dyn Graph>,
     initial_condition: Vec<usize>,
     halting_condition: HaltCondition,
     record_condition: RecordCondition,
-    mut rng: ThreadRng,
-) -> (Vec<usize>, Vec<usize>, f64, u64, u64) {
+    options: SolverOptions,
+    rng: R,
+) -> Solution {
+    particle_system_solver_checked(ips_rules, graph, initial_condition, halting_condition, record_condition, options, rng)
+        .expect("The snapshots take more memory than allowed!")
+}
+
+/// `particle_system_solver`, but returns an error instead of panicking if the snapshots take more
+/// memory than `SolverOptions::max_record_bytes`.
+///
+/// # Errors
+/// Returns the projected size of the snapshots before simulating, if it is known and too large.
+/// Otherwise returns the size of the recorded snapshots as soon as it is too large, which discards
+/// the simulation.
+pub fn particle_system_solver_checked<R: Rng>(
+    ips_rules: Box<dyn IPSRules>,
+    graph: Box<dyn Graph>,
+    initial_condition: Vec<usize>,
+    halting_condition: HaltCondition,
+    record_condition: RecordCondition,
+    options: SolverOptions,
+    rng: R,
+) -> Result<Solution, RecordTooLarge> {
+    solve_from(ips_rules, graph, initial_condition, 0.0, halting_condition, record_condition, options, rng)
+}
+
+/// Continue a simulation of `particle_system_solver` from the final state of a previous run, e.g.,
+/// to simulate a long run in chunks and save the output between them. The parameters are as in
+/// `particle_system_solver`, except:
+/// * `previous_final_state`: The final state of the previous run, i.e., its `final_state`, which
+///   has all particles even if the previous run recorded only a region.
+/// * `elapsed_time`: The time simulated so far, e.g., the `time_simulated` of the previous run.
+///   The simulation continues from this time: the halting and record conditions and the recorded
+///   times are in the total simulated time, so `TimePassed(100.0)` after 50.0 time units simulates
+///   50.0 more time units. The numbers of steps only count the steps of this run.
+///
+/// By the memorylessness of the exponential waiting times, the resumed run has the same
+/// distribution as the corresponding part of a single run.
+#[allow(clippy::too_many_arguments)]
+pub fn particle_system_solver_resume<R: Rng>(
+    mut ips_rules: Box<dyn IPSRules>,
+    graph: Box<dyn Graph>,
+    previous_final_state: Vec<usize>,
+    elapsed_time: f64,
+    halting_condition: HaltCondition,
+    record_condition: RecordCondition,
+    options: SolverOptions,
+    rng: R,
+) -> Solution {
+    assert!(elapsed_time >= 0.0);
+    ips_rules.set_time(elapsed_time);
+
+    solve_from(ips_rules, graph, previous_final_state, elapsed_time, halting_condition, record_condition, options, rng)
+        .expect("The snapshots take more memory than allowed!")
+}
+
+/// The simulation of `particle_system_solver`, starting at the time `start_time`.
+#[allow(clippy::too_many_arguments)]
+fn solve_from<R: Rng>(
+    mut ips_rules: Box<dyn IPSRules>,
+    graph: Box<dyn Graph>,
+    initial_condition: Vec<usize>,
+    start_time: f64,
+    halting_condition: HaltCondition,
+    record_condition: RecordCondition,
+    mut options: SolverOptions,
+    mut rng: R,
+) -> Result<Solution, RecordTooLarge> {
     // * PHASE I: Initialization * //
 
     // Initialize state & reactivity vectors
@@ -152,25 +521,49 @@ pub fn particle_system_solver(
 
     // Check if enough information was given in the initial state
     assert_eq!(states.len(), graph.nr_points());
+    validate_initial_condition(ips_rules.as_ref(), &states)
+        .expect("The initial condition contains a state which is not a state of the particle system!");
+    if let Some(region) = &options.record_region {
+        assert!(region.iter().all(|&i| i < graph.nr_points()), "Recorded region is not in the graph!");
+    }
+    if let Some(multipliers) = &options.rate_multipliers {
+        assert_eq!(multipliers.len(), graph.nr_points());
+        assert!(multipliers.iter().all(|&multiplier| multiplier >= 0.0));
+    }
+    assert!(options.frozen.iter().all(|&i| i < graph.nr_points()), "Frozen particle is not in the graph!");
+    assert!(options.immunized.iter().all(|&i| i < graph.nr_points()), "Immunized particle is not in the graph!");
+    let snapshot_length = options.record_region.as_ref().map_or(graph.nr_points(), |region| region.len());
+    if let Some(max_bytes) = options.max_record_bytes {
+        check_record_size(&halting_condition, &record_condition, snapshot_length, max_bytes)?;
+    }
+    let time_scale = options.time_scale.unwrap_or(1.0);
+    assert!(time_scale > 0.0);
+    // Edge weights scale the contributions of the individual neighbors
+    assert!(!graph.is_weighted() || ips_rules.is_neighbor_additive(),
+            "Weighted graphs need rules which are additive in the neighbors!");
+    assert!(!graph.is_signed() || ips_rules.is_neighbor_additive(),
+            "Signed graphs need rules which are additive in the neighbors!");
+
+    // Factor by which all rates of a particle are multiplied. Doesn't affect to which state the
+    // particle transitions, only how often. Frozen and immunized particles never transition.
+    let multiplier = |i: usize| -> f64 {
+        if options.frozen.contains(&i) || options.immunized.contains(&i) {
+            0.0
+        } else {
+            options.rate_multipliers.as_ref().map_or(1.0, |multipliers| multipliers[i])
+        }
+    };
 
     // Compute initial reactivities
     let mut reactivities: Vec<f64> = Vec::with_capacity(graph.nr_points());
 
     for i in 0..graph.nr_points() { // Loop over all points
         // Count how many of which neighboring states this point i has, by looping over all neighbors
-        let mut neigh_counts: HashMap<usize, usize> = HashMap::new();
-
-        for j in graph.get_neighbors(i) {
-            let state_j = states.get(j).unwrap();
-            neigh_counts.insert(
-                state_j.clone(),
-                neigh_counts.get(state_j).unwrap_or(&0usize) + 1,
-            );
-        }
+        let neigh_states = NeighborStates::of(graph.as_ref(), &states, i, &options.immunized);
 
         // Pass these counts to the IPS rules object to find the rate
         reactivities.push(
-            ips_rules.get_reactivity(states[i].clone(), &neigh_counts)
+            multiplier(i) * neigh_states.reactivity(ips_rules.as_ref(), states[i])
         );
     }
 
@@ -178,11 +571,23 @@ pub fn particle_system_solver(
     let mut total_reactivity: f64 = reactivities.iter().sum();
     // Initialize state record
     let mut states_record: Vec<usize> = vec![];
+    let mut times: Vec<f64> = vec![];
+    let mut reactivity_trace: Vec<(f64, f64)> = vec![];
+    let mut events: Vec<Event> = vec![];
+    // Number of particles in each state, kept up to date at every transition
+    let mut state_counts: HashMap<usize, usize> = count_states(&states);
 
     // Initialize timekeeping
-    let mut time_passed = 0.0;
-    let mut steps_recorded = 1;
+    let mut time_passed = start_time;
+    let mut time_last_recorded = start_time;
+    let mut time_last_infection = start_time;
+    let mut steps_recorded = 0; // Number of snapshots in states_record
     let mut steps_taken = 0;
+    let mut nr_candidates = 0; // Number of steps offered to the reservoir, if sampling
+
+    if let RecordCondition::ReservoirSample(size) = record_condition {
+        assert!(size >= 1); // there has to be room for the final state
+    }
 
     // Initialize location-finding distribution
     let mut distr_location = match WeightedIndex::new(&reactivities) {
@@ -196,88 +601,208 @@ pub fn particle_system_solver(
     };
 
     // * PHASE 2: Simulation loop * //
-    while halting_condition.should_continue(time_passed, steps_recorded, steps_taken) {
+    while halting_condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_infection) {
         /* Update timekeeping */
         steps_taken += 1;
-        let prev_state = states.clone();
 
         // Generate time step (until next event)
-        let time_step: f64 = {
+        let mut time_step: f64 = {
             let standard_exp_object: StandardExponential = rng.gen();
-            standard_exp_object.0 / total_reactivity
+            time_scale * standard_exp_object.0 / total_reactivity
         };
+        // If the rates change before the next event, only step until the rates change. By
+        // memorylessness, the time until the next event can then be drawn anew with the new rates.
+        let rate_change: Option<f64> = ips_rules.next_rate_change(time_passed)
+            .filter(|&change_time| time_passed + time_step >= change_time);
+        if let Some(change_time) = rate_change {
+            time_step = change_time - time_passed;
+        }
+        // Keep the reactivity of the state before the update, for the adaptive record condition
+        let mean_reactivity = total_reactivity / graph.nr_points() as f64;
 
         time_passed += time_step;
 
+        /* Record the state */
+        // The state during the time step is the state before the update. By recording it before
+        // the update happens, we don't need to keep a copy of the previous state around.
+        let nr_records = if time_passed < options.burn_in_time {
+            0 // still in the burn-in period
+        } else {
+            record_condition.how_often_record(time_passed, time_step, steps_taken,
+                                              time_last_recorded, mean_reactivity)
+        };
+        for _ in 0..nr_records {
+            // When reservoir sampling, the reservoir has one snapshot less than the sample, since
+            // the final state is always recorded. Once it is full, the nth candidate replaces a
+            // uniformly random snapshot with probability (size - 1) / n, and is discarded otherwise.
+            let reservoir_slot: Option<usize> = match record_condition {
+                RecordCondition::ReservoirSample(size) => {
+                    nr_candidates += 1;
+                    if nr_candidates < size {
+                        None // the reservoir is not full yet, append
+                    } else {
+                        let slot = rng.gen_range(0..nr_candidates);
+                        if slot + 1 < size { Some(slot) } else { continue; }
+                    }
+                }
+                _ => None,
+            };
+
+            match reservoir_slot {
+                Some(slot) => {
+                    replace_snapshot(&mut states_record, slot, &states, &options.record_region);
+                    times[slot] = time_passed;
+                    if options.record_reactivity_trace {
+                        reactivity_trace[slot] = (time_passed, total_reactivity);
+                    }
+                }
+                None => {
+                    record_snapshot(&mut states_record, &states, &options.record_region);
+                    times.push(time_passed);
+                    if options.record_reactivity_trace {
+                        reactivity_trace.push((time_passed, total_reactivity));
+                    }
+                    steps_recorded += 1;
+                    if let Some(max_bytes) = options.max_record_bytes {
+                        // For conditions whose number of snapshots isn't known in advance
+                        let recorded_bytes = states_record.len() * size_of::<usize>();
+                        if recorded_bytes > max_bytes {
+                            return Err(RecordTooLarge { projected_bytes: recorded_bytes, max_bytes });
+                        }
+                    }
+                }
+            }
+            if let Some(on_record) = options.on_record.as_mut() {
+                on_record(time_passed, &states);
+            }
+            time_last_recorded = time_passed;
+            if !halting_condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_infection) { // we want to check the halting condition each step
+                break;
+            }
+        }
+
+        /* Change the rates */
+        // Changing the rates changes the reactivities of all particles, so recompute all of them.
+        // Counts as a step, without a transition.
+        if let Some(change_time) = rate_change {
+            time_passed = change_time; // no floating point error in the time of the change
+            ips_rules.set_time(change_time);
+            for i in 0..graph.nr_points() {
+                let neigh_states = NeighborStates::of(graph.as_ref(), &states, i, &options.immunized);
+                reactivities[i] = multiplier(i) * neigh_states.reactivity(ips_rules.as_ref(), states[i]);
+            }
+            total_reactivity = reactivities.iter().sum();
+            match WeightedIndex::new(&reactivities) {
+                Ok(distribution) => { distr_location = distribution; }
+                Err(WeightedError::AllWeightsZero) => {
+                    // No reaction is possible until the rates change again, if they do
+                    if ips_rules.next_rate_change(time_passed).is_none() { break; }
+                    total_reactivity = 0.0;
+                }
+                Err(e) => { panic!("Problem assembling location distribution: {:?}", e) }
+            }
+            continue;
+        }
+
         /* Find place where update occurs */
         // Sample the distribution
         let update_location = distr_location.sample(&mut rng);
 
         /* Find out to which state the selected particle transitions */
-        // Figure out neighbors and their states
-        let neighs: HashSet<usize> = graph.get_neighbors(update_location);
-        let mut neigh_state_counts: HashMap<usize, usize> = HashMap::new();
-
-        for j in &neighs {
-            let state_j = states.get(*j).unwrap();
-            neigh_state_counts.insert(
-                state_j.clone(),
-                neigh_state_counts.get(state_j).unwrap_or(&0usize) + 1,
-            );
-        }
+        // The points whose rates depend on the selected particle (the neighbors, unless the graph
+        // is directed)
+        let influenced: HashSet<usize> = graph.get_influenced(update_location);
+        // Figure out the states of the neighbors
+        let neigh_states = NeighborStates::of(graph.as_ref(), &states, update_location, &options.immunized);
 
         // Assemble transition rate distribution (by sampling all states)
         let mut change_rates: Vec<f64> = vec![];
+        let mut to_states: Vec<usize> = vec![];
         for to_state in ips_rules.all_states() {
-            change_rates.push(
-                ips_rules.get_mutation_rate(states[update_location],
-                                            to_state.clone(),
-                                            &neigh_state_counts));
+            let rate = neigh_states.mutation_rate(ips_rules.as_ref(),
+                                                  states[update_location],
+                                                  to_state.clone());
+            // For the sparse distribution, only keep the states that can be transitioned to.
+            // Leaving out zero weights doesn't change the cumulative weights of the other states,
+            // so the same state is sampled.
+            if !options.sparse_to_state || rate > 0.0 {
+                change_rates.push(rate);
+                to_states.push(to_state);
+            }
         }
 
         // Initialize distribution object
         let distr_to_state = match WeightedIndex::new(change_rates) {
             Ok(distribution) => { distribution }
-            Err(WeightedError::AllWeightsZero) => { break; }
+            Err(WeightedError::AllWeightsZero | WeightedError::NoItem) => { break; }
             Err(other) => { panic!("Strange error! {:?}", other) }
         };
 
         // Sample the distribution we found to get the state to which the particle transitions
-        let new_state = distr_to_state.sample(&mut rng);
+        let new_state = to_states[distr_to_state.sample(&mut rng)];
 
         /* Update states and reactivities */
 
         // Record previous state our particle was in
         let old_particle_state = (*states.get(update_location).unwrap()).clone();
+        if old_particle_state == 0 && new_state != 0 {
+            time_last_infection = time_passed;
+        }
         // Change old state to new state
         states[update_location] = new_state.clone();
+        match state_counts.get_mut(&old_particle_state) {
+            Some(count) if *count > 1 => { *count -= 1; }
+            _ => { state_counts.remove(&old_particle_state); } // no particle left in the old state
+        }
+        *state_counts.entry(new_state).or_insert(0) += 1;
+        if options.record_events {
+            events.push(Event {
+                time: time_passed,
+                site: update_location,
+                old_state: old_particle_state,
+                new_state,
+            });
+        }
 
         // Compute own new rate
-        // first need the state counts of the neighbors
-        let mut neigh_state_counts: HashMap<usize, usize> = HashMap::new();
-        for n in &neighs {
-            neigh_state_counts.insert(
-                (*states.get(*n).unwrap()).clone(),
-                neigh_state_counts.get(&states[*n]).unwrap_or(&0) + 1,
-            );
-        }
+        // The neighbors didn't change, so their states are the same as above
         total_reactivity -= reactivities[update_location]; // Need to update total rate as well
-        reactivities[update_location] = ips_rules.get_reactivity(new_state, &neigh_state_counts);
+        reactivities[update_location] = multiplier(update_location) * neigh_states.reactivity(ips_rules.as_ref(), new_state);
         total_reactivity += reactivities[update_location];
 
 
 
         // Update surrounding rates & total rate
-        for n in &neighs {
-            // For every neighbor of the particle that's being updated
+        for n in &influenced {
+            // For every point influenced by the particle that's being updated
+
+            if !ips_rules.is_neighbor_additive() || graph.is_signed() {
+                // The contribution of the updated particle can't be separated out (or depends on
+                // the sign of its edge), so recompute the rate of the neighbor from all of its
+                // neighbors
+                let neigh_states = NeighborStates::of(graph.as_ref(), &states, *n, &options.immunized);
+                total_reactivity -= reactivities[*n];
+                reactivities[*n] = multiplier(*n) * neigh_states.reactivity(ips_rules.as_ref(), states[*n]);
+                total_reactivity += reactivities[*n];
+                continue;
+            }
+
+            // The contribution of the updated particle is scaled by the weight of its edge to n
+            let weight = if graph.is_weighted() {
+                graph.get_weighted_neighbors(*n).into_iter()
+                    .find(|&(m, _)| m == update_location)
+                    .map_or(0.0, |(_, weight)| weight)
+            } else {
+                1.0
+            };
 
             // Compute the old spread rate
-            let old_spread_rate = ips_rules.get_neighbor_reactivity(states[*n], old_particle_state.clone());
+            let old_spread_rate = multiplier(*n) * weight * ips_rules.get_neighbor_reactivity(states[*n], old_particle_state.clone());
             // Subtract the old spread rate from both the reactivities and the total reactivity
             reactivities[*n] -= old_spread_rate;
             total_reactivity -= old_spread_rate;
             // Compute the new spread rate
-            let new_spread_rate = ips_rules.get_neighbor_reactivity(states[*n], new_state.clone());
+            let new_spread_rate = multiplier(*n) * weight * ips_rules.get_neighbor_reactivity(states[*n], new_state.clone());
             // Add the new spread rate to both the reactivities and total reactivity
             reactivities[*n] += new_spread_rate;
             total_reactivity += new_spread_rate;
@@ -294,30 +819,1048 @@ pub fn particle_system_solver(
         // Collect a list of reactivities that change.
         // TODO: This is ugly, and I want to get rid of it, but I'm not sure how to work around the references. May be able to get rid of `reactivities` entirely
         let mut changing_weights = vec![(update_location, reactivities.get(update_location).unwrap())]; // harvest the new rate of the updating particle
-        for n in &neighs { // harvest the changed rates from the neighbors
+        for n in &influenced { // harvest the changed rates from the influenced points
             changing_weights.push((*n, &reactivities[*n]));
         }
         changing_weights.sort_by(|a, b| (a.0).cmp(&b.0)); // sorting is required for .update_weights()
         match distr_location.update_weights(&changing_weights[..]) {
             Ok(_) => {}
-            Err(WeightedError::AllWeightsZero) => { break; } // All particles have died, no more reaction is possible
+            Err(WeightedError::AllWeightsZero) => {
+                // All particles have died, no more reaction is possible until the rates change
+                if ips_rules.next_rate_change(time_passed).is_none() { break; }
+                total_reactivity = 0.0;
+            }
             Err(e) => { panic!("Changing weights: {:?}, Error: {}", changing_weights, e) }
         }; // By far the heaviest operation in the whole program
+    }
 
-        // Record new state
-        for _ in 0..record_condition.how_often_record(time_passed, time_step, steps_taken) {
-            states_record.append(&mut prev_state.clone());
-            steps_recorded += 1;
-            if !halting_condition.should_continue(time_passed, steps_recorded, steps_taken) { // we want to check the halting condition each step
-                break;
+    // * PHASE III: Cleanup * //
+
+    // Put the reservoir sample in chronological order. The times of the steps are increasing, so
+    // sort by them.
+    if let RecordCondition::ReservoirSample(_) = record_condition {
+        if !times.is_empty() {
+            let snapshot_len = states_record.len() / times.len();
+            let mut order: Vec<usize> = (0..times.len()).collect();
+            order.sort_by(|&a, &b| times[a].total_cmp(&times[b]));
+
+            states_record = order.iter()
+                .flat_map(|&slot| states_record[slot * snapshot_len..(slot + 1) * snapshot_len].iter().copied())
+                .collect();
+            times = order.iter().map(|&slot| times[slot]).collect();
+            if options.record_reactivity_trace {
+                reactivity_trace = order.iter().map(|&slot| reactivity_trace[slot]).collect();
             }
         }
     }
 
-    // * PHASE III: Cleanup * //
-
     // Record final state
-    states_record.append(&mut states.clone());
+    record_snapshot(&mut states_record, &states, &options.record_region);
+    times.push(time_passed);
+    if let Some(on_record) = options.on_record.as_mut() {
+        on_record(time_passed, &states);
+    }
+    if options.record_reactivity_trace {
+        reactivity_trace.push((time_passed, total_reactivity));
+    }
+    steps_recorded += 1;
+
+    Ok(Solution {
+        snapshots: states_record,
+        times,
+        recorded_indices: options.record_region,
+        final_state: states,
+        final_state_counts: state_counts,
+        time_simulated: time_passed,
+        steps_recorded,
+        steps_taken,
+        reactivity_trace: if options.record_reactivity_trace { Some(reactivity_trace) } else { None },
+        events,
+    })
+}
+
+/// Count the number of particles in each state of `states`. States without particles are left out.
+pub fn count_states(states: &[usize]) -> HashMap<usize, usize> {
+    let mut state_counts: HashMap<usize, usize> = HashMap::new();
+    for state in states {
+        *state_counts.entry(*state).or_insert(0) += 1;
+    }
+    state_counts
+}
+
+/// The states of the neighbors of a particle, which determine its rates: the number of neighbors in
+/// each state, on a weighted graph the total weight of the edges to the neighbors in each state, or
+/// on a signed graph the number of friends and of enemies in each state.
+enum NeighborStates {
+    Counts(HashMap<usize, usize>),
+    Weights(HashMap<usize, f64>),
+    Signed { friends: HashMap<usize, usize>, enemies: HashMap<usize, usize> },
+}
+
+impl NeighborStates {
+    /// Look up the states of the neighbors of `particle` in `graph`, leaving out the `immunized`
+    /// particles.
+    fn of(graph: &dyn Graph, states: &[usize], particle: usize, immunized: &HashSet<usize>) -> NeighborStates {
+        if graph.is_weighted() {
+            let mut weights: HashMap<usize, f64> = HashMap::new();
+            for (neighbor, weight) in graph.get_weighted_neighbors(particle) {
+                if immunized.contains(&neighbor) {
+                    continue;
+                }
+                *weights.entry(states[neighbor]).or_insert(0.0) += weight;
+            }
+            NeighborStates::Weights(weights)
+        } else if graph.is_signed() {
+            let mut friends: HashMap<usize, usize> = HashMap::new();
+            let mut enemies: HashMap<usize, usize> = HashMap::new();
+            for (neighbor, sign) in graph.get_signed_neighbors(particle) {
+                if immunized.contains(&neighbor) {
+                    continue;
+                }
+                let counts = match sign {
+                    EdgeSign::Positive => &mut friends,
+                    EdgeSign::Negative => &mut enemies,
+                };
+                *counts.entry(states[neighbor]).or_insert(0) += 1;
+            }
+            NeighborStates::Signed { friends, enemies }
+        } else {
+            let mut counts: HashMap<usize, usize> = HashMap::new();
+            for neighbor in graph.get_neighbors(particle) {
+                if immunized.contains(&neighbor) {
+                    continue;
+                }
+                *counts.entry(states[neighbor]).or_insert(0) += 1;
+            }
+            NeighborStates::Counts(counts)
+        }
+    }
+
+    /// The rate at which a particle in the state `current` changes to the state `goal`.
+    fn mutation_rate(&self, ips_rules: &dyn IPSRules, current: usize, goal: usize) -> f64 {
+        match self {
+            NeighborStates::Counts(counts) => ips_rules.get_mutation_rate(current, goal, counts),
+            NeighborStates::Weights(weights) => ips_rules.get_weighted_mutation_rate(current, goal, weights),
+            NeighborStates::Signed { friends, enemies } => {
+                ips_rules.get_signed_mutation_rate(current, goal, friends, enemies)
+            }
+        }
+    }
+
+    /// The rate at which a particle in the state `current` changes to any other state.
+    fn reactivity(&self, ips_rules: &dyn IPSRules, current: usize) -> f64 {
+        match self {
+            NeighborStates::Counts(counts) => ips_rules.get_reactivity(current, counts),
+            NeighborStates::Weights(weights) => ips_rules.get_weighted_reactivity(current, weights),
+            NeighborStates::Signed { friends, enemies } => {
+                ips_rules.get_signed_reactivity(current, friends, enemies)
+            }
+        }
+    }
+}
+
+/// Append the snapshot `states` to `states_record`, restricted to `region` if given.
+fn record_snapshot(states_record: &mut Vec<usize>, states: &[usize], region: &Option<Vec<usize>>) {
+    match region {
+        Some(region) => states_record.extend(region.iter().map(|&i| states[i])),
+        None => states_record.extend_from_slice(states),
+    }
+}
+
+/// Overwrite the `slot`th snapshot in `states_record` with the snapshot `states`, restricted to
+/// `region` if given.
+fn replace_snapshot(states_record: &mut [usize], slot: usize, states: &[usize], region: &Option<Vec<usize>>) {
+    match region {
+        Some(region) => {
+            let snapshot = &mut states_record[slot * region.len()..(slot + 1) * region.len()];
+            for (recorded, &i) in snapshot.iter_mut().zip(region.iter()) {
+                *recorded = states[i];
+            }
+        }
+        None => states_record[slot * states.len()..(slot + 1) * states.len()].copy_from_slice(states),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::solver::graph::bipartite::CompleteBipartite;
+    use crate::solver::graph::cayley_tree::CayleyTree;
+    use crate::solver::graph::configuration_model::ConfigurationModel;
+    use crate::solver::graph::dense::DenseGraph;
+    use crate::solver::graph::diluted_lattice::DilutedLattice;
+    use crate::solver::graph::erdos_renyi::ErdosRenyi;
+    use crate::solver::graph::grid_n_d::GridND;
+    use crate::solver::graph::hypercube::Hypercube;
+    use crate::solver::graph::kagome::KagomeLattice;
+    use crate::solver::graph::random_geometric::RandomGeometric;
+    use crate::solver::graph::ring_lattice::RingLattice;
+    use crate::solver::graph::signed_graph::SignedGraph;
+    use crate::solver::graph::sbm::StochasticBlockModel;
+    use crate::solver::graph::star::StarGraph;
+    use crate::solver::graph::weighted_adjacency::WeightedAdjacency;
+    use crate::solver::ips_rules::anti_voter::AntiVoter;
+    use crate::solver::ips_rules::asymmetric_two_si::AsymmetricTwoSI;
+    use crate::solver::ips_rules::deffuant::Deffuant;
+    use crate::solver::ips_rules::forest_fire::ForestFire;
+    use crate::solver::ips_rules::linear_voter::LinearVoter;
+    use crate::solver::ips_rules::moran_process::MoranProcess;
+    use crate::solver::ips_rules::multistrain::MultiStrainContact;
+    use crate::solver::ips_rules::noisy_voter::NoisyVoter;
+    use crate::solver::ips_rules::potts::PottsModel;
+    use crate::solver::ips_rules::schelling::Schelling;
+    use crate::solver::ips_rules::seir_partial::SEIRPartial;
+    use crate::solver::ips_rules::si_process::SIProcess;
+    use crate::solver::ips_rules::signed_voter::SignedVoter;
+    use crate::solver::ips_rules::sir_process::SIRProcess;
+    use crate::solver::ips_rules::sirs_process::SIRSProcess;
+    use crate::solver::ips_rules::time_dependent::TimeDependentRules;
+    use crate::solver::ips_rules::two_si_process::TwoSIProcess;
+    use crate::solver::ips_rules::voter_process::VoterProcess;
+    use super::*;
+
+    /// Small connected graphs of every type, with a name for the failure messages. The random
+    /// graphs are seeded.
+    fn fixture_graphs() -> Vec<(&'static str, Box<dyn Graph>)> {
+        vec![
+            ("grid", Box::new(GridND::from(vec![5, 4]))),
+            ("Moore grid", Box::new(GridND::new_moore(vec![5, 4], vec![true, false]))),
+            ("Erdos-Renyi graph", Box::new(ErdosRenyi::new(20, 0.5, StdRng::seed_from_u64(3)))),
+            ("diluted lattice", Box::new(DilutedLattice::new(5, 4, 1.0, StdRng::seed_from_u64(3)))),
+            ("Cayley tree", Box::new(CayleyTree::new(3, 2))),
+            ("complete bipartite graph", Box::new(CompleteBipartite::new(3, 5))),
+            ("hypercube", Box::new(Hypercube::new(4))),
+            ("kagome lattice", Box::new(KagomeLattice::new(3, 3, true))),
+            ("ring lattice", Box::new(RingLattice::new(20, 2))),
+            ("random geometric graph", Box::new(RandomGeometric::new(20, 0.5, StdRng::seed_from_u64(3)))),
+            ("star", Box::new(StarGraph::new(6))),
+            ("configuration model", Box::new(ConfigurationModel::new((0..40).map(|i| 2 + i % 2).collect(), StdRng::seed_from_u64(1)))),
+            ("stochastic block model", Box::new(StochasticBlockModel::new(vec![10, 10], 0.5, 0.125, StdRng::seed_from_u64(3)))),
+        ]
+    }
+
+    /// Simulate the rules made by `make_rules` on every fixture graph for the amount of time
+    /// `time`, with a fixed seed, starting from the initial condition made by `initial_condition`
+    /// for the number of points of the graph. Returns the time simulated and the counts of the
+    /// final states, per graph.
+    fn run_on_fixtures(make_rules: impl Fn() -> Box<dyn IPSRules>, initial_condition: impl Fn(usize) -> Vec<usize>,
+                       time: f64) -> Vec<(&'static str, usize, f64, HashMap<usize, usize>)> {
+        fixture_graphs().into_iter()
+            .map(|(name, graph)| {
+                let nr_points = graph.nr_points();
+                let solution = particle_system_solver(
+                    make_rules(),
+                    graph,
+                    initial_condition(nr_points),
+                    HaltCondition::TimePassed(time),
+                    RecordCondition::Final(),
+                    SolverOptions::default(),
+                    StdRng::seed_from_u64(5),
+                );
+                (name, nr_points, solution.time_simulated, solution.final_state_counts)
+            })
+            .collect()
+    }
+
+    /// Like `run_on_fixtures`, but until the rules are absorbed. The rates should be multiples of
+    /// powers of two, so that the reactivities of absorbing states are exactly zero.
+    fn run_until_absorbed(make_rules: impl Fn() -> Box<dyn IPSRules>,
+                          initial_condition: impl Fn(usize) -> Vec<usize>) -> Vec<(&'static str, usize, HashMap<usize, usize>)> {
+        run_on_fixtures(make_rules, initial_condition, 10_000.0).into_iter()
+            .map(|(name, nr_points, time_simulated, counts)| {
+                assert!(time_simulated < 10_000.0, "Not absorbed on the {}!", name);
+                (name, nr_points, counts)
+            })
+            .collect()
+    }
+
+    /// Only the first particle in state `state`, the others in state 0.
+    fn single_seed(state: usize) -> impl Fn(usize) -> Vec<usize> {
+        move |nr_points| (0..nr_points).map(|i| if i == 0 { state } else { 0 }).collect()
+    }
+
+    #[test]
+    fn si_infects_whole_graph() {
+        let runs = run_until_absorbed(
+            || Box::new(SIProcess { birth_rate: 1.0, death_rate: 0.0, spontaneous_rate: 0.0 }),
+            single_seed(1),
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts, HashMap::from([(1, nr_points)]), "{}", name);
+        }
+    }
+
+    #[test]
+    fn sir_ends_without_infected() {
+        let runs = run_until_absorbed(
+            || Box::new(SIRProcess { birth_rate: 2.0, death_rate: 1.0 }),
+            single_seed(1),
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts.get(&1), None, "{}", name);
+            assert!(counts[&2] >= 1, "{}", name); // at least the seed was removed
+            assert_eq!(counts.get(&0).unwrap_or(&0) + counts[&2], nr_points, "{}", name);
+        }
+    }
+
+    #[test]
+    fn two_si_ends_with_one_species() {
+        // Without deaths, the species fill the graph and then compete until one is left
+        let runs = run_until_absorbed(
+            || Box::new(TwoSIProcess { birth_rate: 1.0, death_rate: 0.0, compete_rate: 1.0 }),
+            |nr_points| (0..nr_points).map(|i| if i == 0 { 1 } else if i == nr_points - 1 { 2 } else { 0 }).collect(),
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts.len(), 1, "{}", name);
+            assert!(counts == HashMap::from([(1, nr_points)]) || counts == HashMap::from([(2, nr_points)]), "{}", name);
+        }
+    }
+
+    #[test]
+    fn voter_reaches_consensus() {
+        let runs = run_until_absorbed(
+            || Box::new(VoterProcess { nr_parties: 2, change_rate: 1.0 }),
+            |nr_points| (0..nr_points).map(|i| i % 2).collect(),
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts.len(), 1, "{}", name);
+            assert_eq!(counts.values().sum::<usize>(), nr_points, "{}", name);
+        }
+    }
+
+    #[test]
+    fn seir_partial_ends_without_exposed_or_infected() {
+        let runs = run_until_absorbed(
+            || Box::new(SEIRPartial { birth_i: 2.0, birth_e_fraction: 0.5, incubation_rate: 1.0, recovery_rate: 1.0 }),
+            single_seed(2),
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!((counts.get(&1), counts.get(&2)), (None, None), "{}", name);
+            assert_eq!(counts.get(&0).unwrap_or(&0) + counts[&3], nr_points, "{}", name);
+        }
+    }
+
+    #[test]
+    fn antivoter_stays_mixed() {
+        // Starting from consensus, the antivoter process moves away from it
+        let runs = run_on_fixtures(
+            || Box::new(AntiVoter { nr_parties: 2, rate: 1.0 }),
+            |nr_points| vec![0; nr_points],
+            10.0,
+        );
+
+        for (name, nr_points, _, counts) in runs {
+            assert_eq!(counts.len(), 2, "{}", name);
+            assert_eq!(counts[&0] + counts[&1], nr_points, "{}", name);
+        }
+    }
+
+    #[test]
+    fn deffuant_with_full_confidence_reaches_consensus() {
+        let runs = run_until_absorbed(
+            || Box::new(Deffuant { nr_bins: 3, confidence: 2, mu: 1.0 }),
+            |nr_points| (0..nr_points).map(|i| i % 3).collect(),
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts.len(), 1, "{}", name);
+            assert_eq!(counts.values().sum::<usize>(), nr_points, "{}", name);
+        }
+    }
+
+    #[test]
+    fn moran_process_fixates() {
+        let runs = run_until_absorbed(
+            || Box::new(MoranProcess { fitness: vec![1.0, 2.0] }),
+            |nr_points| (0..nr_points).map(|i| i % 2).collect(),
+        );
 
-    (states_record, states, time_passed, steps_recorded, steps_taken)
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts.len(), 1, "{}", name);
+            assert_eq!(counts.values().sum::<usize>(), nr_points, "{}", name);
+        }
+    }
+
+    #[test]
+    fn noisy_voter_leaves_consensus() {
+        let runs = run_on_fixtures(
+            || Box::new(NoisyVoter { nr_parties: 2, copy_rate: 1.0, noise_rate: 0.5 }),
+            |nr_points| vec![0; nr_points],
+            10.0,
+        );
+
+        for (name, nr_points, time_simulated, counts) in runs {
+            assert!(time_simulated >= 10.0, "Absorbed on the {}!", name);
+            assert_eq!(counts.values().sum::<usize>(), nr_points, "{}", name);
+        }
+    }
+
+    #[test]
+    fn potts_model_is_never_absorbed() {
+        let runs = run_on_fixtures(
+            || Box::new(PottsModel { q: 3, beta: 2.0 }),
+            |nr_points| vec![0; nr_points],
+            10.0,
+        );
+
+        for (name, nr_points, time_simulated, counts) in runs {
+            assert!(time_simulated >= 10.0, "Absorbed on the {}!", name);
+            assert!(counts.len() > 1, "{}", name);
+            assert_eq!(counts.values().sum::<usize>(), nr_points, "{}", name);
+        }
+    }
+
+    #[test]
+    fn tolerant_schelling_city_fills_up() {
+        // Nobody is unhappy, so nobody moves out
+        let runs = run_until_absorbed(|| Box::new(Schelling { tolerance: 0.0 }), |nr_points| vec![0; nr_points]);
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts.get(&0), None, "{}", name);
+            assert_eq!(counts.values().sum::<usize>(), nr_points, "{}", name);
+        }
+    }
+
+    #[test]
+    fn forest_fire_burns_out() {
+        // Without growth and lightning, a single fire burns out
+        let runs = run_until_absorbed(
+            || Box::new(ForestFire { growth_rate: 0.0, lightning_rate: 0.0, burn_rate: 1.0 }),
+            |nr_points| (0..nr_points).map(|i| if i == 0 { 2 } else { 1 }).collect(),
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts.get(&2), None, "{}", name);
+            assert!(counts[&0] >= 1, "{}", name);
+            assert_eq!(counts[&0] + counts.get(&1).unwrap_or(&0), nr_points, "{}", name);
+        }
+    }
+
+    #[test]
+    fn sirs_without_infection_wanes_to_susceptible() {
+        // Without infection every particle ends up susceptible again
+        let runs = run_until_absorbed(
+            || Box::new(SIRSProcess { birth_rate: 0.0, death_rate: 1.0, waning_rate: 0.5 }),
+            |nr_points| vec![1; nr_points],
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts.get(&0), Some(&nr_points), "{}", name);
+        }
+    }
+
+    #[test]
+    fn single_strain_takes_over() {
+        // A lone strain 2 infection spreads everywhere without ever producing strain 1
+        let runs = run_until_absorbed(
+            || Box::new(MultiStrainContact { birth_rates: vec![1.0, 1.0], death_rates: vec![0.0, 0.0] }),
+            single_seed(2),
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts, HashMap::from([(2, nr_points)]), "{}", name);
+        }
+    }
+
+    #[test]
+    fn stronger_species_converts_the_other() {
+        // Only species 1 can convert, starting from a single particle of it
+        let runs = run_until_absorbed(
+            || Box::new(AsymmetricTwoSI { birth1: 0.0, death1: 0.0, birth2: 0.0, death2: 0.0, compete12: 1.0, compete21: 0.0 }),
+            |nr_points| (0..nr_points).map(|i| if i == 0 { 1 } else { 2 }).collect(),
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts, HashMap::from([(1, nr_points)]), "{}", name);
+        }
+    }
+
+    #[test]
+    fn linear_voter_reaches_consensus() {
+        // Only on a grid: the fractions of neighbors have to be exact in floating point for the
+        // consensus to have reactivity exactly zero, so the degrees have to be powers of two
+        let solution = particle_system_solver(
+            Box::new(LinearVoter { nr_parties: 3 }),
+            Box::new(GridND::from(vec![5, 4])),
+            (0..20).map(|i| i % 3).collect(),
+            HaltCondition::TimePassed(10_000.0),
+            RecordCondition::Final(),
+            SolverOptions::default(),
+            StdRng::seed_from_u64(5),
+        );
+
+        assert!(solution.time_simulated < 10_000.0);
+        assert_eq!(solution.final_state_counts.len(), 1);
+    }
+
+    #[test]
+    fn signed_voter_on_unsigned_graphs_reaches_consensus() {
+        // Without enemies this is the voter process
+        let runs = run_until_absorbed(
+            || Box::new(SignedVoter { nr_parties: 2, change_rate: 1.0 }),
+            |nr_points| (0..nr_points).map(|i| i % 2).collect(),
+        );
+
+        for (name, nr_points, counts) in runs {
+            assert_eq!(counts.len(), 1, "{}", name);
+            assert_eq!(counts.values().sum::<usize>(), nr_points, "{}", name);
+        }
+    }
+
+    /// Voter process on a 10 by 10 grid, starting from a checkerboard, so that it can't be
+    /// absorbed within a few steps.
+    fn solve_voter(halting_condition: HaltCondition, record_condition: RecordCondition,
+                   options: SolverOptions) -> Result<Solution, RecordTooLarge> {
+        particle_system_solver_checked(
+            Box::new(VoterProcess { nr_parties: 2, change_rate: 1.0 }),
+            Box::new(GridND::from(vec![10, 10])),
+            (0..100).map(|i| (i + i / 10) % 2).collect(),
+            halting_condition,
+            record_condition,
+            options,
+            StdRng::seed_from_u64(1),
+        )
+    }
+
+    #[test]
+    fn every_nth_step_projection_is_exact() {
+        for (limit, n) in [(4, 1), (9, 5), (10, 5), (11, 3)] {
+            let solution = solve_voter(HaltCondition::StepsTaken(limit), RecordCondition::EveryNthStep(n),
+                                       SolverOptions::default()).unwrap();
+
+            assert_eq!(Some(solution.steps_recorded),
+                       RecordCondition::EveryNthStep(n).projected_nr_snapshots(&HaltCondition::StepsTaken(limit)));
+        }
+    }
+
+    #[test]
+    fn record_too_large_before_simulating() {
+        let options = SolverOptions { max_record_bytes: Some(1000), ..SolverOptions::default() };
+
+        let result = solve_voter(HaltCondition::StepsTaken(99), RecordCondition::EveryNthStep(1), options);
+
+        assert_eq!(result, Err(RecordTooLarge { projected_bytes: 101 * 100 * size_of::<usize>(), max_bytes: 1000 }));
+    }
+
+    #[test]
+    fn record_too_large_while_simulating() {
+        // The number of snapshots is not known in advance, so the check is while simulating
+        let options = SolverOptions { max_record_bytes: Some(1000), ..SolverOptions::default() };
+
+        let result = solve_voter(HaltCondition::TimePassed(10.0), RecordCondition::EveryNthStep(1), options);
+
+        assert_eq!(result, Err(RecordTooLarge { projected_bytes: 2 * 100 * size_of::<usize>(), max_bytes: 1000 }));
+    }
+
+    /// Apply the transitions `events` to `initial_condition`, in order.
+    fn replay(initial_condition: &[usize], events: &[Event]) -> Vec<usize> {
+        let mut states = initial_condition.to_vec();
+        for event in events {
+            assert_eq!(states[event.site], event.old_state);
+            states[event.site] = event.new_state;
+        }
+        states
+    }
+
+    #[test]
+    fn events_replay_to_final_state() {
+        let initial_condition: Vec<usize> = (0..25).map(|i| (i == 12) as usize).collect();
+
+        let solution = particle_system_solver(
+            Box::new(SIProcess { birth_rate: 2.0, death_rate: 1.0, spontaneous_rate: 0.1 }),
+            Box::new(GridND::from(vec![5, 5])),
+            initial_condition.clone(),
+            HaltCondition::StepsTaken(49),
+            RecordCondition::Final(),
+            SolverOptions { record_events: true, ..SolverOptions::default() },
+            StdRng::seed_from_u64(2),
+        );
+
+        assert_eq!(solution.steps_taken, 50);
+        assert_eq!(solution.events.len() as u64, solution.steps_taken);
+        assert!(solution.events.windows(2).all(|pair| pair[0].time <= pair[1].time));
+        assert_eq!(replay(&initial_condition, &solution.events), solution.final_state);
+    }
+
+    #[test]
+    fn rate_change_is_a_step_without_event() {
+        let initial_condition: Vec<usize> = (0..25).map(|i| (i == 12) as usize).collect();
+        let rules = TimeDependentRules::scaled(
+            Box::new(SIProcess { birth_rate: 2.0, death_rate: 1.0, spontaneous_rate: 0.1 }),
+            |time| if time < 1.0 { 1.0 } else { 2.0 },
+            vec![1.0],
+        );
+
+        let solution = particle_system_solver(
+            Box::new(rules),
+            Box::new(GridND::from(vec![5, 5])),
+            initial_condition.clone(),
+            HaltCondition::TimePassed(2.0),
+            RecordCondition::Final(),
+            SolverOptions { record_events: true, ..SolverOptions::default() },
+            StdRng::seed_from_u64(2),
+        );
+
+        assert_eq!(solution.events.len() as u64 + 1, solution.steps_taken);
+        assert_eq!(replay(&initial_condition, &solution.events), solution.final_state);
+    }
+
+    #[test]
+    fn adaptive_rate_records_densely_when_reactive() {
+        // Every particle flips at rate 1, sped up to 4 until time 10 and slowed down to 1/8 after
+        let rules = TimeDependentRules::scaled(
+            Box::new(SIProcess { birth_rate: 0.0, death_rate: 1.0, spontaneous_rate: 1.0 }),
+            |time| if time < 10.0 { 4.0 } else { 0.125 },
+            vec![10.0],
+        );
+
+        let solution = particle_system_solver(
+            Box::new(rules),
+            Box::new(GridND::from(vec![5, 5])),
+            vec![0; 25],
+            HaltCondition::TimePassed(20.0),
+            RecordCondition::AdaptiveRate { min_interval: 0.1, max_interval: 2.0 },
+            SolverOptions::default(),
+            StdRng::seed_from_u64(3),
+        );
+
+        let nr_reactive = solution.times.iter().filter(|&&time| time < 10.0).count();
+        let nr_quiescent = solution.times.iter().filter(|&&time| time >= 10.0).count();
+        assert!(nr_reactive > 4 * nr_quiescent, "{} versus {} snapshots", nr_reactive, nr_quiescent);
+    }
+
+    #[test]
+    fn steps_recorded_records_that_many_snapshots() {
+        let solution = solve_voter(HaltCondition::StepsRecorded(5), RecordCondition::EveryNthStep(1),
+                                   SolverOptions::default()).unwrap();
+
+        assert_eq!(solution.steps_recorded, 5);
+        assert_eq!(solution.snapshots.len(), 5 * 100);
+    }
+
+    #[test]
+    fn every_step_snapshots_match_replayed_events() {
+        let initial_condition: Vec<usize> = (0..25).map(|i| (i == 12) as usize).collect();
+
+        let solution = particle_system_solver(
+            Box::new(SIProcess { birth_rate: 2.0, death_rate: 1.0, spontaneous_rate: 0.1 }),
+            Box::new(GridND::from(vec![5, 5])),
+            initial_condition.clone(),
+            HaltCondition::StepsTaken(29),
+            RecordCondition::EveryNthStep(1),
+            SolverOptions { record_events: true, ..SolverOptions::default() },
+            StdRng::seed_from_u64(4),
+        );
+
+        // The initial condition, and then a snapshot after every step
+        assert_eq!(solution.steps_recorded, 31);
+        for (step, snapshot) in solution.snapshots.chunks(25).enumerate() {
+            assert_eq!(snapshot, replay(&initial_condition, &solution.events[..step]), "step {}", step);
+        }
+    }
+
+    #[test]
+    fn deduplicated_records_fewer_snapshots_when_quiescent() {
+        // Every particle flips at rate 1 until time 1, and then at rate 1/64: one step spans many
+        // intervals
+        let solve = |record_condition| particle_system_solver(
+            Box::new(TimeDependentRules::scaled(
+                Box::new(SIProcess { birth_rate: 0.0, death_rate: 1.0, spontaneous_rate: 1.0 }),
+                |time| if time < 1.0 { 1.0 } else { 0.015625 },
+                vec![1.0],
+            )),
+            Box::new(GridND::from(vec![2, 2])),
+            vec![0; 4],
+            HaltCondition::TimePassed(20.0),
+            record_condition,
+            SolverOptions::default(),
+            StdRng::seed_from_u64(6),
+        );
+
+        let constant = solve(RecordCondition::ConstantTime(0.1));
+        let deduplicated = solve(RecordCondition::ConstantTimeDeduplicated(0.1));
+
+        assert_eq!(constant.final_state, deduplicated.final_state);
+        assert!(deduplicated.steps_recorded < constant.steps_recorded / 2,
+                "{} versus {} snapshots", deduplicated.steps_recorded, constant.steps_recorded);
+    }
+
+    #[test]
+    fn reactivity_of_dying_contact_process_goes_to_zero() {
+        // Subcritical contact process, starting with every particle infected
+        let solution = particle_system_solver(
+            Box::new(SIProcess { birth_rate: 0.125, death_rate: 1.0, spontaneous_rate: 0.0 }),
+            Box::new(GridND::from(vec![10, 10])),
+            vec![1; 100],
+            HaltCondition::TimePassed(10_000.0),
+            RecordCondition::EveryNthStep(1),
+            SolverOptions { record_reactivity_trace: true, ..SolverOptions::default() },
+            StdRng::seed_from_u64(7),
+        );
+
+        assert_eq!(solution.final_state_counts, HashMap::from([(0, 100)]));
+        let trace = solution.reactivity_trace.unwrap();
+        let mean = |part: &[(f64, f64)]| part.iter().map(|(_, reactivity)| reactivity).sum::<f64>() / part.len() as f64;
+        let (first_half, second_half) = trace.split_at(trace.len() / 2);
+        assert!(mean(second_half) < mean(first_half));
+        assert_eq!(trace.last().unwrap().1, 0.0);
+    }
+
+    #[test]
+    fn quiet_period_stops_after_window_without_infections() {
+        let solution = particle_system_solver(
+            Box::new(SIProcess { birth_rate: 0.25, death_rate: 1.0, spontaneous_rate: 0.0 }),
+            Box::new(GridND::from(vec![10, 10])),
+            (0..100).map(|i| (i + i / 10) % 2).collect(),
+            HaltCondition::Any(vec![HaltCondition::QuietPeriod(0.25), HaltCondition::TimePassed(100.0)]),
+            RecordCondition::Final(),
+            SolverOptions { record_events: true, ..SolverOptions::default() },
+            StdRng::seed_from_u64(8),
+        );
+
+        let time_last_infection = solution.events.iter()
+            .rfind(|event| event.old_state == 0)
+            .unwrap()
+            .time;
+        // Stopped at the first step after the quiet window, before the epidemic died out
+        assert!(solution.time_simulated < 100.0);
+        assert!(solution.time_simulated > time_last_infection + 0.25);
+        let nr_late_events = solution.events.iter().filter(|event| event.time > time_last_infection + 0.25).count();
+        assert!(nr_late_events <= 1); // only the step crossing the end of the window
+        assert!(solution.final_state_counts.contains_key(&1));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn solution_json_round_trip() {
+        let path = std::env::temp_dir().join("rust_particle_system_solution.json");
+        let solution = particle_system_solver(
+            Box::new(SIProcess { birth_rate: 2.0, death_rate: 1.0, spontaneous_rate: 0.1 }),
+            Box::new(GridND::from(vec![4, 4])),
+            (0..16).map(|i| (i == 5) as usize).collect(),
+            HaltCondition::StepsTaken(19),
+            RecordCondition::EveryNthStep(4),
+            SolverOptions { record_reactivity_trace: true, record_events: true, ..SolverOptions::default() },
+            StdRng::seed_from_u64(9),
+        );
+
+        solution.save_json(path.to_str().unwrap()).unwrap();
+        let loaded = Solution::load_json(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(loaded, solution);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn region_snapshots_have_region_length() {
+        let region = vec![11, 12, 21, 22, 35];
+        let options = SolverOptions { record_region: Some(region.clone()), ..SolverOptions::default() };
+
+        let solution = solve_voter(HaltCondition::StepsTaken(9), RecordCondition::EveryNthStep(2), options).unwrap();
+
+        assert_eq!(solution.snapshots.len(), region.len() * solution.steps_recorded as usize);
+        assert_eq!(solution.recorded_indices.as_ref(), Some(&region));
+        let last_snapshot = &solution.snapshots[solution.snapshots.len() - region.len()..];
+        let final_region: Vec<usize> = region.iter().map(|&i| solution.final_state[i]).collect();
+        assert_eq!(last_snapshot, final_region);
+    }
+
+    #[test]
+    fn high_multiplier_site_dominates() {
+        // Every particle flips at rate 1, except particle 42 which flips at rate 1000
+        let mut rate_multipliers = vec![1.0; 100];
+        rate_multipliers[42] = 1000.0;
+        let options = SolverOptions { rate_multipliers: Some(rate_multipliers), record_events: true, ..SolverOptions::default() };
+
+        let solution = particle_system_solver(
+            Box::new(SIProcess { birth_rate: 0.0, death_rate: 1.0, spontaneous_rate: 1.0 }),
+            Box::new(GridND::from(vec![10, 10])),
+            vec![0; 100],
+            HaltCondition::StepsTaken(49),
+            RecordCondition::Final(),
+            options,
+            StdRng::seed_from_u64(10),
+        );
+
+        let nr_at_site = solution.events.iter().filter(|event| event.site == 42).count();
+        assert!(nr_at_site > 40, "{} of the events at the site", nr_at_site);
+    }
+
+    #[test]
+    fn a_time_per_snapshot() {
+        for record_condition in [RecordCondition::ConstantTime(0.05), RecordCondition::EveryNthStep(3), RecordCondition::Final()] {
+            let solution = solve_voter(HaltCondition::TimePassed(1.0), record_condition, SolverOptions::default()).unwrap();
+
+            assert_eq!(solution.times.len() as u64, solution.steps_recorded);
+            assert_eq!(solution.times.len() * 100, solution.snapshots.len());
+            assert!(solution.times.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert_eq!(solution.times.last(), Some(&solution.time_simulated));
+        }
+    }
+
+    #[test]
+    fn callback_called_for_every_snapshot() {
+        let mut callback_times: Vec<f64> = vec![];
+        let mut callback = |time: f64, state: &[usize]| {
+            assert_eq!(state.len(), 100);
+            callback_times.push(time);
+        };
+        // Record no particles, only stream the states to the callback
+        let options = SolverOptions { record_region: Some(vec![]), on_record: Some(&mut callback), ..SolverOptions::default() };
+
+        let solution = solve_voter(HaltCondition::TimePassed(1.0), RecordCondition::ConstantTime(0.05), options).unwrap();
+
+        assert!(solution.snapshots.is_empty());
+        assert_eq!(callback_times.len() as u64, solution.steps_recorded);
+        assert_eq!(callback_times, solution.times);
+    }
+
+    #[test]
+    fn reservoir_sample_spans_the_run() {
+        let solution = solve_voter(HaltCondition::StepsTaken(2000), RecordCondition::ReservoirSample(10),
+                                   SolverOptions::default()).unwrap();
+
+        assert_eq!(solution.steps_recorded, 10);
+        assert_eq!(solution.snapshots.len(), 10 * 100);
+        assert!(solution.times.windows(2).all(|pair| pair[0] <= pair[1]));
+        // Not just the first or the last steps: sampled from both halves of the run, besides the
+        // final state
+        let half_time = solution.time_simulated / 2.0;
+        assert!(solution.times[0] < half_time);
+        assert!(solution.times[8] > half_time);
+    }
+
+    #[test]
+    fn frozen_particles_keep_their_state() {
+        // Zealots of the minority party on the diagonal
+        let zealots: HashSet<usize> = (0..10).map(|i| 11 * i).collect();
+        let initial_condition: Vec<usize> = (0..100).map(|i| zealots.contains(&i) as usize).collect();
+        let options = SolverOptions { frozen: zealots.clone(), ..SolverOptions::default() };
+
+        let solution = particle_system_solver(
+            Box::new(VoterProcess { nr_parties: 2, change_rate: 1.0 }),
+            Box::new(GridND::from(vec![10, 10])),
+            initial_condition,
+            HaltCondition::TimePassed(20.0),
+            RecordCondition::Final(),
+            options,
+            StdRng::seed_from_u64(6),
+        );
+
+        assert!(zealots.iter().all(|&i| solution.final_state[i] == 1));
+        // The zealots still convert their neighbors
+        assert!(solution.final_state_counts[&1] > zealots.len());
+    }
+
+    #[test]
+    fn state_counts_match_recount() {
+        // During an epidemic the counts of all three states change
+        for seed in 0..5 {
+            let solution = particle_system_solver(
+                Box::new(SIRProcess { birth_rate: 2.0, death_rate: 1.0 }),
+                Box::new(GridND::from(vec![10, 10])),
+                (0..100).map(|i| (i == 55) as usize).collect(),
+                HaltCondition::StepsTaken(150),
+                RecordCondition::Final(),
+                SolverOptions::default(),
+                StdRng::seed_from_u64(seed),
+            );
+
+            assert_eq!(solution.final_state_counts, count_states(&solution.final_state), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn double_time_scale_doubles_time() {
+        let options = SolverOptions { record_events: true, ..SolverOptions::default() };
+        let solution = solve_voter(HaltCondition::StepsTaken(50), RecordCondition::Final(), options).unwrap();
+        let options = SolverOptions { record_events: true, time_scale: Some(2.0), ..SolverOptions::default() };
+        let scaled = solve_voter(HaltCondition::StepsTaken(50), RecordCondition::Final(), options).unwrap();
+
+        // The same events, at twice the times
+        assert_eq!(scaled.time_simulated, 2.0 * solution.time_simulated);
+        assert_eq!(scaled.events.len(), solution.events.len());
+        for (scaled_event, event) in scaled.events.iter().zip(solution.events.iter()) {
+            assert_eq!((scaled_event.site, scaled_event.new_state), (event.site, event.new_state));
+            assert_eq!(scaled_event.time, 2.0 * event.time);
+        }
+    }
+
+    #[test]
+    fn nothing_recorded_during_burn_in() {
+        let options = SolverOptions { burn_in_time: 0.5, ..SolverOptions::default() };
+
+        let solution = solve_voter(HaltCondition::TimePassed(1.0), RecordCondition::ConstantTime(0.05), options).unwrap();
+
+        assert!(solution.times.iter().all(|&time| time >= 0.5), "{:?}", solution.times);
+        assert!(solution.steps_recorded > 5); // but recorded after it
+    }
+
+    #[test]
+    fn isolated_particle_infected_at_spontaneous_rate() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // The time of the first step is the time until infection of the only particle
+        let mean_time = (0..1000)
+            .map(|_| particle_system_solver(
+                Box::new(SIProcess { birth_rate: 1.0, death_rate: 0.0, spontaneous_rate: 0.25 }),
+                Box::new(DenseGraph::from_matrix(vec![vec![false]])),
+                vec![0],
+                HaltCondition::StepsTaken(1),
+                RecordCondition::Final(),
+                SolverOptions::default(),
+                &mut rng,
+            ).time_simulated)
+            .sum::<f64>() / 1000.0;
+
+        assert!((mean_time / 4.0 - 1.0).abs() < 0.1, "{}", mean_time);
+    }
+
+    #[test]
+    fn sparse_to_state_samples_same_transitions() {
+        let solve = |sparse_to_state: bool| particle_system_solver(
+            Box::new(VoterProcess { nr_parties: 10, change_rate: 1.0 }),
+            Box::new(GridND::from(vec![10, 10])),
+            (0..100).map(|i| (i * 7) % 10).collect(),
+            HaltCondition::StepsTaken(500),
+            RecordCondition::Final(),
+            SolverOptions { record_events: true, sparse_to_state, ..SolverOptions::default() },
+            StdRng::seed_from_u64(8),
+        );
+
+        let (full, sparse) = (solve(false), solve(true));
+
+        assert_eq!(sparse.events, full.events);
+        assert_eq!(sparse.final_state, full.final_state);
+    }
+
+    #[test]
+    fn doubled_edge_weight_doubles_contribution() {
+        let rules = SIProcess { birth_rate: 0.5, death_rate: 1.0, spontaneous_rate: 0.0 };
+        // Only the neighbor at the end of the edge with varying weight is infected
+        let states = vec![0, 0, 1];
+        let reactivity = |weight: f64| {
+            let graph = WeightedAdjacency::new(3, &[(0, 1, 1.0), (0, 2, weight)]);
+            NeighborStates::of(&graph, &states, 0, &HashSet::new()).reactivity(&rules, 0)
+        };
+
+        assert_eq!(reactivity(1.0), 0.5);
+        assert_eq!(reactivity(2.0), 2.0 * reactivity(1.0));
+    }
+
+    #[test]
+    fn infection_stops_after_rate_drops_to_zero() {
+        // The infection rate drops to zero at time 1, and infected particles never recover
+        let rules = TimeDependentRules::new(Box::new(|time| {
+            let birth_rate = if time < 1.0 { 1.0 } else { 0.0 };
+            Box::new(SIProcess { birth_rate, death_rate: 0.0, spontaneous_rate: 0.0 })
+        }), vec![1.0]);
+
+        let solution = particle_system_solver(
+            Box::new(rules),
+            Box::new(GridND::from(vec![10, 10])),
+            (0..100).map(|i| (i == 55) as usize).collect(),
+            HaltCondition::TimePassed(5.0),
+            RecordCondition::Final(),
+            SolverOptions { record_events: true, ..SolverOptions::default() },
+            StdRng::seed_from_u64(4),
+        );
+
+        assert!(!solution.events.is_empty()); // it did grow before the drop
+        assert!(solution.events.iter().all(|event| event.time < 1.0));
+        assert_eq!(solution.final_state_counts[&1], 1 + solution.events.len());
+    }
+
+    #[test]
+    fn enemy_reverses_copy_direction() {
+        let rules = SignedVoter { nr_parties: 2, change_rate: 1.0 };
+        // The rate at which particle 0 switches party, with particle 1 a friend in party 0
+        let switch_rate = |sign: EdgeSign, states: &[usize]| {
+            let triangle = SignedGraph::new(3, &[(0, 1, EdgeSign::Positive), (0, 2, sign), (1, 2, EdgeSign::Positive)]);
+            NeighborStates::of(&triangle, states, 0, &HashSet::new()).mutation_rate(&rules, states[0], 1 - states[0])
+        };
+
+        assert_eq!(switch_rate(EdgeSign::Positive, &[0, 0, 1]), 1.0); // copies its friend
+        assert_eq!(switch_rate(EdgeSign::Negative, &[0, 0, 1]), 0.0); // already disagrees with its enemy
+        assert_eq!(switch_rate(EdgeSign::Negative, &[1, 0, 1]), 2.0); // copies its friend and leaves its enemy
+    }
+
+    #[test]
+    fn step_cap_fires_before_time_limit() {
+        let halting_condition = HaltCondition::Any(vec![HaltCondition::TimePassed(100.0), HaltCondition::StepsTaken(50)]);
+
+        let solution = solve_voter(halting_condition, RecordCondition::Final(), SolverOptions::default()).unwrap();
+        let capped = solve_voter(HaltCondition::StepsTaken(50), RecordCondition::Final(), SolverOptions::default()).unwrap();
+
+        // Stops exactly where the step cap alone does
+        assert_eq!(solution.steps_taken, capped.steps_taken);
+        assert_eq!(solution.time_simulated, capped.time_simulated);
+        assert!(solution.time_simulated < 100.0);
+    }
+
+    #[test]
+    fn all_conditions_must_be_met() {
+        // First the step count and then the time is the last condition to be met
+        for (min_steps, min_time) in [(300, 0.1), (10, 1.0)] {
+            let halting_condition = HaltCondition::All(vec![HaltCondition::StepsTaken(min_steps), HaltCondition::TimePassed(min_time)]);
+
+            let solution = solve_voter(halting_condition, RecordCondition::Final(), SolverOptions::default()).unwrap();
+
+            assert!(solution.steps_taken > min_steps, "{} steps", solution.steps_taken);
+            assert!(solution.time_simulated >= min_time, "time {}", solution.time_simulated);
+        }
+    }
+
+    #[test]
+    fn infection_does_not_cross_immunized_row() {
+        // A cylinder of height 10 with the row y = 5 immunized, infected from the point (0, 0)
+        let immunized: HashSet<usize> = (50..60).collect();
+        let options = SolverOptions { immunized, ..SolverOptions::default() };
+
+        let solution = particle_system_solver(
+            Box::new(SIProcess { birth_rate: 1.0, death_rate: 0.0, spontaneous_rate: 0.0 }),
+            Box::new(GridND::cylinder(10, 10)),
+            single_seed(1)(100),
+            HaltCondition::TimePassed(10_000.0),
+            RecordCondition::Final(),
+            options,
+            StdRng::seed_from_u64(9),
+        );
+
+        assert!(solution.time_simulated < 10_000.0);
+        assert_eq!(solution.final_state[..50], [1; 50]);
+        assert_eq!(solution.final_state[50..], [0; 50]);
+    }
+
+    #[test]
+    fn resumed_run_matches_single_run() {
+        // A slow infection, so that the number infected after 100 time units varies a lot
+        let rules = || Box::new(SIProcess { birth_rate: 0.015625, death_rate: 0.0, spontaneous_rate: 0.0 });
+        let grid = || Box::new(GridND::from(vec![10, 10]));
+        let initial_condition: Vec<usize> = (0..100).map(|i| (i == 55) as usize).collect();
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let (mut nr_single, mut nr_chunked) = (0, 0);
+        for _ in 0..400 {
+            let single = particle_system_solver(rules(), grid(), initial_condition.clone(), HaltCondition::TimePassed(100.0),
+                                                RecordCondition::Final(), SolverOptions::default(), &mut rng);
+            let first = particle_system_solver(rules(), grid(), initial_condition.clone(), HaltCondition::TimePassed(50.0),
+                                               RecordCondition::Final(), SolverOptions::default(), &mut rng);
+            let resumed = particle_system_solver_resume(rules(), grid(), first.final_state, first.time_simulated,
+                                                        HaltCondition::TimePassed(100.0), RecordCondition::Final(),
+                                                        SolverOptions::default(), &mut rng);
+            assert!(resumed.time_simulated >= 100.0);
+            nr_single += single.final_state_counts[&1];
+            nr_chunked += resumed.final_state_counts[&1];
+        }
+
+        let (mean_single, mean_chunked) = (nr_single as f64 / 400.0, nr_chunked as f64 / 400.0);
+        assert!((mean_chunked / mean_single - 1.0).abs() < 0.1, "{} versus {} infected", mean_chunked, mean_single);
+    }
 }
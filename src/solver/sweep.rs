@@ -0,0 +1,94 @@
+use std::io::{self, ErrorKind};
+
+use crate::config::SimulationSpec;
+use crate::solver::{HaltCondition, RecordCondition};
+use crate::solver::ensemble::{EnsembleConfig, EnsembleStats, run_ensemble};
+
+/// Run an ensemble of `n_runs` simulations of `base_spec` for each of the `values` of the rate
+/// parameter named `param` (see `IPSSpec::with_parameter`), e.g., to map out a phase diagram or
+/// to locate the critical birth rate of the contact process. The graph and initial condition are
+/// built anew for every run. The runs last until the time of the `TimePassed` halting condition of
+/// `base_spec`, and are recorded at the interval of its `ConstantTime` record condition; the
+/// output of `base_spec` is ignored.
+///
+/// # Outputs
+/// Every value together with the statistics of its ensemble (see `run_ensemble`), in the order of
+/// `values`.
+///
+/// # Errors
+/// Returns an error if the particle system has no rate parameter `param`, if the conditions of
+/// `base_spec` are not as above, or if the initial condition doesn't fit the graph.
+pub fn parameter_sweep(base_spec: &SimulationSpec, param: &str, values: &[f64], n_runs: usize)
+                       -> io::Result<Vec<(f64, EnsembleStats)>> {
+    let time_horizon = match base_spec.halt {
+        HaltCondition::TimePassed(time) => time,
+        _ => return Err(io::Error::new(ErrorKind::InvalidInput, "A sweep needs a TimePassed halting condition!")),
+    };
+    let record_interval = match base_spec.record {
+        RecordCondition::ConstantTime(interval) => interval,
+        _ => return Err(io::Error::new(ErrorKind::InvalidInput, "A sweep needs a ConstantTime record condition!")),
+    };
+
+    let mut sweep = Vec::with_capacity(values.len());
+
+    for &value in values {
+        let ips = base_spec.ips.with_parameter(param, value)?;
+        let graph = base_spec.graph.clone();
+        let initial = base_spec.initial.clone();
+
+        // Fail before simulating if the initial condition doesn't fit, the runs can't return errors
+        initial.build(&graph, graph.build().as_ref(), ips.build().0.all_states())?;
+
+        let config = EnsembleConfig {
+            make_system: Box::new(move || {
+                let graph_object = graph.build();
+                let (ips_rules, _) = ips.build();
+                let initial_condition = initial.build(&graph, graph_object.as_ref(), ips_rules.all_states())
+                    .expect("The initial condition doesn't fit the graph!");
+                (ips_rules, graph_object, initial_condition)
+            }),
+            time_horizon,
+            record_interval,
+        };
+
+        sweep.push((value, run_ensemble(config, n_runs)));
+    }
+
+    Ok(sweep)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{GraphSpec, InitialSpec, IPSSpec, OutputSpec};
+    use super::*;
+
+    /// SI process on a star, starting with only the center infected.
+    fn si_on_star() -> SimulationSpec {
+        SimulationSpec {
+            graph: GraphSpec::Star { nr_leaves: 4 },
+            ips: IPSSpec::Si { birth_rate: 1.0, death_rate: 1.0, spontaneous_rate: 0.0 },
+            initial: InitialSpec::DifferentParticles { state: 1, particles: vec![0] },
+            halt: HaltCondition::TimePassed(1.0),
+            record: RecordCondition::ConstantTime(0.5),
+            output: OutputSpec::Json { path: "unused.json".to_string() },
+        }
+    }
+
+    #[test]
+    fn sweep_si_birth_rate() {
+        let sweep = parameter_sweep(&si_on_star(), "birth_rate", &[0.0, 2.0], 5).unwrap();
+
+        assert_eq!(sweep.iter().map(|(value, _)| *value).collect::<Vec<f64>>(), vec![0.0, 2.0]);
+        for (_, stats) in &sweep {
+            assert_eq!(stats.times, vec![0.5, 1.0]);
+            assert!(stats.survival_probability.iter().all(|&probability| (0.0..=1.0).contains(&probability)));
+        }
+        // Without births, only the center can stay infected
+        assert!(sweep[0].1.mean_infected.iter().all(|&mean| mean <= 1.0));
+    }
+
+    #[test]
+    fn sweep_unknown_parameter() {
+        assert!(parameter_sweep(&si_on_star(), "compete_rate", &[1.0], 1).is_err());
+    }
+}
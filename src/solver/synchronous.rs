@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use crate::solver::{count_states, RecordCondition, Solution};
+use crate::solver::assemble_initial_condition::validate_initial_condition;
+use crate::solver::graph::Graph;
+use crate::solver::ips_rules::IPSRules;
+
+/// Discrete-time interacting particle system simulator with synchronous updates, as in a
+/// (probabilistic) cellular automaton. Time advances in ticks of one time unit, and in every tick
+/// all particles update simultaneously, based on the configuration at the start of the tick.
+///
+/// The rates of `ips_rules` are interpreted as probabilities per tick: a particle in the state
+/// `current` changes to the state `goal` with probability `get_mutation_rate(current, goal, ..)`.
+/// If these probabilities sum to more than 1 for some particle, it changes for sure, to a state
+/// chosen proportionally to them. In particular, rules whose rates are 0 or 1 give a
+/// deterministic automaton.
+///
+/// This is a different process than the one simulated by `particle_system_solver`, even for the
+/// same rules. In the (continuous-time) Gillespie solver only one particle changes at a time, and
+/// every change is immediately seen by the neighbors. Here neighboring particles change at the same
+/// time without seeing each other's change, e.g., two neighboring particles of the voter process in
+/// different parties can swap parties. Only for small rates does one tick approximate one unit of
+/// continuous time.
+///
+/// # Parameters
+/// * `ips_rules`, `graph`, `initial_condition`: As in `particle_system_solver`.
+/// * `nr_ticks`: The number of ticks to simulate. The simulation stops earlier if no particle can
+///   change anymore.
+/// * `record_condition`: As in `particle_system_solver`, evaluated once per tick.
+/// * `rng`: Random number generator. Most likely you want to input `rand::thread_rng()`, or a
+///   seeded generator for a reproducible simulation.
+///
+/// # Outputs
+/// A `Solution` in the same format as that of `particle_system_solver`, where the steps are the
+/// ticks, without reactivity trace and events.
+pub fn synchronous_solver<R: Rng>(
+    ips_rules: Box<dyn IPSRules>,
+    graph: Box<dyn Graph>,
+    initial_condition: Vec<usize>,
+    nr_ticks: u64,
+    record_condition: RecordCondition,
+    mut rng: R,
+) -> Solution {
+    // * PHASE I: Initialization * //
+
+    let mut states: Vec<usize> = initial_condition;
+    assert_eq!(states.len(), graph.nr_points());
+    validate_initial_condition(ips_rules.as_ref(), &states)
+        .expect("The initial condition contains a state which is not a state of the particle system!");
+
+    // The neighbors don't change, so look them up only once
+    let neighbors: Vec<Vec<usize>> = (0..graph.nr_points())
+        .map(|i| graph.get_neighbors(i).into_iter().collect())
+        .collect();
+
+    // Initialize state record
+    let mut states_record: Vec<usize> = vec![];
+    let mut times: Vec<f64> = vec![];
+
+    // Initialize timekeeping
+    let mut time_passed = 0.0;
+    let mut time_last_recorded = 0.0;
+    let mut steps_recorded = 0; // Number of snapshots in states_record
+    let mut steps_taken = 0;
+
+    // * PHASE 2: Simulation loop * //
+    while steps_taken < nr_ticks {
+        /* Compute the probabilities of all changes from the frozen configuration */
+        let change_probabilities: Vec<Vec<f64>> = neighbors.iter().zip(states.iter())
+            .map(|(neighs, &current)| {
+                let mut counts: HashMap<usize, usize> = HashMap::new();
+                for j in neighs {
+                    *counts.entry(states[*j]).or_insert(0) += 1;
+                }
+                ips_rules.all_states().into_iter()
+                    .map(|goal| {
+                        if goal == current { 0.0 } else { ips_rules.get_mutation_rate(current, goal, &counts) }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let total_probability: f64 = change_probabilities.iter().flatten().sum();
+        if total_probability <= 0.0 { // No particle can change anymore
+            break;
+        }
+
+        /* Update timekeeping */
+        steps_taken += 1;
+        time_passed += 1.0;
+
+        /* Record the state */
+        // As in `particle_system_solver`, the state during the tick is recorded before the update
+        let mean_reactivity = total_probability / states.len() as f64;
+        for _ in 0..record_condition.how_often_record(time_passed, 1.0, steps_taken,
+                                                      time_last_recorded, mean_reactivity) {
+            states_record.extend_from_slice(&states);
+            times.push(time_passed);
+            time_last_recorded = time_passed;
+            steps_recorded += 1;
+        }
+
+        /* Update all particles at once */
+        for (state, probabilities) in states.iter_mut().zip(change_probabilities.iter()) {
+            let change_probability: f64 = probabilities.iter().sum();
+            if change_probability <= 0.0 || rng.gen::<f64>() >= change_probability {
+                continue; // The particle stays in its state
+            }
+
+            let distr_to_state = WeightedIndex::new(probabilities).unwrap();
+            *state = ips_rules.all_states()[distr_to_state.sample(&mut rng)];
+        }
+    }
+
+    // * PHASE III: Cleanup * //
+
+    // Record final state
+    states_record.extend_from_slice(&states);
+    times.push(time_passed);
+    steps_recorded += 1;
+
+    Solution {
+        snapshots: states_record,
+        times,
+        recorded_indices: None,
+        final_state_counts: count_states(&states),
+        final_state: states,
+        time_simulated: time_passed,
+        steps_recorded,
+        steps_taken,
+        reactivity_trace: None,
+        events: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::solver::graph::grid_n_d::GridND;
+    use crate::solver::ips_rules::si_process::SIProcess;
+    use super::*;
+
+    #[test]
+    fn infection_spreads_one_step_per_tick() {
+        // Infection with probability 1 per infected neighbor is a deterministic automaton
+        let solution = synchronous_solver(
+            Box::new(SIProcess { birth_rate: 1.0, death_rate: 0.0, spontaneous_rate: 0.0 }),
+            Box::new(GridND::from((vec![7], vec![false]))),
+            vec![0, 0, 0, 1, 0, 0, 0],
+            1,
+            RecordCondition::Final(),
+            StdRng::seed_from_u64(1),
+        );
+
+        assert_eq!(solution.steps_taken, 1);
+        assert_eq!(solution.final_state, vec![0, 0, 1, 1, 1, 0, 0]);
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use rand::distributions::{Distribution, WeightedError, WeightedIndex};
+use rand::Rng;
+
+use crate::solver::{count_states, HaltCondition, RecordCondition, Solution};
+use crate::solver::exponential_distribution::StandardExponential;
+use crate::solver::graph::Graph;
+use crate::solver::ips_rules::IPSRules;
+
+/// Approximate interacting particle system simulator using tau-leaping. Instead of simulating every
+/// event exactly as `particle_system_solver` does, time is advanced in windows of fixed length
+/// `tau`. At the start of each window the reactivities are computed, and every particle undergoes a
+/// Poisson-distributed number of transitions (with mean its reactivity times `tau`). All particles
+/// are updated from the state at the start of the window, so the neighborhoods are frozen during a
+/// window.
+///
+/// This is much faster for large systems, since the cost per window is linear in the number of
+/// particles, however many events happen in it, and there is no bookkeeping of a location
+/// distribution. The price is accuracy: the approximation is only good if the neighborhoods hardly
+/// change within a window, i.e., if `tau` times the typical reactivity is small. Correlations
+/// between neighboring events within a window are lost (e.g., an infection can't spread two sites
+/// in a single window), and for large `tau` the dynamics are qualitatively different. Halve `tau`
+/// and compare to check whether it is small enough.
+///
+/// # Parameters
+/// Same as `particle_system_solver`, except:
+/// * `tau`: The length of the time windows.
+/// * Steps are windows: `steps_taken` counts the windows, and the record and halting conditions
+///   are evaluated once per window.
+///
+/// # Outputs
+/// A `Solution` in the same format as that of `particle_system_solver`, without reactivity trace
+/// and events.
+pub fn particle_system_solver_tau_leap<R: Rng>(
+    ips_rules: Box<dyn IPSRules>,
+    graph: Box<dyn Graph>,
+    initial_condition: Vec<usize>,
+    halting_condition: HaltCondition,
+    record_condition: RecordCondition,
+    tau: f64,
+    mut rng: R,
+) -> Solution {
+    // * PHASE I: Initialization * //
+
+    assert!(tau > 0.0);
+
+    let mut states: Vec<usize> = initial_condition;
+    assert_eq!(states.len(), graph.nr_points());
+
+    // The neighbors don't change, so look them up only once
+    let neighbors: Vec<Vec<usize>> = (0..graph.nr_points())
+        .map(|i| graph.get_neighbors(i).into_iter().collect())
+        .collect();
+
+    // Initialize state record
+    let mut states_record: Vec<usize> = vec![];
+    let mut times: Vec<f64> = vec![];
+
+    // Initialize timekeeping
+    let mut time_passed = 0.0;
+    let mut time_last_recorded = 0.0;
+    let mut time_last_infection = 0.0;
+    let mut steps_recorded = 0; // Number of snapshots in states_record
+    let mut steps_taken = 0;
+
+    // * PHASE 2: Simulation loop * //
+    while halting_condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_infection) {
+        /* Compute the neighbor counts and reactivities at the start of the window */
+        let neigh_state_counts: Vec<HashMap<usize, usize>> = neighbors.iter()
+            .map(|neighs| {
+                let mut counts: HashMap<usize, usize> = HashMap::new();
+                for j in neighs {
+                    *counts.entry(states[*j]).or_insert(0) += 1;
+                }
+                counts
+            })
+            .collect();
+
+        let reactivities: Vec<f64> = (0..states.len())
+            .map(|i| ips_rules.get_reactivity(states[i], &neigh_state_counts[i]))
+            .collect();
+        let total_reactivity: f64 = reactivities.iter().sum();
+
+        if total_reactivity <= 0.0 { // No more reaction is possible
+            break;
+        }
+
+        /* Update timekeeping */
+        steps_taken += 1;
+        time_passed += tau;
+
+        /* Record the state */
+        // As in `particle_system_solver`, the state during the window is recorded before the update
+        let mean_reactivity = total_reactivity / states.len() as f64;
+        for _ in 0..record_condition.how_often_record(time_passed, tau, steps_taken,
+                                                      time_last_recorded, mean_reactivity) {
+            states_record.extend_from_slice(&states);
+            times.push(time_passed);
+            time_last_recorded = time_passed;
+            steps_recorded += 1;
+            if !halting_condition.should_continue(time_passed, steps_recorded, steps_taken, time_last_infection) {
+                break;
+            }
+        }
+
+        /* Leap: let every particle undergo its events of the window */
+        let mut new_states = states.clone();
+        for (i, new_state) in new_states.iter_mut().enumerate() {
+            let nr_events = sample_poisson(reactivities[i] * tau, &mut rng);
+
+            for _ in 0..nr_events {
+                // The neighbors are frozen, but the particle itself may have changed already
+                let change_rates: Vec<f64> = ips_rules.all_states().into_iter()
+                    .map(|to_state| ips_rules.get_mutation_rate(*new_state, to_state, &neigh_state_counts[i]))
+                    .collect();
+
+                match WeightedIndex::new(change_rates) {
+                    Ok(distr_to_state) => { *new_state = distr_to_state.sample(&mut rng); }
+                    Err(WeightedError::AllWeightsZero) => { break; } // The particle can't change anymore
+                    Err(other) => { panic!("Strange error! {:?}", other) }
+                };
+            }
+
+            if states[i] == 0 && *new_state != 0 {
+                time_last_infection = time_passed;
+            }
+        }
+        states = new_states;
+    }
+
+    // * PHASE III: Cleanup * //
+
+    // Record final state
+    states_record.extend_from_slice(&states);
+    times.push(time_passed);
+    steps_recorded += 1;
+
+    Solution {
+        snapshots: states_record,
+        times,
+        recorded_indices: None,
+        final_state_counts: count_states(&states),
+        final_state: states,
+        time_simulated: time_passed,
+        steps_recorded,
+        steps_taken,
+        reactivity_trace: None,
+        events: vec![],
+    }
+}
+
+/// Sample from the Poisson distribution with mean `lambda`, by counting the arrivals of a rate 1
+/// Poisson process in the time interval `[0, lambda]`. Takes time linear in `lambda`, which is
+/// small when tau-leaping is accurate.
+fn sample_poisson<R: Rng>(lambda: f64, rng: &mut R) -> usize {
+    let mut nr_arrivals = 0;
+    let mut time: f64 = 0.0;
+
+    loop {
+        let standard_exp_object: StandardExponential = rng.gen();
+        time += standard_exp_object.0;
+        if time > lambda {
+            return nr_arrivals;
+        }
+        nr_arrivals += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::solver::{particle_system_solver, SolverOptions};
+    use crate::solver::graph::grid_n_d::GridND;
+    use crate::solver::ips_rules::si_process::SIProcess;
+    use super::*;
+
+    #[test]
+    fn mean_counts_close_to_exact_solver() {
+        let rules = || Box::new(SIProcess { birth_rate: 0.5, death_rate: 1.0, spontaneous_rate: 0.25 });
+        let initial_condition: Vec<usize> = (0..100).map(|i| i % 2).collect();
+        let nr_runs = 30;
+
+        let mut exact_infected = 0;
+        let mut tau_leap_infected = 0;
+        for run in 0..nr_runs {
+            let exact = particle_system_solver(rules(), Box::new(GridND::from(vec![10, 10])), initial_condition.clone(),
+                                               HaltCondition::TimePassed(2.0), RecordCondition::Final(),
+                                               SolverOptions::default(), StdRng::seed_from_u64(run));
+            let tau_leap = particle_system_solver_tau_leap(rules(), Box::new(GridND::from(vec![10, 10])),
+                                                           initial_condition.clone(), HaltCondition::TimePassed(2.0),
+                                                           RecordCondition::Final(), 0.02, StdRng::seed_from_u64(run));
+            exact_infected += exact.final_state_counts.get(&1).unwrap_or(&0);
+            tau_leap_infected += tau_leap.final_state_counts.get(&1).unwrap_or(&0);
+        }
+
+        let exact_mean = exact_infected as f64 / nr_runs as f64;
+        let tau_leap_mean = tau_leap_infected as f64 / nr_runs as f64;
+        assert!((exact_mean - tau_leap_mean).abs() < 0.15 * exact_mean, "{} versus {}", exact_mean, tau_leap_mean);
+    }
+}
@@ -0,0 +1,54 @@
+use image::{Rgba, RgbaImage};
+
+/// Width of a glyph in pixels.
+const GLYPH_WIDTH: u32 = 3;
+/// Height of a glyph in pixels.
+const GLYPH_HEIGHT: u32 = 5;
+
+/// The 3x5 glyph of a character, as rows from top to bottom where the three lowest bits are the
+/// pixels from left to right. Only the characters needed for numbers are available.
+fn glyph(character: char) -> Option<[u8; 5]> {
+    match character {
+        '0' => Some([0b111, 0b101, 0b101, 0b101, 0b111]),
+        '1' => Some([0b010, 0b110, 0b010, 0b010, 0b111]),
+        '2' => Some([0b111, 0b001, 0b111, 0b100, 0b111]),
+        '3' => Some([0b111, 0b001, 0b111, 0b001, 0b111]),
+        '4' => Some([0b101, 0b101, 0b111, 0b001, 0b001]),
+        '5' => Some([0b111, 0b100, 0b111, 0b001, 0b111]),
+        '6' => Some([0b111, 0b100, 0b111, 0b101, 0b111]),
+        '7' => Some([0b111, 0b001, 0b001, 0b001, 0b001]),
+        '8' => Some([0b111, 0b101, 0b111, 0b101, 0b111]),
+        '9' => Some([0b111, 0b101, 0b111, 0b001, 0b111]),
+        '.' => Some([0b000, 0b000, 0b000, 0b000, 0b010]),
+        '-' => Some([0b000, 0b000, 0b111, 0b000, 0b000]),
+        _ => None,
+    }
+}
+
+/// Draw `text` in white on a black background into the top left corner of `buffer`. Characters
+/// without a glyph are skipped, and the text is clipped at the border of the buffer.
+pub fn draw_text(buffer: &mut RgbaImage, text: &str) {
+    let glyphs: Vec<[u8; 5]> = text.chars().filter_map(glyph).collect();
+
+    // One pixel of margin around the text and between the glyphs
+    let width = glyphs.len() as u32 * (GLYPH_WIDTH + 1) + 1;
+    let height = GLYPH_HEIGHT + 2;
+
+    for y in 0..height.min(buffer.height()) {
+        for x in 0..width.min(buffer.width()) {
+            buffer.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+        }
+    }
+
+    for (index, rows) in glyphs.iter().enumerate() {
+        let left = 1 + index as u32 * (GLYPH_WIDTH + 1);
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                let (x, y) = (left + column, 1 + row as u32);
+                if bits >> (GLYPH_WIDTH - 1 - column) & 1 == 1 && x < buffer.width() && y < buffer.height() {
+                    buffer.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+                }
+            }
+        }
+    }
+}
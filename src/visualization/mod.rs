@@ -1,6 +1,12 @@
-use std::fs::File;
+use std::collections::HashMap;
+use std::fs::{create_dir_all, File};
+use std::path::Path;
 use image::codecs::gif::{GifEncoder, Repeat};
-use image::{Delay, Frame, ImageBuffer};
+use image::{Delay, Frame, ImageBuffer, ImageError, ImageResult};
+use image::error::{ParameterError, ParameterErrorKind};
+use crate::solver::{Event, Solution};
+
+mod bitmap_font;
 
 /// Color trait to be implemented on a particle system enum. Implements the `get_color` trait.
 pub trait Coloration {
@@ -9,14 +15,71 @@ pub trait Coloration {
     fn get_color(&self, state: usize) -> [u8; 4];
 }
 
+impl<C: Coloration + ?Sized> Coloration for Box<C> {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        (**self).get_color(state)
+    }
+}
+
+/// Coloration wrapper which overrides the color of the background state 0 (black for many
+/// processes), and defers to `inner` for all other states.
+/// # Example
+/// Draw the empty sites of the forest-fire model in white
+/// ```
+/// let coloration = RecoloredBackground { inner: forest_fire, background: [255, 255, 255, 255] };
+/// ```
+pub struct RecoloredBackground<C: Coloration> {
+    pub inner: C,
+    pub background: [u8; 4],
+}
+
+impl<C: Coloration> Coloration for RecoloredBackground<C> {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        if state == 0 {
+            self.background
+        } else {
+            self.inner.get_color(state)
+        }
+    }
+}
+
+/// Coloration given by a map from states to colors, for arbitrary sets of states without writing
+/// a `Coloration` implementation (e.g., to highlight a few strains of the multistrain process).
+/// States which are not in `map` are drawn in the color `default`.
+/// # Example
+/// Draw the state 3 in red, and all other states in gray
+/// ```
+/// let coloration = MapColoration { map: HashMap::from([(3, [255, 0, 0, 255])]), default: [97, 97, 97, 255] };
+/// ```
+pub struct MapColoration {
+    pub map: HashMap<usize, [u8; 4]>,
+    pub default: [u8; 4],
+}
+
+impl Coloration for MapColoration {
+    fn get_color(&self, state: usize) -> [u8; 4] {
+        self.map.get(&state).copied().unwrap_or(self.default)
+    }
+}
+
 /// Visualize the input solution as a graph over time. Best suited for 1D graphs (lines or circles).
 ///
 /// # Parameters
 /// * `solution`: Vector containing the state record. Format should be the same as the output of
 /// `particle_system_solver`.
 /// * `img_name`: &str of the image to be saved. Should end in ".png".
-/// * `img_x`: Width of the simulation, i.e., number of points in the graph.
-pub fn save_as_growth_img(coloration: Box<dyn Coloration>, solution: Vec<usize>, img_name: &str, img_x: u32) {
+/// * `img_x`: Width of the simulation, i.e., number of points in the graph (or in the recorded
+///   region).
+///
+/// # Errors
+/// Returns a dimension mismatch error if the length of the solution is not a multiple of `img_x`,
+/// i.e., if the last row would be partial (this signals a wrong `img_x`). Otherwise returns the
+/// error of saving the image, if any.
+pub fn save_as_growth_img(coloration: Box<dyn Coloration>, solution: Vec<usize>, img_name: &str, img_x: u32) -> ImageResult<()> {
+    if img_x == 0 || !solution.len().is_multiple_of(img_x as usize) {
+        return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+    }
+
     // y is the time axis, so the y-range should be the length of the simulation
     let img_y = (solution.len() as u32) / img_x;
 
@@ -26,7 +89,7 @@ pub fn save_as_growth_img(coloration: Box<dyn Coloration>, solution: Vec<usize>,
         *pixel = image::Rgba(coloration.get_color(*solution.get((x + img_x * y) as usize).unwrap()))
     }
 
-    img_buf.save(img_name).unwrap(); // Unwrap to make sure it panics on errors
+    img_buf.save(img_name)
 }
 
 /// Visualize the input solution as a graph over time. Best suited for 2D graphs (rectangles,
@@ -36,19 +99,31 @@ pub fn save_as_growth_img(coloration: Box<dyn Coloration>, solution: Vec<usize>,
 /// * `solution`: Vector containing the state record. Format should be the same as the output of
 /// `particle_system_solver`.
 /// * `img_name`: &str of the image to be saved. Should end in ".gif".
-/// * `img_x`: Width of the graph.
-/// * `img_y`: Height of the graph.
+/// * `dims`: Width and height `[x, y]` of the graph.
+/// * `nr_points`: Number of points in each snapshot, i.e., in the graph (or in the recorded
+///   region).
 /// * `ms_per_frame`: Number of milliseconds each frame (i.e., snapshot) should be displayed in the
 /// output gif.
-pub fn save_as_gif(coloration: Box<dyn Coloration>, solution: Vec<usize>, img_name: &str, img_x: u32, img_y: u32, ms_per_frame: u32) {
-    let file_out = File::create(img_name).unwrap();
-
-    let mut encoder = GifEncoder::new_with_speed(file_out, 30);
-
-    // Gif config: repeat once (does not work)
-    encoder.set_repeat(Repeat::Finite(1)).unwrap();
+/// * `loop_forever`: Whether the gif should loop indefinitely. Otherwise it is replayed once.
+/// * `timestamps`: If given, the simulated time of each snapshot, which is then written in the top
+///   left corner of its frame.
+///
+/// # Errors
+/// Returns a dimension mismatch error if the width times the height is not the number of points
+/// (the graph would be drawn as garbage), if the length of the solution is not a multiple of the
+/// number of points, or if there is not a timestamp for every snapshot. Otherwise returns the error
+/// of saving the gif, if any.
+#[allow(clippy::too_many_arguments)]
+pub fn save_as_gif(coloration: Box<dyn Coloration>, solution: Vec<usize>, img_name: &str, dims: [u32; 2], nr_points: usize, ms_per_frame: u32, loop_forever: bool, timestamps: Option<&[f64]>) -> ImageResult<()> {
+    let [img_x, img_y] = dims;
+    if nr_points == 0 || (img_x * img_y) as usize != nr_points || !solution.len().is_multiple_of(nr_points) {
+        return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+    }
 
-    let nr_frames = solution.len() / (img_x * img_y) as usize;
+    let nr_frames = solution.len() / nr_points;
+    if timestamps.is_some_and(|times| times.len() != nr_frames) {
+        return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+    }
 
     // convert solution into color frames
     let mut frames: Vec<Frame> = Vec::new();
@@ -57,10 +132,583 @@ pub fn save_as_gif(coloration: Box<dyn Coloration>, solution: Vec<usize>, img_na
         for (x, y, pixel) in buffer.enumerate_pixels_mut() {
             *pixel = image::Rgba(coloration.get_color(*solution.get((x + img_x * y + (frame_index as u32 * img_x * img_y)) as usize).unwrap()))
         }
-        let frame = Frame::from_parts(buffer, img_x, img_x, Delay::from_numer_denom_ms(ms_per_frame, 1));
+        if let Some(times) = timestamps {
+            bitmap_font::draw_text(&mut buffer, &format!("{:.1}", times[frame_index]));
+        }
+        let frame = Frame::from_parts(buffer, 0, 0, Delay::from_numer_denom_ms(ms_per_frame, 1));
         frames.push(frame);
     }
 
     // finally encode
-    encoder.encode_frames(&mut frames.into_iter()).unwrap();
-}
\ No newline at end of file
+    encode_gif(frames, img_name, loop_forever)
+}
+
+/// Visualize the input solution of a 3D grid as a gif over time, by laying out the z-slices of
+/// each snapshot side by side (the slice with z = 0 on the left).
+///
+/// # Parameters
+/// * `solution`: Vector containing the state record. Format should be the same as the output of
+///   `particle_system_solver`.
+/// * `dims`: The dimensions `[x, y, z]` of the grid. The frames are `x * z` wide and `y` high.
+/// * `img_name`: &str of the image to be saved. Should end in ".gif".
+/// * `ms_per_frame`: Number of milliseconds each frame (i.e., snapshot) should be displayed in the
+///   output gif.
+/// * `loop_forever`: Whether the gif should loop indefinitely. Otherwise it is replayed once.
+///
+/// # Errors
+/// Returns a dimension mismatch error if the length of the solution is not a multiple of the
+/// number of points of the grid. Otherwise returns the error of saving the gif, if any.
+pub fn save_as_slice_montage(coloration: Box<dyn Coloration>, solution: Vec<usize>, dims: [u32; 3], img_name: &str, ms_per_frame: u32, loop_forever: bool) -> ImageResult<()> {
+    let [dim_x, dim_y, dim_z] = dims;
+    let points_per_frame = (dim_x * dim_y * dim_z) as usize;
+    if points_per_frame == 0 || !solution.len().is_multiple_of(points_per_frame) {
+        return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+    }
+
+    let nr_frames = solution.len() / points_per_frame;
+
+    // convert solution into color frames
+    let mut frames: Vec<Frame> = Vec::new();
+    for frame_index in 0..nr_frames {
+        let mut buffer = ImageBuffer::new(dim_x * dim_z, dim_y);
+        for (x, y, pixel) in buffer.enumerate_pixels_mut() {
+            // The pixel column determines both the slice and the x coordinate within the slice
+            let (z, x_in_slice) = (x / dim_x, x % dim_x);
+            let particle = (x_in_slice + dim_x * y + dim_x * dim_y * z) as usize + frame_index * points_per_frame;
+            *pixel = image::Rgba(coloration.get_color(solution[particle]))
+        }
+        let frame = Frame::from_parts(buffer, 0, 0, Delay::from_numer_denom_ms(ms_per_frame, 1));
+        frames.push(frame);
+    }
+
+    encode_gif(frames, img_name, loop_forever)
+}
+
+/// Encode frames into a gif, and save it as `img_name`.
+fn encode_gif(frames: Vec<Frame>, img_name: &str, loop_forever: bool) -> ImageResult<()> {
+    let file_out = File::create(img_name)?;
+
+    let mut encoder = GifEncoder::new_with_speed(file_out, 30);
+
+    // Gif config: the repeat setting is stored in the encoder, and written as a loop extension
+    // together with the first frame, so it has to be set before encoding.
+    if loop_forever {
+        encoder.set_repeat(Repeat::Infinite)?;
+    } else {
+        encoder.set_repeat(Repeat::Finite(1))?;
+    }
+
+    encoder.encode_frames(frames)
+}
+
+/// Save every snapshot of the input solution as a separate png image, for assembling into a video
+/// with external tools (e.g., `ffmpeg -i frame_%05d.png out.mp4`). Best suited for 2D graphs.
+///
+/// # Parameters
+/// * `solution`: Vector containing the state record. Format should be the same as the output of
+///   `particle_system_solver`.
+/// * `dir`: Directory in which the frames `frame_00000.png`, `frame_00001.png`, ... are saved.
+///   Created if it does not exist.
+/// * `img_x`: Width of the graph.
+/// * `img_y`: Height of the graph.
+/// * `scale`: Every particle is drawn as a square of `scale` by `scale` pixels.
+///
+/// # Errors
+/// Returns a dimension mismatch error if the length of the solution is not a multiple of the width
+/// times the height. Otherwise returns the error of saving the frames, if any.
+pub fn save_frames_as_pngs(coloration: Box<dyn Coloration>, solution: Vec<usize>, dir: &str, img_x: u32, img_y: u32, scale: u32) -> ImageResult<()> {
+    if img_x * img_y == 0 || !solution.len().is_multiple_of((img_x * img_y) as usize) {
+        return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+    }
+    create_dir_all(dir)?;
+
+    let nr_frames = solution.len() / (img_x * img_y) as usize;
+
+    for frame_index in 0..nr_frames {
+        let mut buffer = ImageBuffer::new(img_x * scale, img_y * scale);
+        for (x, y, pixel) in buffer.enumerate_pixels_mut() {
+            let particle = (x / scale + img_x * (y / scale)) as usize + frame_index * (img_x * img_y) as usize;
+            *pixel = image::Rgba(coloration.get_color(solution[particle]))
+        }
+        buffer.save(Path::new(dir).join(format!("frame_{:05}.png", frame_index)))?;
+    }
+
+    Ok(())
+}
+
+/// Save only the final state of the input solution as a png image. Best suited for 2D graphs,
+/// where it shows the configuration the simulation ended in, whatever was recorded before.
+///
+/// # Parameters
+/// * `solution`: Output of `particle_system_solver`. The final state is of the whole graph, also if
+///   only a region was recorded.
+/// * `path`: Path of the image to be saved. Should end in ".png".
+/// * `img_x`: Width of the graph.
+/// * `img_y`: Height of the graph.
+/// * `scale`: Every particle is drawn as a square of `scale` by `scale` pixels, so the image is
+///   `img_x * scale` by `img_y * scale` pixels.
+///
+/// # Errors
+/// Returns a dimension mismatch error if the width times the height is not the number of
+/// particles in the graph. Otherwise returns the error of saving the image, if any.
+pub fn save_final_as_image(coloration: &dyn Coloration, solution: &Solution, path: &str, img_x: u32, img_y: u32, scale: u32) -> ImageResult<()> {
+    if (img_x * img_y) as usize != solution.final_state.len() {
+        return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+    }
+
+    let mut buffer = ImageBuffer::new(img_x * scale, img_y * scale);
+    for (x, y, pixel) in buffer.enumerate_pixels_mut() {
+        let particle = (x / scale + img_x * (y / scale)) as usize;
+        *pixel = image::Rgba(coloration.get_color(solution.final_state[particle]))
+    }
+
+    buffer.save(path)
+}
+
+/// For every particle, find the first time at which it was recorded in the state `infected`.
+///
+/// # Parameters
+/// * `solution`: Vector containing the state record. Format should be the same as the output of
+///   `particle_system_solver`.
+/// * `times`: The time at which each snapshot in `solution` was taken.
+/// * `nr_points`: Number of points in the graph.
+/// * `infected`: The state of interest.
+///
+/// # Output
+/// A vector containing the infection time of each particle, or `None` if the particle was never
+/// recorded in the state `infected`.
+pub fn time_to_infection_field(solution: &[usize], times: &[f64], nr_points: usize, infected: usize) -> Vec<Option<f64>> {
+    assert_eq!(solution.len(), times.len() * nr_points); // one time per snapshot
+
+    let mut field: Vec<Option<f64>> = vec![None; nr_points];
+
+    for (snapshot, time) in solution.chunks(nr_points).zip(times) {
+        for (particle, state) in snapshot.iter().enumerate() {
+            if *state == infected && field[particle].is_none() {
+                field[particle] = Some(*time);
+            }
+        }
+    }
+
+    field
+}
+
+/// Visualize a field of times (e.g., the output of `time_to_infection_field`) as a heatmap. Early
+/// times are colored yellow, late times purple, and missing times black. Best suited for 2D graphs.
+///
+/// # Parameters
+/// * `field`: Vector containing a time (or `None`) for every particle.
+/// * `img_name`: &str of the image to be saved. Should end in ".png".
+/// * `img_x`: Width of the graph.
+/// * `img_y`: Height of the graph.
+///
+/// # Errors
+/// Returns a dimension mismatch error if the field doesn't have a time for every point of the
+/// `img_x` by `img_y` image. Otherwise returns the error of saving the image, if any.
+pub fn save_as_heatmap(field: &[Option<f64>], img_name: &str, img_x: u32, img_y: u32) -> ImageResult<()> {
+    if field.len() != (img_x * img_y) as usize {
+        return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+    }
+
+    let early: [f64; 3] = [253.0, 231.0, 37.0]; // yellow
+    let late: [f64; 3] = [68.0, 1.0, 84.0]; // purple
+
+    // Normalize the times by the range of the field
+    let min_time = field.iter().flatten().cloned().fold(f64::INFINITY, f64::min);
+    let max_time = field.iter().flatten().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+    let mut img_buf = image::ImageBuffer::new(img_x, img_y);
+
+    for (x, y, pixel) in img_buf.enumerate_pixels_mut() {
+        *pixel = match field[(x + img_x * y) as usize] {
+            Some(time) => {
+                let fraction = if max_time > min_time { (time - min_time) / (max_time - min_time) } else { 0.0 };
+                let mut color = [0u8, 0, 0, 255];
+                for channel in 0..3 {
+                    color[channel] = (early[channel] + fraction * (late[channel] - early[channel])).round() as u8;
+                }
+                image::Rgba(color)
+            }
+            None => { image::Rgba([0, 0, 0, 255]) }
+        }
+    }
+
+    img_buf.save(img_name)
+}
+
+/// Visualize where the particle system was active: every particle is colored by the number of
+/// times it changed state, from black (never) to bright yellow (the most changes of any
+/// particle). Best suited for 2D graphs.
+///
+/// # Parameters
+/// * `dims`: Width and height `[x, y]` of the graph.
+/// * `events`: The transitions of the simulation, see `SolverOptions::record_events`.
+/// * `img_name`: &str of the image to be saved. Should end in ".png".
+///
+/// # Errors
+/// Returns a dimension mismatch error if some event happened at a particle outside of the
+/// `x` by `y` image. Otherwise returns the error of saving the image, if any.
+pub fn save_activity_heatmap(dims: [u32; 2], events: &[Event], img_name: &str) -> ImageResult<()> {
+    let [img_x, img_y] = dims;
+    let nr_points = (img_x * img_y) as usize;
+    if events.iter().any(|event| event.site >= nr_points) {
+        return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+    }
+
+    let active: [f64; 3] = [253.0, 231.0, 37.0]; // yellow
+
+    // Count the number of changes of every particle
+    let mut activity: Vec<usize> = vec![0; nr_points];
+    for event in events {
+        activity[event.site] += 1;
+    }
+    let max_activity = activity.iter().max().copied().unwrap_or(0).max(1); // don't divide by zero
+
+    let mut img_buf = image::ImageBuffer::new(img_x, img_y);
+
+    for (x, y, pixel) in img_buf.enumerate_pixels_mut() {
+        let fraction = activity[(x + img_x * y) as usize] as f64 / max_activity as f64;
+        let mut color = [0u8, 0, 0, 255];
+        for channel in 0..3 {
+            color[channel] = (fraction * active[channel]).round() as u8;
+        }
+        *pixel = image::Rgba(color);
+    }
+
+    img_buf.save(img_name)
+}
+
+/// Width and height (in pixels) of the plots of `save_counts_plot`.
+const PLOT_SIZE: [u32; 2] = [640, 320];
+
+/// Plot the number of particles in each state against time, as a line for every state in the color
+/// of that state, on a white background. Time runs from the earliest to the latest snapshot from
+/// left to right, and the count from 0 to all particles (of a snapshot) from bottom to top. There are
+/// no axes, since the range is fixed.
+///
+/// # Parameters
+/// * `solution`: The output of `particle_system_solver`, or any other solver.
+/// * `all_states`: The states to plot, e.g., `ips_rules.all_states()`.
+/// * `coloration`: The coloration of the states.
+/// * `img_name`: &str of the image to be saved. Should end in ".png" (or ".gif").
+///
+/// # Errors
+/// Returns a dimension mismatch error if the snapshots are empty or don't match the number of
+/// recorded times. Otherwise returns the error of saving the image, if any.
+pub fn save_counts_plot(solution: &Solution, all_states: &[usize], coloration: &dyn Coloration, img_name: &str) -> ImageResult<()> {
+    let nr_snapshots = solution.times.len();
+    if nr_snapshots == 0 || solution.snapshots.is_empty() || !solution.snapshots.len().is_multiple_of(nr_snapshots) {
+        return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+    }
+    let snapshot_len = solution.snapshots.len() / nr_snapshots;
+
+    let [img_x, img_y] = PLOT_SIZE;
+    let mut img_buf = ImageBuffer::from_pixel(img_x, img_y, image::Rgba([255u8, 255, 255, 255]));
+
+    // Pixel coordinates of a time and a count
+    let first_time = solution.times.iter().cloned().fold(f64::INFINITY, f64::min);
+    let last_time = solution.times.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let to_x = |time: f64| -> i64 {
+        if last_time > first_time {
+            ((time - first_time) / (last_time - first_time) * (img_x - 1) as f64).round() as i64
+        } else {
+            0
+        }
+    };
+    let to_y = |count: usize| -> i64 {
+        ((1.0 - count as f64 / snapshot_len as f64) * (img_y - 1) as f64).round() as i64
+    };
+
+    for &state in all_states {
+        let color = image::Rgba(coloration.get_color(state));
+        let points: Vec<(i64, i64)> = solution.snapshots.chunks(snapshot_len).zip(&solution.times)
+            .map(|(snapshot, &time)| (to_x(time), to_y(snapshot.iter().filter(|&&s| s == state).count())))
+            .collect();
+
+        draw_line(&mut img_buf, points[0], points[0], color); // in case there is only one snapshot
+        for segment in points.windows(2) {
+            draw_line(&mut img_buf, segment[0], segment[1], color);
+        }
+    }
+
+    img_buf.save(img_name)
+}
+
+/// Draw a line segment between two pixels, by Bresenham's algorithm.
+fn draw_line(img_buf: &mut ImageBuffer<image::Rgba<u8>, Vec<u8>>, from: (i64, i64), to: (i64, i64), color: image::Rgba<u8>) {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let step_x = if x < to.0 { 1 } else { -1 };
+    let step_y = if y < to.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    loop {
+        img_buf.put_pixel(x as u32, y as u32, color);
+        if (x, y) == to {
+            break;
+        }
+        let double_error = 2 * error;
+        if double_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if double_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::AnimationDecoder;
+    use image::codecs::gif::GifDecoder;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::solver::{HaltCondition, particle_system_solver, RecordCondition, SolverOptions};
+    use crate::solver::graph::grid_n_d::GridND;
+    use crate::solver::ips_rules::si_process::SIProcess;
+    use super::*;
+
+    /// Draws state `s` in the gray `[s, s, s, 255]`.
+    struct Gray;
+
+    impl Coloration for Gray {
+        fn get_color(&self, state: usize) -> [u8; 4] {
+            [state as u8, state as u8, state as u8, 255]
+        }
+    }
+
+    fn is_dimension_mismatch<T>(result: ImageResult<T>) -> bool {
+        matches!(result, Err(ImageError::Parameter(error))
+            if error.kind() == ParameterErrorKind::DimensionMismatch)
+    }
+
+    #[test]
+    fn background_override_only_for_state_0() {
+        let coloration = RecoloredBackground { inner: Gray, background: [255, 0, 0, 255] };
+
+        assert_eq!(coloration.get_color(0), [255, 0, 0, 255]);
+        assert_eq!(coloration.get_color(1), Gray.get_color(1));
+        assert_eq!(coloration.get_color(7), Gray.get_color(7));
+    }
+
+    #[test]
+    fn growth_image_rows_should_be_complete() {
+        let path = std::env::temp_dir().join("rust_particle_system_growth.png");
+
+        // Three snapshots of a line of four points
+        save_as_growth_img(Box::new(Gray), (0..12).collect(), path.to_str().unwrap(), 4).unwrap();
+
+        let image = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (4, 3));
+        assert_eq!(image.get_pixel(1, 2).0, Gray.get_color(9));
+        std::fs::remove_file(&path).unwrap();
+        assert!(is_dimension_mismatch(save_as_growth_img(Box::new(Gray), vec![0; 10], path.to_str().unwrap(), 4)));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn gif_dimensions_should_multiply_to_nr_points() {
+        let path = std::env::temp_dir().join("rust_particle_system_mismatch.gif");
+
+        // Two snapshots of six points, drawn as if the graph were 4 by 2
+        let result = save_as_gif(Box::new(Gray), vec![0; 12], path.to_str().unwrap(), [4, 2], 6, 10,
+                                 false, None);
+
+        assert!(is_dimension_mismatch(result));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn heatmap_field_should_cover_image() {
+        let path = std::env::temp_dir().join("rust_particle_system_mismatch_heatmap.png");
+
+        let result = save_as_heatmap(&[Some(0.0), None, Some(1.0)], path.to_str().unwrap(), 2, 2);
+
+        assert!(is_dimension_mismatch(result));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn png_frames_dimensions_should_divide_solution() {
+        let dir = std::env::temp_dir().join("rust_particle_system_mismatch_frames");
+
+        let result = save_frames_as_pngs(Box::new(Gray), vec![0; 12], dir.to_str().unwrap(), 5, 1, 1);
+
+        assert!(is_dimension_mismatch(result));
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn final_image_is_of_whole_graph() {
+        let path = std::env::temp_dir().join("rust_particle_system_final.png");
+
+        // Only record a region, the final state is still of the whole 3 by 2 grid
+        let solution = particle_system_solver(
+            Box::new(SIProcess { birth_rate: 1.0, death_rate: 1.0, spontaneous_rate: 0.0 }),
+            Box::new(GridND::from(vec![3, 2])),
+            vec![1, 0, 0, 0, 0, 0],
+            HaltCondition::StepsTaken(3),
+            RecordCondition::Final(),
+            SolverOptions { record_region: Some(vec![0, 1]), ..SolverOptions::default() },
+            StdRng::seed_from_u64(1),
+        );
+        save_final_as_image(&Gray, &solution, path.to_str().unwrap(), 3, 2, 4).unwrap();
+
+        let image = image::open(&path).unwrap();
+        assert_eq!((image.width(), image.height()), (12, 8));
+        std::fs::remove_file(path).unwrap();
+        assert!(is_dimension_mismatch(save_final_as_image(&Gray, &solution, "unused.png", 2, 1, 4)));
+    }
+
+    #[test]
+    fn slice_montage_of_2x2x2_grid() {
+        let path = std::env::temp_dir().join("rust_particle_system_montage.gif");
+
+        save_as_slice_montage(Box::new(Gray), (0..16).collect(), [2, 2, 2], path.to_str().unwrap(), 10, false)
+            .unwrap();
+
+        // The two slices of the first frame side by side, particle (x, y, z) in column 2z + x
+        let image = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(image.get_pixel(1, 0).0, Gray.get_color(1));
+        assert_eq!(image.get_pixel(2, 0).0, Gray.get_color(4));
+        assert_eq!(image.get_pixel(3, 1).0, Gray.get_color(7));
+        std::fs::remove_file(&path).unwrap();
+        assert!(is_dimension_mismatch(
+            save_as_slice_montage(Box::new(Gray), vec![0; 12], [2, 2, 2], path.to_str().unwrap(), 10, false)
+        ));
+    }
+
+    #[test]
+    fn png_frame_per_snapshot() {
+        let dir = std::env::temp_dir().join("rust_particle_system_frames");
+
+        // Three snapshots of a 3 by 2 grid
+        save_frames_as_pngs(Box::new(Gray), (0..18).collect(), dir.to_str().unwrap(), 3, 2, 2).unwrap();
+
+        let mut names: Vec<String> = std::fs::read_dir(&dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["frame_00000.png", "frame_00001.png", "frame_00002.png"]);
+        let last_frame = image::open(dir.join("frame_00002.png")).unwrap().to_rgba8();
+        assert_eq!(last_frame.dimensions(), (6, 4));
+        assert_eq!(last_frame.get_pixel(5, 3).0, Gray.get_color(17));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn timestamps_keep_gif_frames_and_dimensions() {
+        let path = std::env::temp_dir().join("rust_particle_system_timestamps.gif");
+        let solution: Vec<usize> = (0..3 * 40 * 20).map(|i| i % 7).collect();
+
+        save_as_gif(Box::new(Gray), solution, path.to_str().unwrap(), [40, 20], 800, 10, false, Some(&[0.0, 1.5, 3.0]))
+            .unwrap();
+
+        let decoder = GifDecoder::new(File::open(&path).unwrap()).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), 3);
+        assert!(frames.iter().all(|frame| frame.buffer().dimensions() == (40, 20)));
+        let too_few = save_as_gif(Box::new(Gray), (0..3 * 40 * 20).collect(), path.to_str().unwrap(), [40, 20], 800, 10, false, Some(&[0.0, 1.5]));
+        assert!(is_dimension_mismatch(too_few));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// The loop count in the application extension of the gif `bytes`, if it has one.
+    fn gif_loop_count(bytes: &[u8]) -> Option<u16> {
+        let start = bytes.windows(11).position(|window| window == b"NETSCAPE2.0")?;
+        // Followed by the sub-block size 3, the sub-block id 1 and the count (little endian)
+        let count = &bytes[start + 13..start + 15];
+        Some(u16::from_le_bytes([count[0], count[1]]))
+    }
+
+    #[test]
+    fn gif_loop_extension_when_requested() {
+        let path = std::env::temp_dir().join("rust_particle_system_loop.gif");
+
+        save_as_gif(Box::new(Gray), (0..12).collect(), path.to_str().unwrap(), [3, 2], 6, 10, true, None).unwrap();
+        let looping = std::fs::read(&path).unwrap();
+        save_as_gif(Box::new(Gray), (0..12).collect(), path.to_str().unwrap(), [3, 2], 6, 10, false, None).unwrap();
+        let once = std::fs::read(&path).unwrap();
+
+        assert_eq!(gif_loop_count(&looping), Some(0)); // 0 is forever
+        assert_ne!(gif_loop_count(&once), Some(0));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn infection_times_increase_with_distance() {
+        // On a line segment seeded at one end, the infection can only spread outward
+        let solution = particle_system_solver(
+            Box::new(SIProcess { birth_rate: 1.0, death_rate: 0.0, spontaneous_rate: 0.0 }),
+            Box::new(GridND::from((vec![10], vec![false]))),
+            (0..10).map(|i| (i == 0) as usize).collect(),
+            HaltCondition::StepsTaken(5),
+            RecordCondition::EveryNthStep(1),
+            SolverOptions::default(),
+            StdRng::seed_from_u64(2),
+        );
+
+        let field = time_to_infection_field(&solution.snapshots, &solution.times, 10, 1);
+
+        // Six steps infect six more particles. The final state is recorded at the time of the last
+        // snapshot, so the last two particles share their time.
+        let times: Vec<f64> = field[..7].iter().map(|time| time.unwrap()).collect();
+        assert!(times[..6].windows(2).all(|pair| pair[0] < pair[1]), "{:?}", times);
+        assert_eq!(times[5], times[6]);
+        assert_eq!(field[7..], [None, None, None]);
+    }
+
+    #[test]
+    fn more_active_site_is_brighter() {
+        let path = std::env::temp_dir().join("rust_particle_system_heatmap.png");
+        // Particle 0 of a 3 by 1 grid changes three times, particle 1 once and particle 2 never
+        let events: Vec<Event> = [0, 1, 0, 0].iter().enumerate()
+            .map(|(i, &site)| Event { time: i as f64, site, old_state: i % 2, new_state: (i + 1) % 2 })
+            .collect();
+
+        save_activity_heatmap([3, 1], &events, path.to_str().unwrap()).unwrap();
+
+        let heatmap = image::open(&path).unwrap().to_rgba8();
+        let brightness = |x: u32| heatmap.get_pixel(x, 0).0[..3].iter().map(|&c| c as u32).sum::<u32>();
+        assert!(brightness(0) > brightness(1));
+        assert!(brightness(1) > brightness(2));
+        assert!(is_dimension_mismatch(save_activity_heatmap([1, 1], &events, path.to_str().unwrap())));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn counts_plot_has_plot_size_and_lines() {
+        let path = std::env::temp_dir().join("rust_particle_system_counts.png");
+        let solution = particle_system_solver(
+            Box::new(SIProcess { birth_rate: 1.0, death_rate: 0.5, spontaneous_rate: 0.0 }),
+            Box::new(GridND::from(vec![10, 10])),
+            (0..100).map(|i| (i == 55) as usize).collect(),
+            HaltCondition::TimePassed(5.0),
+            RecordCondition::ConstantTime(0.1),
+            SolverOptions::default(),
+            StdRng::seed_from_u64(2),
+        );
+
+        save_counts_plot(&solution, &[0, 1], &Gray, path.to_str().unwrap()).unwrap();
+
+        let plot = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(plot.dimensions(), (PLOT_SIZE[0], PLOT_SIZE[1]));
+        for state in [0, 1] {
+            assert!(plot.pixels().any(|pixel| pixel.0 == Gray.get_color(state)), "state {}", state);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unmapped_states_get_default_color() {
+        let coloration = MapColoration { map: HashMap::from([(3, [255, 0, 0, 255])]), default: [97, 97, 97, 255] };
+
+        assert_eq!(coloration.get_color(3), [255, 0, 0, 255]);
+        assert_eq!(coloration.get_color(0), [97, 97, 97, 255]);
+        assert_eq!(coloration.get_color(4), [97, 97, 97, 255]);
+    }
+}